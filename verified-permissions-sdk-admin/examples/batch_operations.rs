@@ -418,6 +418,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 }),
                 context: None,
                 entities: vec![],
+                correlation_id: None,
+                explain: false,
             };
             req
         },
@@ -438,6 +440,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 }),
                 context: None,
                 entities: vec![],
+                correlation_id: None,
+                explain: false,
             };
             req
         },
@@ -8,13 +8,17 @@ use verified_permissions_sdk::proto::{
     BatchCreatePoliciesRequest, BatchCreatePoliciesResponse, BatchDeletePoliciesRequest,
     BatchDeletePoliciesResponse, BatchIsAuthorizedRequest, BatchIsAuthorizedResponse,
     BatchPolicyItem, BatchUpdatePoliciesRequest, BatchUpdatePoliciesResponse, CreatePolicyRequest,
-    CreatePolicyResponse, CreatePolicyStoreRequest, CreatePolicyStoreResponse, DeletePolicyRequest,
-    DeletePolicyResponse, DeletePolicyStoreRequest, DeletePolicyStoreResponse, EntityIdentifier,
-    GetPolicyRequest, GetPolicyResponse, GetPolicyStoreRequest, GetPolicyStoreResponse,
-    IsAuthorizedRequest, ListPoliciesRequest, ListPoliciesResponse, ListPolicyStoresRequest,
-    ListPolicyStoresResponse, PolicyDefinition, PutSchemaRequest, PutSchemaResponse, StaticPolicy,
-    TestAuthorizationRequest, TestAuthorizationResponse, UpdatePolicyRequest, UpdatePolicyResponse,
-    ValidatePolicyRequest, ValidatePolicyResponse,
+    CreatePolicyResponse, CreatePolicyStoreRequest, CreatePolicyStoreResponse,
+    DeleteEntitiesRequest, DeleteEntitiesResponse, DeletePolicyRequest, DeletePolicyResponse,
+    DeletePolicyStoreRequest, DeletePolicyStoreResponse, DeleteTokenRevocationsRequest,
+    DeleteTokenRevocationsResponse, Entity, EntityIdentifier, GetEntitiesRequest,
+    GetEntitiesResponse, GetPolicyRequest, GetPolicyResponse, GetPolicyStoreRequest,
+    GetPolicyStoreResponse, IsAuthorizedRequest, ListPoliciesRequest, ListPoliciesResponse,
+    ListPolicyStoresRequest, ListPolicyStoresResponse, ListTokenRevocationsRequest,
+    ListTokenRevocationsResponse, PolicyDefinition, PutEntitiesRequest, PutEntitiesResponse,
+    PutSchemaRequest, PutSchemaResponse, RevokeTokensRequest, RevokeTokensResponse, StaticPolicy,
+    TestAuthorizationRequest, TestAuthorizationResponse, TokenRevocation, UpdatePolicyRequest,
+    UpdatePolicyResponse, ValidatePolicyRequest, ValidatePolicyResponse,
     authorization_control_client::AuthorizationControlClient,
     authorization_data_client::AuthorizationDataClient,
 };
@@ -305,6 +309,142 @@ impl HodeiAdmin {
         Ok(response.into_inner())
     }
 
+    // =========================================================================
+    // Entity Store
+    // =========================================================================
+
+    /// Create or replace entities stored in a policy store
+    pub async fn put_entities(
+        &mut self,
+        policy_store_id: impl Into<String>,
+        entities: Vec<Entity>,
+    ) -> Result<PutEntitiesResponse> {
+        let request = PutEntitiesRequest {
+            policy_store_id: policy_store_id.into(),
+            entities,
+        };
+
+        info!("Putting {} entities", request.entities.len());
+
+        let response = self
+            .control_client
+            .put_entities(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Get stored entities (all of them when `identifiers` is empty)
+    pub async fn get_entities(
+        &mut self,
+        policy_store_id: impl Into<String>,
+        identifiers: Vec<EntityIdentifier>,
+    ) -> Result<GetEntitiesResponse> {
+        let request = GetEntitiesRequest {
+            policy_store_id: policy_store_id.into(),
+            identifiers,
+        };
+
+        info!("Getting entities");
+
+        let response = self
+            .control_client
+            .get_entities(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Delete stored entities
+    pub async fn delete_entities(
+        &mut self,
+        policy_store_id: impl Into<String>,
+        identifiers: Vec<EntityIdentifier>,
+    ) -> Result<DeleteEntitiesResponse> {
+        let request = DeleteEntitiesRequest {
+            policy_store_id: policy_store_id.into(),
+            identifiers,
+        };
+
+        info!("Deleting {} entities", request.identifiers.len());
+
+        let response = self
+            .control_client
+            .delete_entities(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    // =========================================================================
+    // Token Revocation
+    // =========================================================================
+
+    /// Deny tokens carrying the given `jti`, `sub` or `sid` values
+    pub async fn revoke_tokens(
+        &mut self,
+        policy_store_id: impl Into<String>,
+        revocations: Vec<TokenRevocation>,
+    ) -> Result<RevokeTokensResponse> {
+        let request = RevokeTokensRequest {
+            policy_store_id: policy_store_id.into(),
+            revocations,
+        };
+
+        info!("Revoking {} token claims", request.revocations.len());
+
+        let response = self
+            .control_client
+            .revoke_tokens(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    /// List the unexpired token revocations of a policy store
+    pub async fn list_token_revocations(
+        &mut self,
+        policy_store_id: impl Into<String>,
+    ) -> Result<ListTokenRevocationsResponse> {
+        let request = ListTokenRevocationsRequest {
+            policy_store_id: policy_store_id.into(),
+        };
+
+        let response = self
+            .control_client
+            .list_token_revocations(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
+    /// Remove token revocations, matched by claim and value
+    pub async fn delete_token_revocations(
+        &mut self,
+        policy_store_id: impl Into<String>,
+        revocations: Vec<TokenRevocation>,
+    ) -> Result<DeleteTokenRevocationsResponse> {
+        let request = DeleteTokenRevocationsRequest {
+            policy_store_id: policy_store_id.into(),
+            revocations,
+        };
+
+        info!("Deleting {} token revocations", request.revocations.len());
+
+        let response = self
+            .control_client
+            .delete_token_revocations(request)
+            .await
+            .map_err(SdkAdminError::from)?;
+
+        Ok(response.into_inner())
+    }
+
     // =========================================================================
     // Bulk Operations
     // =========================================================================
@@ -467,6 +607,7 @@ impl HodeiAdmin {
         let request = BatchIsAuthorizedRequest {
             policy_store_id: policy_store_id.into(),
             requests,
+            ..Default::default()
         };
 
         info!(
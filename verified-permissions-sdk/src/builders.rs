@@ -11,6 +11,8 @@ pub struct IsAuthorizedRequestBuilder {
     resource: Option<EntityIdentifier>,
     context: Option<String>,
     entities: Vec<Entity>,
+    correlation_id: Option<String>,
+    explain: bool,
 }
 
 impl IsAuthorizedRequestBuilder {
@@ -22,6 +24,8 @@ impl IsAuthorizedRequestBuilder {
             resource: None,
             context: None,
             entities: Vec::new(),
+            correlation_id: None,
+            explain: false,
         }
     }
 
@@ -59,6 +63,18 @@ impl IsAuthorizedRequestBuilder {
         self
     }
 
+    /// Id echoed back in the response, to match batch responses to requests
+    pub fn correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Ask for a per-policy explanation of the decision
+    pub fn explain(mut self) -> Self {
+        self.explain = true;
+        self
+    }
+
     pub fn build(self) -> IsAuthorizedRequest {
         IsAuthorizedRequest {
             policy_store_id: self.policy_store_id,
@@ -67,6 +83,8 @@ impl IsAuthorizedRequestBuilder {
             resource: self.resource,
             context: self.context,
             entities: self.entities,
+            correlation_id: self.correlation_id,
+            explain: self.explain,
         }
     }
 }
@@ -121,6 +139,8 @@ pub struct IsAuthorizedWithTokenRequestBuilder {
     resource: Option<EntityIdentifier>,
     context: Option<String>,
     entities: Vec<Entity>,
+    explain: bool,
+    proof_of_possession: Option<ProofOfPossession>,
 }
 
 impl IsAuthorizedWithTokenRequestBuilder {
@@ -144,6 +164,8 @@ impl IsAuthorizedWithTokenRequestBuilder {
             resource: None,
             context: None,
             entities: Vec::new(),
+            explain: false,
+            proof_of_possession: None,
         }
     }
 
@@ -177,6 +199,36 @@ impl IsAuthorizedWithTokenRequestBuilder {
         self
     }
 
+    /// Ask for a per-policy explanation of the decision
+    pub fn explain(mut self) -> Self {
+        self.explain = true;
+        self
+    }
+
+    /// Forward the DPoP proof of a DPoP-bound token, with the method and URL
+    /// of the request that carried it
+    pub fn dpop_proof(
+        mut self,
+        proof: impl Into<String>,
+        http_method: impl Into<String>,
+        http_url: impl Into<String>,
+    ) -> Self {
+        let proof_of_possession = self.proof_of_possession.get_or_insert_default();
+        proof_of_possession.dpop_proof = Some(proof.into());
+        proof_of_possession.http_method = Some(http_method.into());
+        proof_of_possession.http_url = Some(http_url.into());
+        self
+    }
+
+    /// Forward the base64url SHA-256 thumbprint of the mTLS client
+    /// certificate of a certificate-bound token
+    pub fn client_certificate_thumbprint(mut self, thumbprint: impl Into<String>) -> Self {
+        self.proof_of_possession
+            .get_or_insert_default()
+            .client_certificate_thumbprint = Some(thumbprint.into());
+        self
+    }
+
     /// Build the IsAuthorizedWithTokenRequest
     pub fn build(self) -> IsAuthorizedWithTokenRequest {
         IsAuthorizedWithTokenRequest {
@@ -187,6 +239,8 @@ impl IsAuthorizedWithTokenRequestBuilder {
             resource: self.resource,
             context: self.context,
             entities: self.entities,
+            explain: self.explain,
+            proof_of_possession: self.proof_of_possession,
         }
     }
 }
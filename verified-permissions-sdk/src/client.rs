@@ -124,6 +124,8 @@ impl AuthorizationClient {
             resource: Some(parse_entity_id(resource.into())?),
             context: None,
             entities: vec![],
+            correlation_id: None,
+            explain: false,
         };
 
         let response = self
@@ -220,6 +222,7 @@ impl AuthorizationClient {
         let request = BatchIsAuthorizedRequest {
             policy_store_id: policy_store_id.into(),
             requests,
+            ..Default::default()
         };
 
         let response = self
@@ -240,7 +243,8 @@ impl AuthorizationClient {
     /// # Arguments
     ///
     /// * `policy_store_id` - The ID of the policy store to evaluate against
-    /// * `identity_source_id` - The ID of the identity source for token validation
+    /// * `identity_source_id` - The ID of the identity source for token validation,
+    ///   or empty to select it by the token issuer
     /// * `access_token` - The JWT access token
     /// * `action` - The action being performed (format: "Type::id")
     /// * `resource` - The resource being accessed (format: "Type::id")
@@ -282,6 +286,8 @@ impl AuthorizationClient {
             resource: Some(parse_entity_id(resource.into())?),
             context: None,
             entities: vec![],
+            explain: false,
+            proof_of_possession: None,
         };
 
         let response = self
@@ -294,6 +300,82 @@ impl AuthorizationClient {
         Ok(response)
     }
 
+    /// Batch authorization check with JWT token
+    ///
+    /// The token is validated and mapped to a principal once, then every
+    /// action/resource pair is evaluated for that principal. Responses are in
+    /// request order; an item that fails carries its error in `errors`.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy_store_id` - The ID of the policy store to evaluate against
+    /// * `identity_source_id` - The ID of the identity source for token validation,
+    ///   or empty to select it by the token issuer
+    /// * `access_token` - The JWT access token
+    /// * `requests` - The action/resource pairs to evaluate
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use verified_permissions_sdk::AuthorizationClient;
+    /// # use verified_permissions_sdk::proto::{EntityIdentifier, TokenAuthorizationItem};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = AuthorizationClient::connect("http://localhost:50051").await?;
+    ///
+    /// let jwt_token = "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9...";
+    /// let document = EntityIdentifier {
+    ///     entity_type: "Document".to_string(),
+    ///     entity_id: "doc123".to_string(),
+    /// };
+    ///
+    /// let requests = ["view", "edit"]
+    ///     .into_iter()
+    ///     .map(|action| TokenAuthorizationItem {
+    ///         action: Some(EntityIdentifier {
+    ///             entity_type: "Action".to_string(),
+    ///             entity_id: action.to_string(),
+    ///         }),
+    ///         resource: Some(document.clone()),
+    ///         ..Default::default()
+    ///     })
+    ///     .collect();
+    ///
+    /// let response = client
+    ///     .batch_is_authorized_with_token(
+    ///         "policy-store-id",
+    ///         "identity-source-id",
+    ///         jwt_token,
+    ///         requests,
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn batch_is_authorized_with_token(
+        &self,
+        policy_store_id: impl Into<String>,
+        identity_source_id: impl Into<String>,
+        access_token: impl Into<String>,
+        requests: Vec<TokenAuthorizationItem>,
+    ) -> Result<BatchIsAuthorizedResponse> {
+        let request = BatchIsAuthorizedWithTokenRequest {
+            policy_store_id: policy_store_id.into(),
+            identity_source_id: identity_source_id.into(),
+            access_token: access_token.into(),
+            requests,
+            ..Default::default()
+        };
+
+        let response = self
+            .data_client
+            .clone()
+            .batch_is_authorized_with_token(request)
+            .await?
+            .into_inner();
+
+        Ok(response)
+    }
+
     /// Check authorization with JWT token and context
     ///
     /// This method validates a JWT token and performs authorization with additional
@@ -416,6 +498,24 @@ impl crate::client_trait::AuthorizationClientTrait for AuthorizationClient {
         .await
     }
 
+    /// Batch authorization check with JWT token
+    async fn batch_is_authorized_with_token(
+        &self,
+        policy_store_id: &str,
+        identity_source_id: &str,
+        access_token: &str,
+        requests: Vec<TokenAuthorizationItem>,
+    ) -> Result<BatchIsAuthorizedResponse> {
+        AuthorizationClient::batch_is_authorized_with_token(
+            self,
+            policy_store_id,
+            identity_source_id,
+            access_token,
+            requests,
+        )
+        .await
+    }
+
     /// Check authorization with JWT token and context
     async fn is_authorized_with_token_and_context(
         &self,
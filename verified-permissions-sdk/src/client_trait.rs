@@ -138,7 +138,8 @@ pub trait AuthorizationClientTrait: Send + Sync {
     /// # Arguments
     ///
     /// * `policy_store_id` - The ID of the policy store to evaluate against
-    /// * `identity_source_id` - The ID of the identity source for token validation
+    /// * `identity_source_id` - The ID of the identity source for token validation,
+    ///   or empty to select it by the token issuer
     /// * `access_token` - The JWT access token
     /// * `action` - The action being performed (format: "Type::id")
     /// * `resource` - The resource being accessed (format: "Type::id")
@@ -155,6 +156,30 @@ pub trait AuthorizationClientTrait: Send + Sync {
         resource: &str,
     ) -> Result<IsAuthorizedResponse>;
 
+    /// Batch authorization check with JWT token
+    ///
+    /// Validates the token once and evaluates every action/resource pair for
+    /// its principal.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy_store_id` - The ID of the policy store to evaluate against
+    /// * `identity_source_id` - The ID of the identity source for token validation,
+    ///   or empty to select it by the token issuer
+    /// * `access_token` - The JWT access token
+    /// * `requests` - The action/resource pairs to evaluate
+    ///
+    /// # Returns
+    ///
+    /// Returns `Result<BatchIsAuthorizedResponse>` with one decision per item, in request order.
+    async fn batch_is_authorized_with_token(
+        &self,
+        policy_store_id: &str,
+        identity_source_id: &str,
+        access_token: &str,
+        requests: Vec<TokenAuthorizationItem>,
+    ) -> Result<BatchIsAuthorizedResponse>;
+
     /// Check authorization with JWT token and context
     ///
     /// Validates a JWT token and performs authorization with additional
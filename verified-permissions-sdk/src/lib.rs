@@ -81,6 +81,7 @@ pub use validation::OidcConfigValidator;
 
 // Re-export Data Plane types (most commonly used)
 pub use proto::{
-    BatchIsAuthorizedRequest, BatchIsAuthorizedResponse, Decision, IsAuthorizedRequest,
-    IsAuthorizedResponse, IsAuthorizedWithTokenRequest,
+    BatchIsAuthorizedRequest, BatchIsAuthorizedResponse, BatchIsAuthorizedWithTokenRequest,
+    Decision, IsAuthorizedRequest, IsAuthorizedResponse, IsAuthorizedWithTokenRequest,
+    TokenAuthorizationItem,
 };
@@ -15,7 +15,8 @@ impl OidcConfigValidator {
     /// # Validation Rules
     ///
     /// - `issuer` must be a valid HTTPS URL (when schema feature is enabled)
-    /// - `jwks_uri` must be a valid HTTPS URL (when schema feature is enabled)
+    /// - `jwks_uri`, when set, must be a valid HTTPS URL (when schema feature is enabled);
+    ///   leave it empty to use the issuer's OIDC discovery document
     /// - `client_ids` must not be empty
     /// - At least one client ID must be provided
     ///
@@ -51,27 +52,22 @@ impl OidcConfigValidator {
             }
         }
 
-        // Validate JWKS URI
-        if config.jwks_uri.is_empty() {
-            return Err(SdkError::InvalidRequest(
-                "OIDC JWKS URI cannot be empty".to_string(),
-            ));
-        }
-
-        // Validate JWKS URI is a valid HTTPS URL
-        match Url::parse(&config.jwks_uri) {
-            Ok(url) => {
-                if url.scheme() != "https" {
+        // Validate JWKS URI is a valid HTTPS URL (empty: discovered from the issuer)
+        if !config.jwks_uri.is_empty() {
+            match Url::parse(&config.jwks_uri) {
+                Ok(url) => {
+                    if url.scheme() != "https" {
+                        return Err(SdkError::InvalidRequest(
+                            "OIDC JWKS URI must use HTTPS scheme".to_string(),
+                        ));
+                    }
+                }
+                Err(_) => {
                     return Err(SdkError::InvalidRequest(
-                        "OIDC JWKS URI must use HTTPS scheme".to_string(),
+                        "OIDC JWKS URI must be a valid URL".to_string(),
                     ));
                 }
             }
-            Err(_) => {
-                return Err(SdkError::InvalidRequest(
-                    "OIDC JWKS URI must be a valid URL".to_string(),
-                ));
-            }
         }
 
         // Validate client IDs
@@ -233,6 +229,20 @@ mod tests {
             client_ids: vec!["client-1".to_string()],
             jwks_uri: "https://auth.example.com/.well-known/jwks.json".to_string(),
             group_claim: "groups".to_string(),
+            ..Default::default()
+        };
+        assert!(OidcConfigValidator::validate(&config).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_oidc_config_discovered_jwks_uri() {
+        let config = OidcConfiguration {
+            issuer: "https://auth.example.com".to_string(),
+            client_ids: vec!["client-1".to_string()],
+            jwks_uri: String::new(),
+            group_claim: "groups".to_string(),
+            ..Default::default()
         };
         assert!(OidcConfigValidator::validate(&config).is_ok());
     }
@@ -245,6 +255,7 @@ mod tests {
             client_ids: vec!["client-1".to_string()],
             jwks_uri: "https://auth.example.com/.well-known/jwks.json".to_string(),
             group_claim: "".to_string(),
+            ..Default::default()
         };
         assert!(OidcConfigValidator::validate(&config).is_err());
     }
@@ -257,6 +268,7 @@ mod tests {
             client_ids: vec![],
             jwks_uri: "https://auth.example.com/.well-known/jwks.json".to_string(),
             group_claim: "".to_string(),
+            ..Default::default()
         };
         assert!(OidcConfigValidator::validate(&config).is_err());
     }
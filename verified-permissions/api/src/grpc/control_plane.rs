@@ -3,30 +3,65 @@
 use crate::proto::authorization_control_server::AuthorizationControl;
 use crate::proto::*;
 use cedar_policy::{
-    Authorizer, Context, Decision, Entities, EntityUid, Policy as CedarPolicyType, PolicySet,
-    Request as CedarRequest, Schema, Validator,
+    Authorizer, Context, Decision, Entities, EntityTypeName, EntityUid, Policy as CedarPolicyType,
+    PolicySet, Request as CedarRequest, Schema, Validator,
 };
 use hodei_domain::events::{
     PolicyStoreCreated, PolicyStoreTagsUpdated, PolicyStoreUpdated, DomainEventEnvelope,
 };
+use hodei_infrastructure::cache::{CacheManager, CompiledPolicyStore};
+use hodei_infrastructure::cache::policy_store_cache::{add_template, entity_json, link_policy};
+use hodei_infrastructure::error::AuthorizationError;
 use hodei_infrastructure::events::{InMemoryEventBus, EventStoreBox};
+use hodei_infrastructure::jwt::providers::CognitoProvider;
+use hodei_infrastructure::jwt::{
+    AlgorithmAllowlist, AttributeType, AudienceCheck, ClaimPath, ClaimsMappingConfig,
+    ContextClaimsConfig, ParentMapping, RequiredClaim, SenderConstraint, TokenValidationPolicy,
+    ValueTransform,
+};
 use hodei_domain::events::{EventDispatcher, EventDispatcherPort};
-use hodei_domain::{CedarPolicy, IdentitySourceType, PolicyId, PolicyRepository, PolicyStoreId};
+use chrono::{DateTime, Utc};
+use hodei_domain::{
+    CedarPolicy, DecisionLogFilter, EntityKey, IdentitySourceType, PolicyId, PolicyRepository,
+    PolicyStoreId, PolicyTemplate, Principal, Resource, StoredEntity, TemplateLink,
+};
 use hodei_infrastructure::repository::RepositoryAdapter;
 use serde_json;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 use tracing::{error, info};
 
+/// Shortest secret accepted for shared-secret identity sources (the HS256
+/// output size)
+const MIN_SHARED_SECRET_LEN: usize = 32;
+
 pub struct AuthorizationControlService {
     repository: Arc<RepositoryAdapter>,
     dispatcher: Arc<EventDispatcher<InMemoryEventBus, EventStoreBox>>,
+    cache: Arc<CacheManager>,
+    shared_secret_identity_sources: bool,
 }
 
 impl AuthorizationControlService {
-    pub fn new(repository: Arc<RepositoryAdapter>, dispatcher: Arc<EventDispatcher<InMemoryEventBus, EventStoreBox>>) -> Self {
-        Self { repository, dispatcher }
+    pub fn new(
+        repository: Arc<RepositoryAdapter>,
+        dispatcher: Arc<EventDispatcher<InMemoryEventBus, EventStoreBox>>,
+        cache: Arc<CacheManager>,
+    ) -> Self {
+        Self {
+            repository,
+            dispatcher,
+            cache,
+            shared_secret_identity_sources: false,
+        }
+    }
+
+    /// Allows creating shared-secret (HS256) identity sources
+    pub fn with_shared_secret_identity_sources(mut self, enabled: bool) -> Self {
+        self.shared_secret_identity_sources = enabled;
+        self
     }
 
     async fn publish_event(&self, event: DomainEventEnvelope) {
@@ -34,6 +69,542 @@ impl AuthorizationControlService {
             error!("Failed to publish event: {}", e);
         }
     }
+
+    /// Builds a template link and checks that Cedar can instantiate it
+    fn build_template_link(
+        template: &PolicyTemplate,
+        policy_id: &PolicyId,
+        template_linked: &TemplateLinkedPolicy,
+    ) -> Result<TemplateLink, Status> {
+        let entity_uid =
+            |e: &EntityIdentifier| format!("{}::\"{}\"", e.entity_type, e.entity_id);
+
+        let link = TemplateLink {
+            policy_template_id: template_linked.policy_template_id.clone(),
+            principal: template_linked
+                .principal
+                .as_ref()
+                .map(|p| Principal::new(entity_uid(p)))
+                .transpose()
+                .map_err(|e| Status::invalid_argument(format!("Invalid principal: {}", e)))?,
+            resource: template_linked
+                .resource
+                .as_ref()
+                .map(|r| Resource::new(entity_uid(r)))
+                .transpose()
+                .map_err(|e| Status::invalid_argument(format!("Invalid resource: {}", e)))?,
+        };
+
+        let mut policy_set = PolicySet::new();
+        add_template(&mut policy_set, template)
+            .and_then(|_| link_policy(&mut policy_set, policy_id.as_str(), &link))
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(link)
+    }
+
+    /// Fails if a policy template of the store already uses `policy_id`
+    ///
+    /// Policies and templates share the ID namespace of the compiled Cedar
+    /// policy set, so a colliding ID would make the whole store fail to load.
+    async fn ensure_not_template_id(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_id: &str,
+    ) -> Result<(), Status> {
+        let templates = self
+            .repository
+            .list_policy_templates(policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list policy templates: {}", e);
+                Status::internal(format!("Failed to list policy templates: {}", e))
+            })?;
+
+        if templates.iter().any(|t| t.template_id == policy_id) {
+            return Err(Status::already_exists(format!(
+                "Policy ID {} is already used by a policy template",
+                policy_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fails if a policy of the store already uses `template_id`
+    async fn ensure_not_policy_id(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        template_id: &str,
+    ) -> Result<(), Status> {
+        let policies = self
+            .repository
+            .list_policies(policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list policies: {}", e);
+                Status::internal(format!("Failed to list policies: {}", e))
+            })?;
+
+        if policies.iter().any(|p| p.policy_id.as_str() == template_id) {
+            return Err(Status::already_exists(format!(
+                "Template ID {} is already used by a policy",
+                template_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Loads the compiled policy store from the cache
+    async fn load_compiled(
+        &self,
+        policy_store_id: &PolicyStoreId,
+    ) -> Result<Arc<CompiledPolicyStore>, Status> {
+        self.cache
+            .get_compiled(policy_store_id)
+            .await
+            .map_err(|e| match e {
+                AuthorizationError::NotFound(msg) => Status::not_found(msg),
+                e => Status::internal(format!("Failed to load policy store: {}", e)),
+            })
+    }
+
+    /// Fails if the stored entities or policies of a store do not conform
+    /// to `schema`
+    ///
+    /// Strict stores parse their entities against the schema when they are
+    /// loaded, so a non-conforming entity would stop the store from serving.
+    async fn ensure_conforms_to_schema(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_set: &PolicySet,
+        schema: &Schema,
+    ) -> Result<(), Status> {
+        let stored = self
+            .repository
+            .list_entities(policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list entities: {}", e);
+                Status::internal(format!("Failed to list entities: {}", e))
+            })?;
+        Entities::from_json_value(
+            serde_json::Value::Array(stored.iter().map(entity_json).collect()),
+            Some(schema),
+        )
+        .map_err(|e| {
+            Status::invalid_argument(format!(
+                "Stored entities do not conform to the schema: {}",
+                e
+            ))
+        })?;
+
+        let result = Validator::new(schema.clone())
+            .validate(policy_set, cedar_policy::ValidationMode::default());
+        if let Some(e) = result.validation_errors().next() {
+            return Err(Status::invalid_argument(format!(
+                "Stored policies do not conform to the schema: {}",
+                e
+            )));
+        }
+        Ok(())
+    }
+
+    /// Converts a stored template link back to its API representation
+    fn template_linked_definition(link: TemplateLink) -> Result<TemplateLinkedPolicy, Status> {
+        let entity_identifier = |uid: &str| {
+            EntityUid::from_str(uid)
+                .map(|uid| EntityIdentifier {
+                    entity_type: uid.type_name().to_string(),
+                    entity_id: uid.id().unescaped().to_string(),
+                })
+                .map_err(|e| Status::internal(format!("Invalid stored entity UID: {}", e)))
+        };
+
+        Ok(TemplateLinkedPolicy {
+            policy_template_id: link.policy_template_id,
+            principal: link
+                .principal
+                .map(|p| entity_identifier(p.as_str()))
+                .transpose()?,
+            resource: link
+                .resource
+                .map(|r| entity_identifier(r.as_str()))
+                .transpose()?,
+        })
+    }
+
+    /// Converts the API claims mapping into the stored mapping configuration
+    fn claims_mapping_config(
+        mapping: ClaimsMappingConfiguration,
+    ) -> Result<ClaimsMappingConfig, Status> {
+        let defaults = ClaimsMappingConfig::default();
+
+        let mut paths = vec![&mapping.principal_id_claim, &mapping.group_claim];
+        paths.extend(mapping.attribute_mappings.values());
+        paths.extend(mapping.parent_mappings.iter().map(|p| &p.claim_path));
+        if let Some(context) = &mapping.context_claims {
+            paths.extend(&context.claims);
+        }
+        for path in paths.into_iter().filter(|path| !path.is_empty()) {
+            Self::validate_claim_path(path)?;
+        }
+
+        let attribute_types = mapping
+            .attribute_types
+            .into_iter()
+            .map(|(attribute, attr_type)| {
+                if !mapping.attribute_mappings.contains_key(&attribute) {
+                    return Err(Status::invalid_argument(format!(
+                        "attribute_types names '{}', which is not in attribute_mappings",
+                        attribute
+                    )));
+                }
+                let attr_type = attr_type.parse::<AttributeType>().map_err(|e| {
+                    Status::invalid_argument(format!(
+                        "Invalid type for attribute '{}': {}",
+                        attribute, e
+                    ))
+                })?;
+                Ok((attribute, attr_type))
+            })
+            .collect::<Result<_, Status>>()?;
+
+        let parent_mappings = mapping
+            .parent_mappings
+            .into_iter()
+            .map(|parent| {
+                if parent.claim_path.is_empty() || parent.entity_type.is_empty() {
+                    return Err(Status::invalid_argument(
+                        "Parent mapping requires claim_path and entity_type",
+                    ));
+                }
+                let transform = match parent.transform.as_deref() {
+                    Some(json) if !json.is_empty() => serde_json::from_str::<ValueTransform>(json)
+                        .map_err(|e| e.to_string())
+                        .and_then(|transform| transform.validate().map(|()| transform))
+                        .map_err(|e| {
+                            Status::invalid_argument(format!(
+                                "Invalid transform for claim '{}': {}",
+                                parent.claim_path, e
+                            ))
+                        })?,
+                    _ => ValueTransform::None,
+                };
+                Ok(ParentMapping {
+                    claim_path: parent.claim_path,
+                    entity_type: parent.entity_type,
+                    transform,
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        Ok(ClaimsMappingConfig {
+            principal_id_claim: Some(mapping.principal_id_claim)
+                .filter(|claim| !claim.is_empty())
+                .unwrap_or(defaults.principal_id_claim),
+            principal_entity_type: mapping
+                .principal_entity_type
+                .filter(|entity_type| !entity_type.is_empty())
+                .unwrap_or(defaults.principal_entity_type),
+            group_claim: Some(mapping.group_claim).filter(|claim| !claim.is_empty()),
+            group_entity_type: defaults.group_entity_type,
+            attribute_mappings: mapping.attribute_mappings.into_iter().collect(),
+            attribute_types,
+            parent_mappings,
+            context_claims: mapping.context_claims.map(|context| ContextClaimsConfig {
+                context_key: Some(context.context_key)
+                    .filter(|key| !key.is_empty())
+                    .unwrap_or_else(|| ContextClaimsConfig::default().context_key),
+                claims: context.claims,
+            }),
+        })
+    }
+
+    /// Converts a stored claims mapping back to its API representation
+    fn claims_mapping_definition(config: ClaimsMappingConfig) -> ClaimsMappingConfiguration {
+        ClaimsMappingConfiguration {
+            principal_id_claim: config.principal_id_claim,
+            group_claim: config.group_claim.unwrap_or_default(),
+            attribute_mappings: config.attribute_mappings.into_iter().collect(),
+            attribute_types: config
+                .attribute_types
+                .into_iter()
+                .map(|(attribute, attr_type)| (attribute, attr_type.to_string()))
+                .collect(),
+            principal_entity_type: Some(config.principal_entity_type),
+            parent_mappings: config
+                .parent_mappings
+                .into_iter()
+                .map(|parent| ParentMappingConfiguration {
+                    transform: match parent.transform {
+                        ValueTransform::None => None,
+                        transform => serde_json::to_string(&transform).ok(),
+                    },
+                    claim_path: parent.claim_path,
+                    entity_type: parent.entity_type,
+                })
+                .collect(),
+            context_claims: config
+                .context_claims
+                .map(|context| ContextClaimsConfiguration {
+                    context_key: context.context_key,
+                    claims: context.claims,
+                }),
+        }
+    }
+
+    /// Converts a token validation configuration to the policy stored with
+    /// the identity source
+    fn token_validation_policy(
+        config: TokenValidationConfiguration,
+    ) -> Result<TokenValidationPolicy, Status> {
+        let defaults = TokenValidationPolicy::default();
+
+        let mut required_claims = Vec::with_capacity(config.required_claims.len());
+        for required in &config.required_claims {
+            if required.claim.is_empty() {
+                return Err(Status::invalid_argument(
+                    "Required claim name cannot be empty",
+                ));
+            }
+            Self::validate_claim_path(&required.claim)?;
+            required_claims.push(RequiredClaim {
+                claim: required.claim.clone(),
+                values: required.values.clone(),
+            });
+        }
+
+        Ok(TokenValidationPolicy {
+            clock_skew_seconds: config
+                .clock_skew_seconds
+                .unwrap_or(defaults.clock_skew_seconds),
+            validate_nbf: config.validate_nbf.unwrap_or(defaults.validate_nbf),
+            max_token_age_seconds: config.max_token_age_seconds,
+            required_claims,
+            audience_check: match config.audience_validation() {
+                AudienceValidation::Unspecified => None,
+                AudienceValidation::Aud => Some(AudienceCheck::Aud),
+                AudienceValidation::ClientId => Some(AudienceCheck::ClientId),
+                AudienceValidation::AudOrClientId => Some(AudienceCheck::AudOrClientId),
+            },
+            sender_constraint: match config.sender_constraint() {
+                SenderConstraintEnforcement::Unspecified
+                | SenderConstraintEnforcement::Disabled => SenderConstraint::Disabled,
+                SenderConstraintEnforcement::IfBound => SenderConstraint::IfBound,
+                SenderConstraintEnforcement::Required => SenderConstraint::Required,
+            },
+        })
+    }
+
+    /// Converts a stored token validation policy back to its API form
+    fn token_validation_definition(policy: TokenValidationPolicy) -> TokenValidationConfiguration {
+        let audience_validation = match policy.audience_check {
+            None => AudienceValidation::Unspecified,
+            Some(AudienceCheck::Aud) => AudienceValidation::Aud,
+            Some(AudienceCheck::ClientId) => AudienceValidation::ClientId,
+            Some(AudienceCheck::AudOrClientId) => AudienceValidation::AudOrClientId,
+        };
+        let sender_constraint = match policy.sender_constraint {
+            SenderConstraint::Disabled => SenderConstraintEnforcement::Disabled,
+            SenderConstraint::IfBound => SenderConstraintEnforcement::IfBound,
+            SenderConstraint::Required => SenderConstraintEnforcement::Required,
+        };
+        TokenValidationConfiguration {
+            clock_skew_seconds: Some(policy.clock_skew_seconds),
+            validate_nbf: Some(policy.validate_nbf),
+            max_token_age_seconds: policy.max_token_age_seconds,
+            required_claims: policy
+                .required_claims
+                .into_iter()
+                .map(|required| RequiredClaimConfiguration {
+                    claim: required.claim,
+                    values: required.values,
+                })
+                .collect(),
+            audience_validation: audience_validation as i32,
+            sender_constraint: sender_constraint as i32,
+        }
+    }
+
+    /// Checks that a claim name or path used by an identity source parses
+    fn validate_claim_path(path: &str) -> Result<(), Status> {
+        ClaimPath::parse(path)
+            .map(|_| ())
+            .map_err(|e| Status::invalid_argument(e.to_string()))
+    }
+
+    /// Checks an identity source's algorithm allowlist
+    ///
+    /// Returns the normalized names, or an empty list to keep the default.
+    fn allowed_algorithms(names: &[String], symmetric: bool) -> Result<Vec<String>, Status> {
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let allowlist = if symmetric {
+            AlgorithmAllowlist::symmetric(names)
+        } else {
+            AlgorithmAllowlist::asymmetric(names)
+        };
+        allowlist
+            .map(|allowlist| allowlist.names())
+            .map_err(|e| Status::invalid_argument(format!("Invalid allowed_algorithms: {}", e)))
+    }
+
+    /// Checks that identity source entity types are valid Cedar type names
+    /// declared in the store schema, when the store has one
+    fn validate_entity_types(
+        schema: Option<&Schema>,
+        entity_types: &[(&str, String)],
+    ) -> Result<(), Status> {
+        for (field, entity_type) in entity_types {
+            let name = EntityTypeName::from_str(entity_type).map_err(|e| {
+                Status::invalid_argument(format!("Invalid {} '{}': {}", field, entity_type, e))
+            })?;
+
+            if let Some(schema) = schema
+                && !schema.entity_types().any(|declared| declared == &name)
+            {
+                return Err(Status::invalid_argument(format!(
+                    "{} '{}' is not declared in the policy store schema",
+                    field, entity_type
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn entity_key(identifier: &EntityIdentifier) -> Result<EntityKey, Status> {
+        if identifier.entity_type.is_empty() || identifier.entity_id.is_empty() {
+            return Err(Status::invalid_argument(
+                "Entity identifier requires entity_type and entity_id",
+            ));
+        }
+        Ok(EntityKey::new(
+            identifier.entity_type.clone(),
+            identifier.entity_id.clone(),
+        ))
+    }
+
+    /// Converts an API entity to its stored form
+    ///
+    /// Attribute values are JSON; values that are not valid JSON are stored as
+    /// strings, matching how the data plane reads request entities.
+    fn stored_entity(
+        policy_store_id: &PolicyStoreId,
+        entity: &Entity,
+    ) -> Result<StoredEntity, Status> {
+        let key = Self::entity_key(
+            entity
+                .identifier
+                .as_ref()
+                .ok_or_else(|| Status::invalid_argument("Entity identifier is required"))?,
+        )?;
+
+        let attributes: BTreeMap<String, serde_json::Value> = entity
+            .attributes
+            .iter()
+            .map(|(name, value)| {
+                let parsed = serde_json::from_str(value)
+                    .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+                (name.clone(), parsed)
+            })
+            .collect();
+
+        let parents = entity
+            .parents
+            .iter()
+            .map(Self::entity_key)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(StoredEntity::new(
+            policy_store_id.clone(),
+            key,
+            attributes,
+            parents,
+        ))
+    }
+
+    /// Converts a stored entity back to its API representation
+    fn entity_message(entity: StoredEntity) -> Entity {
+        let identifier = |key: EntityKey| EntityIdentifier {
+            entity_type: key.entity_type,
+            entity_id: key.entity_id,
+        };
+
+        Entity {
+            identifier: Some(identifier(entity.key)),
+            attributes: entity
+                .attributes
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string()))
+                .collect(),
+            parents: entity.parents.into_iter().map(identifier).collect(),
+        }
+    }
+
+    /// Claim and value of an API token revocation
+    fn revocation_key(
+        revocation: &TokenRevocation,
+    ) -> Result<(hodei_domain::RevokedClaim, String), Status> {
+        let claim = match revocation.claim() {
+            RevokedClaim::Unspecified => {
+                return Err(Status::invalid_argument(
+                    "Revocation claim is required (JTI, SUB or SID)",
+                ));
+            }
+            RevokedClaim::Jti => hodei_domain::RevokedClaim::Jti,
+            RevokedClaim::Sub => hodei_domain::RevokedClaim::Sub,
+            RevokedClaim::Sid => hodei_domain::RevokedClaim::Sid,
+        };
+        if revocation.value.is_empty() {
+            return Err(Status::invalid_argument("Revocation value is required"));
+        }
+        Ok((claim, revocation.value.clone()))
+    }
+
+    /// Converts an API token revocation to its stored form
+    fn stored_revocation(
+        policy_store_id: &PolicyStoreId,
+        revocation: &TokenRevocation,
+    ) -> Result<hodei_domain::TokenRevocation, Status> {
+        let (claim, value) = Self::revocation_key(revocation)?;
+        let expires_at = revocation
+            .expires_at
+            .as_deref()
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                DateTime::parse_from_rfc3339(t)
+                    .map(|t| t.with_timezone(&Utc))
+                    .map_err(|e| {
+                        Status::invalid_argument(format!("Invalid expires_at '{}': {}", t, e))
+                    })
+            })
+            .transpose()?;
+
+        Ok(hodei_domain::TokenRevocation::new(
+            policy_store_id.clone(),
+            claim,
+            value,
+            expires_at,
+            revocation.reason.clone().filter(|r| !r.is_empty()),
+        ))
+    }
+
+    /// Converts a stored token revocation back to its API representation
+    fn revocation_message(revocation: hodei_domain::TokenRevocation) -> TokenRevocation {
+        let claim = match revocation.claim {
+            hodei_domain::RevokedClaim::Jti => RevokedClaim::Jti,
+            hodei_domain::RevokedClaim::Sub => RevokedClaim::Sub,
+            hodei_domain::RevokedClaim::Sid => RevokedClaim::Sid,
+        };
+        TokenRevocation {
+            claim: claim as i32,
+            value: revocation.value,
+            expires_at: revocation.expires_at.map(|t| t.to_rfc3339()),
+            reason: revocation.reason,
+            created_at: revocation.created_at.to_rfc3339(),
+        }
+    }
 }
 
 #[tonic::async_trait]
@@ -83,6 +654,7 @@ impl AuthorizationControl for AuthorizationControlService {
             version: 1,
         };
         self.publish_event(DomainEventEnvelope::PolicyStoreCreated(Box::new(created_event))).await;
+        self.cache.refresh(&store.id).await;
 
         Ok(Response::new(CreatePolicyStoreResponse {
             policy_store_id,
@@ -121,6 +693,7 @@ impl AuthorizationControl for AuthorizationControlService {
             default_identity_source_id: store.default_identity_source_id,
             created_at: store.created_at.to_rfc3339(),
             updated_at: store.updated_at.to_rfc3339(),
+            strict_validation: store.strict_validation,
         }))
     }
 
@@ -149,6 +722,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 default_identity_source_id: store.default_identity_source_id,
                 created_at: store.created_at.to_rfc3339(),
                 updated_at: store.updated_at.to_rfc3339(),
+                strict_validation: store.strict_validation,
             })
             .collect();
 
@@ -175,6 +749,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 error!("Failed to delete policy store: {}", e);
                 Status::internal(format!("Failed to delete policy store: {}", e))
             })?;
+        self.cache.invalidate(&policy_store_id).await;
 
         Ok(Response::new(DeletePolicyStoreResponse {}))
     }
@@ -189,14 +764,50 @@ impl AuthorizationControl for AuthorizationControlService {
         let policy_store_id = PolicyStoreId::new(req.policy_store_id)
             .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
 
+        // Turning strict validation on must not leave the store unable to load
+        if req.strict_validation == Some(true) {
+            let compiled = self.load_compiled(&policy_store_id).await?;
+            if let Some(schema) = compiled.schema() {
+                self.ensure_conforms_to_schema(&policy_store_id, compiled.policy_set(), schema)
+                    .await?;
+            }
+        }
+
+        // An empty id clears the default identity source; any other id must
+        // exist before anything is written
+        let default_identity_source_id = req
+            .default_identity_source_id
+            .map(|id| Some(id).filter(|id| !id.is_empty()));
+        if let Some(Some(identity_source_id)) = &default_identity_source_id {
+            self.repository
+                .get_identity_source(&policy_store_id, identity_source_id)
+                .await
+                .map_err(|e| {
+                    Status::invalid_argument(format!(
+                        "Invalid default identity source {}: {}",
+                        identity_source_id, e
+                    ))
+                })?;
+        }
+
         let store = self
             .repository
-            .update_policy_store(&policy_store_id, req.name, req.description, req.status)
+            .update_policy_store(
+                &policy_store_id,
+                req.name,
+                req.description,
+                req.status,
+                req.strict_validation,
+                default_identity_source_id,
+            )
             .await
             .map_err(|e| {
                 error!("Failed to update policy store: {}", e);
                 Status::internal(format!("Failed to update policy store: {}", e))
             })?;
+        if req.strict_validation.is_some() {
+            self.cache.refresh(&policy_store_id).await;
+        }
 
         Ok(Response::new(UpdatePolicyStoreResponse {
             policy_store_id: store.id.into_string(),
@@ -204,6 +815,8 @@ impl AuthorizationControl for AuthorizationControlService {
             description: store.description,
             status: Some(store.status.to_string()),
             updated_at: store.updated_at.to_rfc3339(),
+            strict_validation: store.strict_validation,
+            default_identity_source_id: store.default_identity_source_id,
         }))
     }
 
@@ -218,11 +831,18 @@ impl AuthorizationControl for AuthorizationControlService {
             .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
 
         // Validate schema format
-        Schema::from_str(&req.schema).map_err(|e| {
+        let schema = Schema::from_str(&req.schema).map_err(|e| {
             error!("Invalid schema format: {}", e);
             Status::invalid_argument(format!("Invalid schema format: {}", e))
         })?;
 
+        // A strict store must still load with the new schema
+        let compiled = self.load_compiled(&policy_store_id).await?;
+        if compiled.strict_validation() {
+            self.ensure_conforms_to_schema(&policy_store_id, compiled.policy_set(), &schema)
+                .await?;
+        }
+
         self.repository
             .put_schema(&policy_store_id, req.schema)
             .await
@@ -230,6 +850,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 error!("Failed to put schema: {}", e);
                 Status::internal(format!("Failed to put schema: {}", e))
             })?;
+        self.cache.refresh(&policy_store_id).await;
 
         Ok(Response::new(PutSchemaResponse {
             policy_store_id: req.policy_store_id,
@@ -285,10 +906,13 @@ impl AuthorizationControl for AuthorizationControlService {
             .definition
             .ok_or_else(|| Status::invalid_argument("Policy definition is required"))?;
 
-        let statement = match definition.policy_type {
+        self.ensure_not_template_id(&policy_store_id, policy_id.as_str())
+            .await?;
+
+        let (statement, template_link) = match definition.policy_type {
             Some(policy_definition::PolicyType::Static(static_policy)) => {
                 // Static policy - use as-is
-                static_policy.statement
+                (static_policy.statement, None)
             }
             Some(policy_definition::PolicyType::TemplateLinked(template_linked)) => {
                 // Template-linked policy - instantiate template with values
@@ -307,7 +931,10 @@ impl AuthorizationControl for AuthorizationControlService {
                         Status::not_found(format!("Policy template not found: {}", e))
                     })?;
 
-                // 2. Instantiate template with principal and resource
+                // 2. Link through Cedar so the policy is stored and evaluated as a link
+                let link = Self::build_template_link(&template, &policy_id, &template_linked)?;
+
+                // 3. Render the template with principal and resource for display
                 let mut instantiated = template.statement.clone();
 
                 // Replace ?principal placeholder
@@ -326,7 +953,7 @@ impl AuthorizationControl for AuthorizationControlService {
                     info!("Replaced ?resource with {}", resource_value);
                 }
 
-                // 3. Verify all placeholders were replaced
+                // 4. Verify all placeholders were replaced
                 if instantiated.contains("?principal") || instantiated.contains("?resource") {
                     return Err(Status::invalid_argument(
                         "Template contains placeholders that were not provided. \
@@ -335,7 +962,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 }
 
                 info!("Template instantiated successfully");
-                instantiated
+                (instantiated, Some(link))
             }
             None => {
                 return Err(Status::invalid_argument("Policy type is required"));
@@ -351,14 +978,30 @@ impl AuthorizationControl for AuthorizationControlService {
         let cedar_policy = CedarPolicy::new(statement)
             .map_err(|e| Status::invalid_argument(format!("Invalid policy: {}", e)))?;
 
-        let policy = self
-            .repository
-            .create_policy(&policy_store_id, &policy_id, &cedar_policy, req.description)
-            .await
-            .map_err(|e| {
-                error!("Failed to create policy: {}", e);
-                Status::internal(format!("Failed to create policy: {}", e))
-            })?;
+        let result = match &template_link {
+            Some(link) => {
+                self.repository
+                    .create_template_linked_policy(
+                        &policy_store_id,
+                        &policy_id,
+                        link,
+                        &cedar_policy,
+                        req.description,
+                    )
+                    .await
+            }
+            None => {
+                self.repository
+                    .create_policy(&policy_store_id, &policy_id, &cedar_policy, req.description)
+                    .await
+            }
+        };
+
+        let policy = result.map_err(|e| {
+            error!("Failed to create policy: {}", e);
+            Status::internal(format!("Failed to create policy: {}", e))
+        })?;
+        self.cache.refresh(&policy_store_id).await;
 
         Ok(Response::new(CreatePolicyResponse {
             policy_store_id: policy.policy_store_id.into_string(),
@@ -392,13 +1035,20 @@ impl AuthorizationControl for AuthorizationControlService {
                 Status::not_found(format!("Policy not found: {}", e))
             })?;
 
+        let policy_type = match policy.template_link {
+            Some(link) => policy_definition::PolicyType::TemplateLinked(
+                Self::template_linked_definition(link)?,
+            ),
+            None => policy_definition::PolicyType::Static(StaticPolicy {
+                statement: policy.statement.into_string(),
+            }),
+        };
+
         Ok(Response::new(GetPolicyResponse {
             policy_store_id: policy.policy_store_id.into_string(),
             policy_id: policy.policy_id.into_string(),
             definition: Some(PolicyDefinition {
-                policy_type: Some(policy_definition::PolicyType::Static(StaticPolicy {
-                    statement: policy.statement.into_string(),
-                })),
+                policy_type: Some(policy_type),
             }),
             description: policy.description,
             created_at: policy.created_at.to_rfc3339(),
@@ -426,8 +1076,10 @@ impl AuthorizationControl for AuthorizationControlService {
             .definition
             .ok_or_else(|| Status::invalid_argument("Policy definition is required"))?;
 
-        let statement = match definition.policy_type {
-            Some(policy_definition::PolicyType::Static(static_policy)) => static_policy.statement,
+        let (statement, template_link) = match definition.policy_type {
+            Some(policy_definition::PolicyType::Static(static_policy)) => {
+                (static_policy.statement, None)
+            }
             Some(policy_definition::PolicyType::TemplateLinked(template_linked)) => {
                 // Template-linked policy - instantiate template with values
                 info!(
@@ -445,6 +1097,8 @@ impl AuthorizationControl for AuthorizationControlService {
                         Status::not_found(format!("Policy template not found: {}", e))
                     })?;
 
+                let link = Self::build_template_link(&template, &policy_id, &template_linked)?;
+
                 let mut instantiated = template.statement.clone();
 
                 if let Some(principal) = &template_linked.principal {
@@ -465,7 +1119,7 @@ impl AuthorizationControl for AuthorizationControlService {
                     ));
                 }
 
-                instantiated
+                (instantiated, Some(link))
             }
             None => {
                 return Err(Status::invalid_argument("Policy type is required"));
@@ -481,14 +1135,30 @@ impl AuthorizationControl for AuthorizationControlService {
         let cedar_policy = CedarPolicy::new(statement)
             .map_err(|e| Status::invalid_argument(format!("Invalid policy: {}", e)))?;
 
-        let policy = self
-            .repository
-            .update_policy(&policy_store_id, &policy_id, &cedar_policy, req.description)
-            .await
-            .map_err(|e| {
-                error!("Failed to update policy: {}", e);
-                Status::internal(format!("Failed to update policy: {}", e))
-            })?;
+        let result = match &template_link {
+            Some(link) => {
+                self.repository
+                    .update_template_linked_policy(
+                        &policy_store_id,
+                        &policy_id,
+                        link,
+                        &cedar_policy,
+                        req.description,
+                    )
+                    .await
+            }
+            None => {
+                self.repository
+                    .update_policy(&policy_store_id, &policy_id, &cedar_policy, req.description)
+                    .await
+            }
+        };
+
+        let policy = result.map_err(|e| {
+            error!("Failed to update policy: {}", e);
+            Status::internal(format!("Failed to update policy: {}", e))
+        })?;
+        self.cache.refresh(&policy_store_id).await;
 
         Ok(Response::new(UpdatePolicyResponse {
             policy_store_id: policy.policy_store_id.into_string(),
@@ -520,6 +1190,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 error!("Failed to delete policy: {}", e);
                 Status::internal(format!("Failed to delete policy: {}", e))
             })?;
+        self.cache.refresh(&policy_store_id).await;
 
         Ok(Response::new(DeletePolicyResponse {}))
     }
@@ -579,37 +1250,143 @@ impl AuthorizationControl for AuthorizationControlService {
             .configuration
             .ok_or_else(|| Status::invalid_argument("Configuration is required"))?;
 
-        let (config_type, config_json) = match config.configuration_type {
+        let (config_type, mut config_json) = match config.configuration_type {
             Some(identity_source_configuration::ConfigurationType::Oidc(oidc)) => {
                 let json = serde_json::json!({
                     "issuer": oidc.issuer,
                     "client_ids": oidc.client_ids,
                     "jwks_uri": oidc.jwks_uri,
                     "group_claim": oidc.group_claim,
+                    "principal_entity_type": oidc.principal_entity_type.filter(|t| !t.is_empty()),
+                    "group_entity_type": oidc.group_entity_type.filter(|t| !t.is_empty()),
+                    "allowed_algorithms": Self::allowed_algorithms(&oidc.allowed_algorithms, false)?,
                 });
-                (IdentitySourceType::Oidc, json.to_string())
+                (IdentitySourceType::Oidc, json)
             }
             Some(identity_source_configuration::ConfigurationType::CognitoUserPool(cognito)) => {
+                // The data plane derives the issuer and JWKS URL from the ARN
+                CognitoProvider::from_arn(&cognito.user_pool_arn).map_err(|e| {
+                    Status::invalid_argument(format!("Invalid user pool ARN: {}", e))
+                })?;
                 let json = serde_json::json!({
                     "user_pool_arn": cognito.user_pool_arn,
                     "client_ids": cognito.client_ids,
                     "group_configuration_group_claim": cognito.group_configuration_group_claim,
+                    "principal_entity_type": cognito.principal_entity_type.filter(|t| !t.is_empty()),
+                    "group_entity_type": cognito.group_entity_type.filter(|t| !t.is_empty()),
+                    "allowed_algorithms": Self::allowed_algorithms(&cognito.allowed_algorithms, false)?,
                 });
-                (IdentitySourceType::Cognito, json.to_string())
+                (IdentitySourceType::Cognito, json)
+            }
+            Some(identity_source_configuration::ConfigurationType::SharedSecret(shared)) => {
+                if !self.shared_secret_identity_sources {
+                    return Err(Status::failed_precondition(
+                        "Shared-secret identity sources are disabled; set SHARED_SECRET_IDENTITY_SOURCES_ENABLED=true to allow them",
+                    ));
+                }
+                if shared.issuer.is_empty() {
+                    return Err(Status::invalid_argument("Issuer is required"));
+                }
+                if shared.secret.len() < MIN_SHARED_SECRET_LEN {
+                    return Err(Status::invalid_argument(format!(
+                        "Secret must be at least {} bytes",
+                        MIN_SHARED_SECRET_LEN
+                    )));
+                }
+                let json = serde_json::json!({
+                    "issuer": shared.issuer,
+                    "secret": shared.secret,
+                    "client_ids": shared.client_ids,
+                    "group_claim": shared.group_claim,
+                    "principal_entity_type": shared.principal_entity_type.filter(|t| !t.is_empty()),
+                    "group_entity_type": shared.group_entity_type.filter(|t| !t.is_empty()),
+                    "allowed_algorithms": Self::allowed_algorithms(&shared.allowed_algorithms, true)?,
+                });
+                (IdentitySourceType::SharedSecret, json)
+            }
+            Some(identity_source_configuration::ConfigurationType::Introspection(
+                introspection,
+            )) => {
+                if !introspection.introspection_endpoint.starts_with("https://")
+                    && !introspection.introspection_endpoint.starts_with("http://")
+                {
+                    return Err(Status::invalid_argument(
+                        "Introspection endpoint must be an HTTP(S) URL",
+                    ));
+                }
+                if introspection.issuer.is_empty() {
+                    return Err(Status::invalid_argument("Issuer is required"));
+                }
+                if introspection.client_id.is_empty() || introspection.client_secret.is_empty() {
+                    return Err(Status::invalid_argument(
+                        "Client ID and client secret are required to call the introspection endpoint",
+                    ));
+                }
+                let json = serde_json::json!({
+                    "introspection_endpoint": introspection.introspection_endpoint,
+                    "client_id": introspection.client_id,
+                    "client_secret": introspection.client_secret,
+                    "issuer": introspection.issuer,
+                    "client_ids": introspection.client_ids,
+                    "group_claim": introspection.group_claim,
+                    "principal_entity_type": introspection.principal_entity_type.filter(|t| !t.is_empty()),
+                    "group_entity_type": introspection.group_entity_type.filter(|t| !t.is_empty()),
+                    "cache_ttl_seconds": introspection.cache_ttl_seconds,
+                });
+                (IdentitySourceType::Introspection, json)
             }
             None => {
                 return Err(Status::invalid_argument("Configuration type is required"));
             }
         };
 
-        let claims_mapping_json = req.claims_mapping.map(|mapping| {
-            serde_json::json!({
-                "principal_id_claim": mapping.principal_id_claim,
-                "group_claim": mapping.group_claim,
-                "attribute_mappings": mapping.attribute_mappings,
-            })
-            .to_string()
-        });
+        // Entity types used for tokens must exist in the store schema
+        let mut entity_types = Vec::new();
+        for field in ["principal_entity_type", "group_entity_type"] {
+            if let Some(entity_type) = config_json[field].as_str() {
+                entity_types.push((field, entity_type.to_string()));
+            }
+        }
+        if let Some(mapping) = &req.claims_mapping {
+            if let Some(entity_type) = mapping
+                .principal_entity_type
+                .as_ref()
+                .filter(|t| !t.is_empty())
+            {
+                entity_types.push(("claims_mapping.principal_entity_type", entity_type.clone()));
+            }
+            for parent in &mapping.parent_mappings {
+                entity_types.push(("parent_mappings.entity_type", parent.entity_type.clone()));
+            }
+        }
+
+        if !entity_types.is_empty() {
+            let compiled = self
+                .cache
+                .get_compiled(&policy_store_id)
+                .await
+                .map_err(|e| {
+                    error!("Failed to load policy store: {}", e);
+                    Status::not_found(format!("Policy store not found: {}", e))
+                })?;
+            Self::validate_entity_types(compiled.schema(), &entity_types)?;
+        }
+        // The validation policy is stored with the rest of the configuration
+        if let Some(token_validation) = req.token_validation {
+            let policy = Self::token_validation_policy(token_validation)?;
+            config_json["token_validation"] = serde_json::to_value(policy).map_err(|e| {
+                Status::internal(format!("Failed to serialize token validation: {}", e))
+            })?;
+        }
+        let config_json = config_json.to_string();
+
+        let claims_mapping_json = req
+            .claims_mapping
+            .map(Self::claims_mapping_config)
+            .transpose()?
+            .map(|config| serde_json::to_string(&config))
+            .transpose()
+            .map_err(|e| Status::internal(format!("Failed to serialize claims mapping: {}", e)))?;
 
         let identity_source = self
             .repository
@@ -659,6 +1436,17 @@ impl AuthorizationControl for AuthorizationControlService {
             serde_json::from_str(&identity_source.configuration_json)
                 .map_err(|e| Status::internal(format!("Failed to parse config: {}", e)))?;
 
+        let strings = |field: &str| -> Vec<String> {
+            config_json[field]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
         let configuration_type = match identity_source.configuration_type {
             IdentitySourceType::Oidc => {
                 let oidc = OidcConfiguration {
@@ -666,14 +1454,7 @@ impl AuthorizationControl for AuthorizationControlService {
                         .as_str()
                         .unwrap_or_default()
                         .to_string(),
-                    client_ids: config_json["client_ids"]
-                        .as_array()
-                        .map(|arr| {
-                            arr.iter()
-                                .filter_map(|v| v.as_str().map(String::from))
-                                .collect()
-                        })
-                        .unwrap_or_default(),
+                    client_ids: strings("client_ids"),
                     jwks_uri: config_json["jwks_uri"]
                         .as_str()
                         .unwrap_or_default()
@@ -682,6 +1463,11 @@ impl AuthorizationControl for AuthorizationControlService {
                         .as_str()
                         .unwrap_or_default()
                         .to_string(),
+                    principal_entity_type: config_json["principal_entity_type"]
+                        .as_str()
+                        .map(String::from),
+                    group_entity_type: config_json["group_entity_type"].as_str().map(String::from),
+                    allowed_algorithms: strings("allowed_algorithms"),
                 };
                 Some(identity_source_configuration::ConfigurationType::Oidc(oidc))
             }
@@ -699,35 +1485,78 @@ impl AuthorizationControl for AuthorizationControlService {
                         .as_str()
                         .unwrap_or_default()
                         .to_string(),
+                    principal_entity_type: config_json["principal_entity_type"]
+                        .as_str()
+                        .map(String::from),
+                    group_entity_type: config_json["group_entity_type"].as_str().map(String::from),
+                    allowed_algorithms: strings("allowed_algorithms"),
                 };
                 Some(identity_source_configuration::ConfigurationType::CognitoUserPool(cognito))
             }
+            IdentitySourceType::SharedSecret => {
+                // The secret stays on the server
+                let shared = SharedSecretConfiguration {
+                    issuer: config_json["issuer"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    secret: String::new(),
+                    client_ids: strings("client_ids"),
+                    group_claim: config_json["group_claim"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    principal_entity_type: config_json["principal_entity_type"]
+                        .as_str()
+                        .map(String::from),
+                    group_entity_type: config_json["group_entity_type"].as_str().map(String::from),
+                    allowed_algorithms: strings("allowed_algorithms"),
+                };
+                Some(identity_source_configuration::ConfigurationType::SharedSecret(shared))
+            }
+            IdentitySourceType::Introspection => {
+                // The client secret stays on the server
+                let introspection = IntrospectionConfiguration {
+                    introspection_endpoint: config_json["introspection_endpoint"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    client_id: config_json["client_id"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    client_secret: String::new(),
+                    issuer: config_json["issuer"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    client_ids: strings("client_ids"),
+                    group_claim: config_json["group_claim"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    principal_entity_type: config_json["principal_entity_type"]
+                        .as_str()
+                        .map(String::from),
+                    group_entity_type: config_json["group_entity_type"].as_str().map(String::from),
+                    cache_ttl_seconds: config_json["cache_ttl_seconds"].as_u64(),
+                };
+                Some(identity_source_configuration::ConfigurationType::Introspection(introspection))
+            }
         };
 
         let claims_mapping = identity_source
             .claims_mapping_json
-            .as_ref()
-            .and_then(|json| {
-                serde_json::from_str::<serde_json::Value>(json)
-                    .ok()
-                    .map(|val| ClaimsMappingConfiguration {
-                        principal_id_claim: val["principal_id_claim"]
-                            .as_str()
-                            .unwrap_or_default()
-                            .to_string(),
-                        group_claim: val["group_claim"].as_str().unwrap_or_default().to_string(),
-                        attribute_mappings: val["attribute_mappings"]
-                            .as_object()
-                            .map(|obj| {
-                                obj.iter()
-                                    .map(|(k, v)| {
-                                        (k.clone(), v.as_str().unwrap_or_default().to_string())
-                                    })
-                                    .collect()
-                            })
-                            .unwrap_or_default(),
-                    })
-            });
+            .as_deref()
+            .and_then(|json| ClaimsMappingConfig::from_json(json).ok())
+            .map(Self::claims_mapping_definition);
+
+        let token_validation = serde_json::from_value::<Option<TokenValidationPolicy>>(
+            config_json["token_validation"].clone(),
+        )
+        .ok()
+        .flatten()
+        .map(Self::token_validation_definition);
 
         Ok(Response::new(GetIdentitySourceResponse {
             identity_source_id: identity_source.id,
@@ -737,6 +1566,7 @@ impl AuthorizationControl for AuthorizationControlService {
             description: identity_source.description,
             created_at: identity_source.created_at.to_rfc3339(),
             updated_at: identity_source.updated_at.to_rfc3339(),
+            token_validation,
         }))
     }
 
@@ -821,6 +1651,9 @@ impl AuthorizationControl for AuthorizationControlService {
         let policy_store_id = PolicyStoreId::new(req.policy_store_id)
             .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
 
+        self.ensure_not_policy_id(&policy_store_id, &req.template_id)
+            .await?;
+
         let template = self
             .repository
             .create_policy_template(
@@ -924,6 +1757,34 @@ impl AuthorizationControl for AuthorizationControlService {
         let policy_store_id = PolicyStoreId::new(req.policy_store_id)
             .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
 
+        // A template cannot be removed while policies are still linked to it
+        let policies = self
+            .repository
+            .list_policies(&policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list policies: {}", e);
+                Status::internal(format!("Failed to list policies: {}", e))
+            })?;
+
+        let linked: Vec<String> = policies
+            .into_iter()
+            .filter(|p| {
+                p.template_link
+                    .as_ref()
+                    .is_some_and(|link| link.policy_template_id == req.template_id)
+            })
+            .map(|p| p.policy_id.into_string())
+            .collect();
+
+        if !linked.is_empty() {
+            return Err(Status::failed_precondition(format!(
+                "Policy template {} is still linked by policies: {}",
+                req.template_id,
+                linked.join(", ")
+            )));
+        }
+
         self.repository
             .delete_policy_template(&policy_store_id, &req.template_id)
             .await
@@ -937,6 +1798,349 @@ impl AuthorizationControl for AuthorizationControlService {
         }))
     }
 
+    // ========================================================================
+    // Entity Store
+    // ========================================================================
+
+    async fn put_entities(
+        &self,
+        request: Request<PutEntitiesRequest>,
+    ) -> Result<Response<PutEntitiesResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Putting {} entities in policy store: {}",
+            req.entities.len(),
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        let compiled = self.load_compiled(&policy_store_id).await?;
+
+        let entities = req
+            .entities
+            .iter()
+            .map(|entity| Self::stored_entity(&policy_store_id, entity))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Validate with Cedar before persisting (entity types, UIDs, duplicates),
+        // and against the schema when the store has strict validation on
+        let schema = compiled.schema().filter(|_| compiled.strict_validation());
+        Entities::from_json_value(
+            serde_json::Value::Array(entities.iter().map(entity_json).collect()),
+            schema,
+        )
+        .map_err(|e| {
+            error!("Invalid entities: {}", e);
+            Status::invalid_argument(format!("Invalid entities: {}", e))
+        })?;
+
+        self.repository
+            .put_entities(&policy_store_id, &entities)
+            .await
+            .map_err(|e| {
+                error!("Failed to put entities: {}", e);
+                Status::internal(format!("Failed to put entities: {}", e))
+            })?;
+        self.cache.refresh(&policy_store_id).await;
+
+        Ok(Response::new(PutEntitiesResponse {
+            policy_store_id: req.policy_store_id,
+            entities_stored: entities.len() as i32,
+        }))
+    }
+
+    async fn get_entities(
+        &self,
+        request: Request<GetEntitiesRequest>,
+    ) -> Result<Response<GetEntitiesResponse>, Status> {
+        let req = request.into_inner();
+        info!("Getting entities from policy store: {}", req.policy_store_id);
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        let entities = if req.identifiers.is_empty() {
+            self.repository.list_entities(&policy_store_id).await
+        } else {
+            let keys = req
+                .identifiers
+                .iter()
+                .map(Self::entity_key)
+                .collect::<Result<Vec<_>, _>>()?;
+            self.repository.get_entities(&policy_store_id, &keys).await
+        }
+        .map_err(|e| {
+            error!("Failed to get entities: {}", e);
+            Status::internal(format!("Failed to get entities: {}", e))
+        })?;
+
+        Ok(Response::new(GetEntitiesResponse {
+            entities: entities.into_iter().map(Self::entity_message).collect(),
+        }))
+    }
+
+    async fn delete_entities(
+        &self,
+        request: Request<DeleteEntitiesRequest>,
+    ) -> Result<Response<DeleteEntitiesResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Deleting {} entities from policy store: {}",
+            req.identifiers.len(),
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        if req.identifiers.is_empty() {
+            return Err(Status::invalid_argument(
+                "At least one entity identifier is required",
+            ));
+        }
+
+        let keys = req
+            .identifiers
+            .iter()
+            .map(Self::entity_key)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let deleted = self
+            .repository
+            .delete_entities(&policy_store_id, &keys)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete entities: {}", e);
+                Status::internal(format!("Failed to delete entities: {}", e))
+            })?;
+        self.cache.refresh(&policy_store_id).await;
+
+        Ok(Response::new(DeleteEntitiesResponse {
+            policy_store_id: req.policy_store_id,
+            entities_deleted: deleted as i32,
+        }))
+    }
+
+    // ========================================================================
+    // Token Revocation
+    // ========================================================================
+
+    async fn revoke_tokens(
+        &self,
+        request: Request<RevokeTokensRequest>,
+    ) -> Result<Response<RevokeTokensResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Revoking {} token claims in policy store: {}",
+            req.revocations.len(),
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        self.repository
+            .get_policy_store(&policy_store_id)
+            .await
+            .map_err(|e| Status::not_found(format!("Policy store not found: {}", e)))?;
+
+        let revocations = req
+            .revocations
+            .iter()
+            .map(|revocation| Self::stored_revocation(&policy_store_id, revocation))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.repository
+            .put_token_revocations(&policy_store_id, &revocations)
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke tokens: {}", e);
+                Status::internal(format!("Failed to revoke tokens: {}", e))
+            })?;
+        // Token authorization reads the denylist from the compiled store
+        self.cache.refresh(&policy_store_id).await;
+
+        Ok(Response::new(RevokeTokensResponse {
+            policy_store_id: req.policy_store_id,
+            revocations_stored: revocations.len() as i32,
+        }))
+    }
+
+    async fn list_token_revocations(
+        &self,
+        request: Request<ListTokenRevocationsRequest>,
+    ) -> Result<Response<ListTokenRevocationsResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Listing token revocations of policy store: {}",
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        let revocations = self
+            .repository
+            .list_token_revocations(&policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list token revocations: {}", e);
+                Status::internal(format!("Failed to list token revocations: {}", e))
+            })?;
+
+        Ok(Response::new(ListTokenRevocationsResponse {
+            revocations: revocations
+                .into_iter()
+                .map(Self::revocation_message)
+                .collect(),
+        }))
+    }
+
+    async fn delete_token_revocations(
+        &self,
+        request: Request<DeleteTokenRevocationsRequest>,
+    ) -> Result<Response<DeleteTokenRevocationsResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Deleting {} token revocations from policy store: {}",
+            req.revocations.len(),
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        if req.revocations.is_empty() {
+            return Err(Status::invalid_argument(
+                "At least one token revocation is required",
+            ));
+        }
+
+        let keys = req
+            .revocations
+            .iter()
+            .map(Self::revocation_key)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let deleted = self
+            .repository
+            .delete_token_revocations(&policy_store_id, &keys)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete token revocations: {}", e);
+                Status::internal(format!("Failed to delete token revocations: {}", e))
+            })?;
+        self.cache.refresh(&policy_store_id).await;
+
+        Ok(Response::new(DeleteTokenRevocationsResponse {
+            policy_store_id: req.policy_store_id,
+            revocations_deleted: deleted as i32,
+        }))
+    }
+
+    // ========================================================================
+    // Decision Logs
+    // ========================================================================
+
+    async fn query_decision_logs(
+        &self,
+        request: Request<QueryDecisionLogsRequest>,
+    ) -> Result<Response<QueryDecisionLogsResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Querying decision logs for policy store: {}",
+            req.policy_store_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        let parse_time = |value: Option<&str>, field: &str| {
+            value
+                .map(|v| {
+                    DateTime::parse_from_rfc3339(v)
+                        .map(|t| t.with_timezone(&Utc))
+                        .map_err(|e| Status::invalid_argument(format!("Invalid {}: {}", field, e)))
+                })
+                .transpose()
+        };
+
+        let decision = match req.decision {
+            Some(value) => match crate::proto::Decision::try_from(value) {
+                Ok(crate::proto::Decision::Unspecified) | Err(_) => {
+                    return Err(Status::invalid_argument("Invalid decision filter"));
+                }
+                Ok(decision) => Some(decision.as_str_name().to_string()),
+            },
+            None => None,
+        };
+
+        let before_id = req
+            .next_token
+            .as_deref()
+            .map(|token| {
+                token
+                    .parse::<i64>()
+                    .map_err(|_| Status::invalid_argument("Invalid next_token"))
+            })
+            .transpose()?;
+
+        let limit = req.max_results.unwrap_or(100).clamp(1, 1000) as u32;
+
+        let filter = DecisionLogFilter {
+            start_time: parse_time(req.start_time.as_deref(), "start_time")?,
+            end_time: parse_time(req.end_time.as_deref(), "end_time")?,
+            principal: req.principal,
+            action: req.action,
+            resource: req.resource,
+            decision,
+            before_id,
+            limit,
+        };
+
+        let logs = self
+            .repository
+            .query_decision_logs(&policy_store_id, &filter)
+            .await
+            .map_err(|e| {
+                error!("Failed to query decision logs: {}", e);
+                Status::internal(format!("Failed to query decision logs: {}", e))
+            })?;
+
+        // A full page may be followed by more decisions
+        let next_token = if logs.len() == limit as usize {
+            logs.last().and_then(|log| log.id).map(|id| id.to_string())
+        } else {
+            None
+        };
+
+        let entries = logs
+            .into_iter()
+            .map(|log| DecisionLogEntry {
+                policy_store_id: log.policy_store_id.into_string(),
+                principal: log.principal,
+                action: log.action,
+                resource: log.resource,
+                decision: crate::proto::Decision::from_str_name(&log.decision)
+                    .unwrap_or(crate::proto::Decision::Unspecified)
+                    as i32,
+                determining_policies: log.determining_policies,
+                errors: log.errors,
+                latency_micros: i64::try_from(log.latency_micros).unwrap_or(i64::MAX),
+                caller: log.caller.into_iter().collect(),
+                correlation_id: log.correlation_id,
+                timestamp: log.timestamp.to_rfc3339(),
+            })
+            .collect();
+
+        Ok(Response::new(QueryDecisionLogsResponse {
+            entries,
+            next_token,
+        }))
+    }
+
     // ========================================================================
     // Playground / Testing Endpoints
     // ========================================================================
@@ -1381,6 +2585,7 @@ impl AuthorizationControl for AuthorizationControlService {
                 error!("Failed to rollback to snapshot: {}", e);
                 Status::internal(format!("Failed to rollback to snapshot: {}", e))
             })?;
+        self.cache.refresh(&policy_store_id).await;
 
         Ok(Response::new(RollbackToSnapshotResponse {
             policy_store_id: result.policy_store_id.into_string(),
@@ -1503,6 +2708,19 @@ impl AuthorizationControl for AuthorizationControlService {
                 }
             };
 
+            if let Err(status) = self
+                .ensure_not_template_id(&policy_store_id, policy_id.as_str())
+                .await
+            {
+                errors.push(format!("{}: {}", item.policy_id, status.message()));
+                results.push(BatchPolicyResult {
+                    policy_id: item.policy_id,
+                    created_at: String::new(),
+                    error: Some(status.message().to_string()),
+                });
+                continue;
+            }
+
             match self
                 .repository
                 .create_policy(
@@ -1532,6 +2750,8 @@ impl AuthorizationControl for AuthorizationControlService {
             }
         }
 
+        self.cache.refresh(&policy_store_id).await;
+
         Ok(Response::new(BatchCreatePoliciesResponse {
             results,
             errors,
@@ -1649,6 +2869,8 @@ impl AuthorizationControl for AuthorizationControlService {
             }
         }
 
+        self.cache.refresh(&policy_store_id).await;
+
         Ok(Response::new(BatchUpdatePoliciesResponse {
             results,
             errors,
@@ -1707,6 +2929,8 @@ impl AuthorizationControl for AuthorizationControlService {
             }
         }
 
+        self.cache.refresh(&policy_store_id).await;
+
         Ok(Response::new(BatchDeletePoliciesResponse {
             results,
             errors,
@@ -1747,3 +2971,251 @@ impl AuthorizationControl for AuthorizationControlService {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_service() -> AuthorizationControlService {
+        let repository = Arc::new(RepositoryAdapter::new(":memory:").await.unwrap());
+        let event_store = EventStoreBox::new("sqlite::memory:").await.unwrap();
+        let dispatcher = Arc::new(EventDispatcher::new(InMemoryEventBus::new(), event_store));
+        let cache = Arc::new(CacheManager::new(repository.clone()));
+        AuthorizationControlService::new(repository, dispatcher, cache)
+    }
+
+    async fn create_store(service: &AuthorizationControlService) -> String {
+        service
+            .create_policy_store(Request::new(CreatePolicyStoreRequest {
+                name: "Store".to_string(),
+                description: None,
+                tags: vec![],
+                user: "test".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .policy_store_id
+    }
+
+    fn static_policy(store: &str, policy_id: &str, statement: &str) -> CreatePolicyRequest {
+        CreatePolicyRequest {
+            policy_store_id: store.to_string(),
+            policy_id: policy_id.to_string(),
+            definition: Some(PolicyDefinition {
+                policy_type: Some(policy_definition::PolicyType::Static(StaticPolicy {
+                    statement: statement.to_string(),
+                })),
+            }),
+            description: None,
+        }
+    }
+
+    fn template(store: &str, template_id: &str) -> CreatePolicyTemplateRequest {
+        CreatePolicyTemplateRequest {
+            policy_store_id: store.to_string(),
+            template_id: template_id.to_string(),
+            statement: "permit(principal == ?principal, action, resource);".to_string(),
+            description: None,
+        }
+    }
+
+    /// Puts a schema with a typed `User` entity and turns strict validation on
+    async fn make_strict(service: &AuthorizationControlService, store: &str) {
+        service
+            .put_schema(Request::new(PutSchemaRequest {
+                policy_store_id: store.to_string(),
+                schema: r#"
+                    entity User { level: Long };
+                    action view appliesTo { principal: User, resource: User };
+                "#
+                .to_string(),
+            }))
+            .await
+            .unwrap();
+        service
+            .update_policy_store(Request::new(UpdatePolicyStoreRequest {
+                policy_store_id: store.to_string(),
+                name: None,
+                description: None,
+                status: None,
+                strict_validation: Some(true),
+                default_identity_source_id: None,
+            }))
+            .await
+            .unwrap();
+    }
+
+    fn user(level: &str) -> Entity {
+        Entity {
+            identifier: Some(EntityIdentifier {
+                entity_type: "User".to_string(),
+                entity_id: "alice".to_string(),
+            }),
+            attributes: [("level".to_string(), level.to_string())].into(),
+            parents: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_entities_validates_against_strict_schema() {
+        let service = create_service().await;
+        let store = create_store(&service).await;
+        make_strict(&service, &store).await;
+
+        let put = |entity: Entity| PutEntitiesRequest {
+            policy_store_id: store.clone(),
+            entities: vec![entity],
+        };
+
+        let err = service
+            .put_entities(Request::new(put(user(r#""high""#))))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let stored = service
+            .put_entities(Request::new(put(user("3"))))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stored.entities_stored, 1);
+    }
+
+    #[tokio::test]
+    async fn test_template_and_policy_ids_cannot_collide() {
+        let service = create_service().await;
+        let store = create_store(&service).await;
+
+        service
+            .create_policy(Request::new(static_policy(
+                &store,
+                "shared-id",
+                "permit(principal, action, resource);",
+            )))
+            .await
+            .unwrap();
+        let err = service
+            .create_policy_template(Request::new(template(&store, "shared-id")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+        service
+            .create_policy_template(Request::new(template(&store, "tmpl")))
+            .await
+            .unwrap();
+        let err = service
+            .create_policy(Request::new(static_policy(
+                &store,
+                "tmpl",
+                "permit(principal, action, resource);",
+            )))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::AlreadyExists);
+    }
+
+    #[tokio::test]
+    async fn test_put_schema_rejects_schema_stored_entities_do_not_conform_to() {
+        let service = create_service().await;
+        let store = create_store(&service).await;
+        make_strict(&service, &store).await;
+        service
+            .put_entities(Request::new(PutEntitiesRequest {
+                policy_store_id: store.clone(),
+                entities: vec![user("3")],
+            }))
+            .await
+            .unwrap();
+
+        let err = service
+            .put_schema(Request::new(PutSchemaRequest {
+                policy_store_id: store.clone(),
+                schema: "entity User { level: String };".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("Stored entities"));
+
+        // The old schema is kept and the store still loads
+        let schema = service
+            .get_schema(Request::new(GetSchemaRequest {
+                policy_store_id: store.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(schema.schema.contains("Long"));
+        let policy_store_id = PolicyStoreId::new(store).unwrap();
+        assert!(service.cache.get_compiled(&policy_store_id).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_strict_validation_rejected_when_stored_entities_do_not_conform() {
+        let service = create_service().await;
+        let store = create_store(&service).await;
+        service
+            .put_schema(Request::new(PutSchemaRequest {
+                policy_store_id: store.clone(),
+                schema: "entity User { level: Long };".to_string(),
+            }))
+            .await
+            .unwrap();
+        service
+            .put_entities(Request::new(PutEntitiesRequest {
+                policy_store_id: store.clone(),
+                entities: vec![user(r#""high""#)],
+            }))
+            .await
+            .unwrap();
+
+        let err = service
+            .update_policy_store(Request::new(UpdatePolicyStoreRequest {
+                policy_store_id: store.clone(),
+                name: None,
+                description: None,
+                status: None,
+                strict_validation: Some(true),
+                default_identity_source_id: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("Stored entities"));
+
+        // The store stays non-strict and keeps serving
+        let policy_store_id = PolicyStoreId::new(store).unwrap();
+        let compiled = service.cache.get_compiled(&policy_store_id).await.unwrap();
+        assert!(!compiled.strict_validation());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_default_identity_source_leaves_store_unchanged() {
+        let service = create_service().await;
+        let store = create_store(&service).await;
+
+        let err = service
+            .update_policy_store(Request::new(UpdatePolicyStoreRequest {
+                policy_store_id: store.clone(),
+                name: Some("Renamed".to_string()),
+                description: None,
+                status: None,
+                strict_validation: None,
+                default_identity_source_id: Some("missing".to_string()),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        let policy_store_id = PolicyStoreId::new(store).unwrap();
+        let stored = service
+            .repository
+            .get_policy_store(&policy_store_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.name, "Store");
+        assert_eq!(stored.default_identity_source_id, None);
+    }
+}
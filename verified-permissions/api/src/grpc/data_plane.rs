@@ -2,218 +2,410 @@
 
 use crate::proto::authorization_data_server::AuthorizationData;
 use crate::proto::*;
-use cedar_policy::{Authorizer, Context, Entities, EntityUid, PolicySet, Request as CedarRequest};
+use cedar_policy::{
+    ActionConstraint, Authorizer, Context, Effect, Entities, EntityTypeName, EntityUid, Policy,
+    PolicyId, PolicySet, PrincipalConstraint, Request as CedarRequest, RequestBuilder,
+    RequestValidationError, ResourceConstraint, Schema,
+};
+use chrono::Utc;
 use hodei_domain::{
-    DomainEventEnvelope, EventBusPort, EventDispatcher, EventStorePort, PolicyRepository, PolicyStoreId,
+    DecisionLog, DomainEventEnvelope, EntityKey, IdentitySource, IdentitySourceType,
+    PolicyRepository, PolicyStoreId, RevokedClaim,
+};
+use hodei_infrastructure::cache::policy_store_cache::entity_uid_json;
+use hodei_infrastructure::cache::{CacheManager, CompiledPolicyStore};
+use hodei_infrastructure::decision_log::DecisionLogWriter;
+use hodei_infrastructure::error::AuthorizationError;
+use hodei_infrastructure::jwt::claims_mapper::EntityId;
+use hodei_infrastructure::jwt::introspection::DEFAULT_INTROSPECTION_CACHE_TTL_SECONDS;
+use hodei_infrastructure::jwt::issuer_detection::extract_issuer_from_token;
+use hodei_infrastructure::jwt::providers::{CognitoProvider, IdentityProvider};
+use hodei_infrastructure::jwt::{
+    AlgorithmAllowlist, AudienceCheck, ClaimsMapper, ClaimsMappingConfig, IntrospectionEndpoint,
+    JwksCache, JwtValidator, PossessionProof, SenderConstraintVerifier, TokenIntrospector,
+    TokenValidationPolicy, ValidatedClaims,
 };
-use hodei_infrastructure::jwt::JwtValidator;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
-use tonic::{Request, Response, Status};
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, mpsc};
+use tokio::task::JoinSet;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataValue;
+use tonic::{Request, Response, Status, Streaming};
 use tracing::{error, info};
 use async_trait::async_trait;
 
+/// Principal resolved from a validated token
+struct TokenPrincipal {
+    principal: EntityIdentifier,
+    /// Principal entity with its mapped attributes and parents
+    entities: Vec<Entity>,
+    claims: ValidatedClaims,
+    claims_mapping: ClaimsMappingConfig,
+}
+
+/// Where and how the tokens of an identity source are verified
+struct TokenIssuer {
+    issuer: String,
+    /// Found through OIDC discovery of the issuer when not configured
+    jwks_uri: Option<String>,
+    client_ids: Vec<String>,
+    /// Signature algorithms accepted for the source's tokens
+    algorithms: AlgorithmAllowlist,
+    /// Clock skew, token age, audience and required claim checks
+    policy: TokenValidationPolicy,
+    /// HMAC secret of shared-secret sources, used instead of JWKS keys
+    secret: Option<String>,
+    /// Cognito tokens need provider-specific claim checks
+    cognito: Option<CognitoProvider>,
+    /// Opaque tokens are validated by this endpoint instead of a signature
+    introspection: Option<IntrospectionEndpoint>,
+}
+
+/// Default cap on batch items evaluated at once
+pub const DEFAULT_BATCH_MAX_CONCURRENCY: usize = 16;
+
 pub struct AuthorizationDataService<R> {
     repository: Arc<R>,
+    cache: Arc<CacheManager>,
     jwt_validator: JwtValidator,
+    token_introspector: Arc<TokenIntrospector>,
+    sender_constraint_verifier: Arc<SenderConstraintVerifier>,
+    batch_max_concurrency: usize,
+    decision_log: Option<DecisionLogWriter>,
+    shared_secret_identity_sources: bool,
 }
 
 impl<R> AuthorizationDataService<R>
 where
     R: PolicyRepository + Send + Sync + 'static,
 {
-    pub fn new(repository: Arc<R>) -> Self {
+    pub fn new(repository: Arc<R>, cache: Arc<CacheManager>) -> Self {
         Self {
             repository,
+            cache,
             jwt_validator: JwtValidator::new(),
+            token_introspector: Arc::new(TokenIntrospector::new()),
+            sender_constraint_verifier: Arc::new(SenderConstraintVerifier::new()),
+            batch_max_concurrency: DEFAULT_BATCH_MAX_CONCURRENCY,
+            decision_log: None,
+            shared_secret_identity_sources: false,
         }
     }
 
+    /// Validates tokens with keys from the given JWKS cache
+    ///
+    /// Lets the cache be shared, e.g. with its background refresh task.
+    pub fn with_jwks_cache(mut self, jwks_cache: Arc<JwksCache>) -> Self {
+        self.jwt_validator = JwtValidator::with_jwks_cache(jwks_cache);
+        self
+    }
+
+    /// Introspects opaque tokens with the given client and response cache
+    pub fn with_token_introspector(mut self, token_introspector: Arc<TokenIntrospector>) -> Self {
+        self.token_introspector = token_introspector;
+        self
+    }
+
+    /// Records every decision through the given decision log writer
+    pub fn with_decision_log(mut self, decision_log: DecisionLogWriter) -> Self {
+        self.decision_log = Some(decision_log);
+        self
+    }
+
+    /// Accepts tokens of shared-secret (HS256) identity sources
+    pub fn with_shared_secret_identity_sources(mut self, enabled: bool) -> Self {
+        self.shared_secret_identity_sources = enabled;
+        self
+    }
+
+    /// Sets how many batch items may be evaluated at once
+    pub fn with_batch_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.batch_max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Gets the compiled policy set and schema of a store from the cache
+    async fn load_compiled(
+        cache: &CacheManager,
+        policy_store_id: &PolicyStoreId,
+    ) -> Result<Arc<CompiledPolicyStore>, Status> {
+        cache
+            .get_compiled(policy_store_id)
+            .await
+            .map_err(|e| match e {
+                AuthorizationError::NotFound(msg) => Status::not_found(msg),
+                e => {
+                    error!("Failed to load policies: {}", e);
+                    Status::internal(format!("Failed to load policies: {}", e))
+                }
+            })
+    }
+
     async fn publish_event(&self, _event: DomainEventEnvelope) {
         // Event publishing not implemented
         info!("Event would be published here");
     }
 
-    fn build_entity_uid(identifier: &EntityIdentifier) -> Result<EntityUid, Status> {
+    /// InvalidArgument error pointing at the offending request field
+    ///
+    /// The field name is also sent in the `x-invalid-field` metadata so that
+    /// clients can tell which input was rejected without parsing the message.
+    fn invalid_field(field: &str, error: impl std::fmt::Display) -> Status {
+        let mut status = Status::invalid_argument(format!("Invalid {}: {}", field, error));
+        if let Ok(value) = MetadataValue::try_from(field) {
+            status.metadata_mut().insert("x-invalid-field", value);
+        }
+        status
+    }
+
+    /// Maps a request validation error to the request field it refers to
+    fn request_error_field(error: &RequestValidationError) -> &'static str {
+        match error {
+            RequestValidationError::UndeclaredAction(_) => "action",
+            RequestValidationError::UndeclaredPrincipalType(_)
+            | RequestValidationError::InvalidPrincipalType(_) => "principal",
+            RequestValidationError::UndeclaredResourceType(_)
+            | RequestValidationError::InvalidResourceType(_) => "resource",
+            RequestValidationError::InvalidContext(_)
+            | RequestValidationError::TypeOfContext(_) => "context",
+            _ => "request",
+        }
+    }
+
+    /// Schema to validate requests with, when the store has strict validation on
+    fn validation_schema(compiled: &CompiledPolicyStore) -> Result<Option<&Schema>, Status> {
+        if !compiled.strict_validation() {
+            return Ok(None);
+        }
+        compiled.schema().map(Some).ok_or_else(|| {
+            Status::failed_precondition(
+                "Strict validation is enabled but the policy store has no schema",
+            )
+        })
+    }
+
+    fn build_entity_uid(identifier: &EntityIdentifier, field: &str) -> Result<EntityUid, Status> {
         EntityUid::from_str(&format!(
             "{}::\"{}\"",
             identifier.entity_type, identifier.entity_id
         ))
         .map_err(|e| {
-            error!("Failed to parse entity UID: {}", e);
-            Status::invalid_argument(format!("Invalid entity identifier: {}", e))
+            error!("Failed to parse {} UID: {}", field, e);
+            Self::invalid_field(field, e)
         })
     }
 
-    fn build_entities(entities: &[Entity]) -> Result<Entities, Status> {
-        let mut entities_json = Vec::new();
-
-        for entity in entities {
-            let uid = Self::build_entity_uid(
-                entity
-                    .identifier
-                    .as_ref()
-                    .ok_or_else(|| Status::invalid_argument("Entity identifier is required"))?,
-            )?;
-
-            let mut entity_obj = serde_json::json!({
-                "uid": uid.to_string(),
-                "attrs": {},
-                "parents": []
-            });
+    fn entity_key(identifier: &EntityIdentifier) -> EntityKey {
+        EntityKey::new(identifier.entity_type.clone(), identifier.entity_id.clone())
+    }
 
-            // Add attributes
-            if !entity.attributes.is_empty() {
-                let mut attrs = HashMap::new();
-                for (key, value) in &entity.attributes {
-                    // Parse JSON value
-                    let parsed_value: serde_json::Value = serde_json::from_str(value)
-                        .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
-                    attrs.insert(key.clone(), parsed_value);
-                }
-                entity_obj["attrs"] = serde_json::json!(attrs);
-            }
+    fn build_entity_type(type_name: &str, field: &str) -> Result<EntityTypeName, Status> {
+        EntityTypeName::from_str(type_name).map_err(|e| {
+            error!("Failed to parse {}: {}", field, e);
+            Self::invalid_field(field, e)
+        })
+    }
 
-            // Add parents
-            if !entity.parents.is_empty() {
-                let parents: Result<Vec<String>, Status> = entity
-                    .parents
-                    .iter()
-                    .map(|p| Ok(Self::build_entity_uid(p)?.to_string()))
-                    .collect();
-                entity_obj["parents"] = serde_json::json!(parents?);
-            }
+    /// Actions of the schema that apply to the given principal and resource
+    fn applicable_actions<'a>(
+        schema: &'a Schema,
+        principal: &EntityUid,
+        resource: &EntityUid,
+    ) -> Vec<&'a EntityUid> {
+        let mut actions: Vec<&EntityUid> = schema
+            .actions()
+            .filter(|action| {
+                schema
+                    .principals_for_action(action)
+                    .is_some_and(|mut types| types.any(|t| t == principal.type_name()))
+                    && schema
+                        .resources_for_action(action)
+                        .is_some_and(|mut types| types.any(|t| t == resource.type_name()))
+            })
+            .collect();
+        actions.sort_by_key(|action| action.to_string());
+        actions
+    }
 
-            entities_json.push(entity_obj);
-        }
+    /// Converts a residual policy to its text and JSON representations
+    fn residual_policy(policy: &cedar_policy::Policy) -> Result<ResidualPolicy, Status> {
+        let json = policy.to_json().map_err(|e| {
+            error!("Failed to serialize residual policy {}: {}", policy.id(), e);
+            Status::internal(format!(
+                "Failed to serialize residual policy {}: {}",
+                policy.id(),
+                e
+            ))
+        })?;
 
-        Entities::from_json_value(serde_json::Value::Array(entities_json), None).map_err(|e| {
-            error!("Failed to build entities: {}", e);
-            Status::invalid_argument(format!("Invalid entities: {}", e))
+        Ok(ResidualPolicy {
+            policy_id: policy.id().to_string(),
+            cedar: policy.to_string(),
+            json: json.to_string(),
         })
     }
 
-    fn build_context(context_json: Option<&str>) -> Result<Context, Status> {
-        if let Some(json_str) = context_json {
-            let value: serde_json::Value = serde_json::from_str(json_str).map_err(|e| {
-                error!("Failed to parse context JSON: {}", e);
-                Status::invalid_argument(format!("Invalid context JSON: {}", e))
-            })?;
-
-            Context::from_json_value(value, None).map_err(|e| {
-                error!("Failed to build context: {}", e);
-                Status::invalid_argument(format!("Invalid context: {}", e))
-            })
-        } else {
-            Ok(Context::empty())
+    /// Builds the Cedar entities for a request
+    ///
+    /// The request slice is laid over the store's entities: a request entity
+    /// replaces the stored entity with the same UID. Without a request slice
+    /// the store's entities are used as they are.
+    fn build_entities<'a>(
+        entities: &[Entity],
+        stored: &'a Entities,
+        schema: Option<&Schema>,
+    ) -> Result<Cow<'a, Entities>, Status> {
+        if entities.is_empty() {
+            return Ok(Cow::Borrowed(stored));
         }
-    }
-}
 
-#[async_trait]
-impl<R> AuthorizationData for AuthorizationDataService<R>
-where
-    R: PolicyRepository + Send + Sync + 'static,
-{
-    async fn is_authorized(
-        &self,
-        request: Request<IsAuthorizedRequest>,
-    ) -> Result<Response<IsAuthorizedResponse>, Status> {
-        let req = request.into_inner();
-        info!(
-            "Authorization request for policy store: {}",
-            req.policy_store_id
-        );
+        let mut entities_json = Vec::with_capacity(entities.len());
+        for entity in entities {
+            let identifier = entity
+                .identifier
+                .as_ref()
+                .ok_or_else(|| Self::invalid_field("entities", "entity identifier is required"))?;
+            let key = Self::entity_key(identifier);
 
-        // 1. Parse policy store ID
-        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
-            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+            // Add attributes
+            let mut attrs = serde_json::Map::new();
+            for (name, value) in &entity.attributes {
+                // Parse JSON value
+                let parsed_value: serde_json::Value = serde_json::from_str(value)
+                    .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+                attrs.insert(name.clone(), parsed_value);
+            }
 
-        // 2. Load policies from database
-        let policies = self
-            .repository
-            .list_policies(&policy_store_id)
-            .await
-            .map_err(|e| {
-                error!("Failed to load policies: {}", e);
-                Status::internal(format!("Failed to load policies: {}", e))
-            })?;
+            // Add parents
+            let parents: Vec<serde_json::Value> = entity
+                .parents
+                .iter()
+                .map(|p| entity_uid_json(&Self::entity_key(p)))
+                .collect();
 
-        if policies.is_empty() {
-            info!(
-                "No policies found for policy store: {}",
-                req.policy_store_id
-            );
-            return Ok(Response::new(IsAuthorizedResponse {
-                decision: Decision::Deny as i32,
-                determining_policies: vec![],
-                errors: vec!["No policies found in policy store".to_string()],
+            entities_json.push(serde_json::json!({
+                "uid": entity_uid_json(&key),
+                "attrs": attrs,
+                "parents": parents,
             }));
         }
 
-        // 3. Build Cedar PolicySet
-        let mut policy_set_str = String::new();
-        for policy in &policies {
-            policy_set_str.push_str(policy.statement.as_str());
-            policy_set_str.push('\n');
-        }
+        let invalid_entities = |e: cedar_policy::entities_errors::EntitiesError| {
+            error!("Failed to build entities: {}", e);
+            Self::invalid_field("entities", e)
+        };
+        let request_entities =
+            Entities::from_json_value(serde_json::Value::Array(entities_json), schema)
+                .map_err(invalid_entities)?;
 
-        let policy_set = PolicySet::from_str(&policy_set_str).map_err(|e| {
-            error!("Failed to parse policy set: {}", e);
-            Status::internal(format!("Failed to parse policy set: {}", e))
-        })?;
+        stored
+            .clone()
+            .upsert_entities(request_entities.iter().cloned(), schema)
+            .map(Cow::Owned)
+            .map_err(invalid_entities)
+    }
+
+    /// Evaluates a request against a compiled policy store
+    ///
+    /// `entities` are used as given when present; otherwise they are built
+    /// from the request slice laid over the store's entities.
+    fn evaluate(
+        compiled: &CompiledPolicyStore,
+        req: &IsAuthorizedRequest,
+        entities: Option<&Entities>,
+    ) -> Result<IsAuthorizedResponse, Status> {
+        // 1. Build Cedar entities
+        let schema = Self::validation_schema(compiled)?;
 
-        // 4. Build Cedar entities
         let principal = Self::build_entity_uid(
             req.principal
                 .as_ref()
                 .ok_or_else(|| Status::invalid_argument("Principal is required"))?,
+            "principal",
         )?;
 
         let action = Self::build_entity_uid(
             req.action
                 .as_ref()
                 .ok_or_else(|| Status::invalid_argument("Action is required"))?,
+            "action",
         )?;
 
+        // The context is typed by the action, so reject unknown actions first
+        if let Some(schema) = schema
+            && !schema.actions().any(|declared| declared == &action)
+        {
+            return Err(Self::invalid_field(
+                "action",
+                format!("{} is not declared in the schema", action),
+            ));
+        }
+
         let resource = Self::build_entity_uid(
             req.resource
                 .as_ref()
                 .ok_or_else(|| Status::invalid_argument("Resource is required"))?,
+            "resource",
         )?;
 
-        // 5. Build context
-        let context = Self::build_context(req.context.as_deref())?;
+        // 2. Build context
+        let context = Self::build_context(req.context.as_deref(), schema, &action)?;
 
-        // 6. Build entities slice
-        let entities = Self::build_entities(&req.entities)?;
+        // 3. Build entities: stored entities overlaid with the request slice
+        let built;
+        let entities = match entities {
+            Some(entities) => entities,
+            None => {
+                built = Self::build_entities(&req.entities, compiled.entities(), schema)?;
+                &*built
+            }
+        };
 
-        // 7. Create Cedar request
+        // 4. Create Cedar request (validated when the store is strict)
         let cedar_request =
-            CedarRequest::new(principal, action, resource, context, None).map_err(|e| {
+            CedarRequest::new(principal, action, resource, context, schema).map_err(|e| {
                 error!("Failed to create Cedar request: {}", e);
-                Status::internal(format!("Failed to create Cedar request: {}", e))
+                Self::invalid_field(Self::request_error_field(&e), e)
             })?;
 
-        // 8. Evaluate with Cedar Authorizer
+        // An empty store denies, but only once the request has been validated
+        if compiled.is_empty() {
+            info!(
+                "No policies found for policy store: {}",
+                req.policy_store_id
+            );
+            return Ok(IsAuthorizedResponse {
+                decision: Decision::Deny as i32,
+                determining_policies: vec![],
+                errors: vec!["No policies found in policy store".to_string()],
+                correlation_id: req.correlation_id.clone(),
+                explanation: None,
+            });
+        }
+
+        // 5. Evaluate with Cedar Authorizer
         let authorizer = Authorizer::new();
-        let response = authorizer.is_authorized(&cedar_request, &policy_set, &entities);
+        let response = authorizer.is_authorized(&cedar_request, compiled.policy_set(), entities);
 
-        // 9. Convert decision
+        // 6. Convert decision
         let decision = match response.decision() {
             cedar_policy::Decision::Allow => Decision::Allow,
             cedar_policy::Decision::Deny => Decision::Deny,
         };
 
-        // 10. Extract determining policies
+        // 7. Extract determining policies
         let determining_policies: Vec<String> = response
             .diagnostics()
             .reason()
             .map(|policy_id| policy_id.to_string())
             .collect();
 
-        // 11. Extract errors
+        // 8. Extract errors
         let errors: Vec<String> = response
             .diagnostics()
             .errors()
@@ -225,167 +417,2343 @@ where
             decision, determining_policies
         );
 
-        // 12. Return response
-        Ok(Response::new(IsAuthorizedResponse {
+        // 9. Explain the decision when asked to
+        let explanation = req.explain.then(|| {
+            Self::explain_decision(compiled.policy_set(), &cedar_request, entities, &response)
+        });
+
+        Ok(IsAuthorizedResponse {
             decision: decision as i32,
             determining_policies,
             errors,
-        }))
+            correlation_id: req.correlation_id.clone(),
+            explanation,
+        })
     }
 
-    async fn batch_is_authorized(
-        &self,
-        request: Request<BatchIsAuthorizedRequest>,
-    ) -> Result<Response<BatchIsAuthorizedResponse>, Status> {
-        let req = request.into_inner();
-        info!(
-            "Batch authorization request with {} requests",
-            req.requests.len()
-        );
+    /// Explains a decision policy by policy
+    ///
+    /// A deny is explicit when a forbid policy determined it and implicit
+    /// when no permit policy was satisfied.
+    fn explain_decision(
+        policy_set: &PolicySet,
+        request: &CedarRequest,
+        entities: &Entities,
+        response: &cedar_policy::Response,
+    ) -> DecisionExplanation {
+        let determining: HashSet<&PolicyId> = response.diagnostics().reason().collect();
+
+        let deny_reason = match response.decision() {
+            cedar_policy::Decision::Allow => DenyReason::Unspecified,
+            cedar_policy::Decision::Deny if determining.is_empty() => DenyReason::Implicit,
+            cedar_policy::Decision::Deny => DenyReason::Explicit,
+        };
+
+        let policy_errors: Vec<PolicyError> = response
+            .diagnostics()
+            .errors()
+            .map(|err| match err {
+                cedar_policy::AuthorizationError::PolicyEvaluationError(e) => PolicyError {
+                    policy_id: e.policy_id().to_string(),
+                    message: err.to_string(),
+                },
+            })
+            .collect();
+        let errored: HashSet<&str> = policy_errors
+            .iter()
+            .map(|err| err.policy_id.as_str())
+            .collect();
 
-        let mut responses = Vec::new();
+        let mut policies: Vec<PolicyEvaluation> = policy_set
+            .policies()
+            .map(|policy| PolicyEvaluation {
+                policy_id: policy.id().to_string(),
+                effect: match policy.effect() {
+                    Effect::Permit => "permit".to_string(),
+                    Effect::Forbid => "forbid".to_string(),
+                },
+                scope_matched: Self::scope_matches(policy, request, entities),
+                determining: determining.contains(policy.id()),
+                errored: errored.contains(policy.id().to_string().as_str()),
+            })
+            .collect();
+        policies.sort_by(|a, b| a.policy_id.cmp(&b.policy_id));
+
+        DecisionExplanation {
+            deny_reason: deny_reason as i32,
+            policy_errors,
+            policies,
+        }
+    }
 
-        for auth_request in req.requests {
-            // Wrap in a Request for the is_authorized method
-            let result = self.is_authorized(Request::new(auth_request)).await;
+    /// Whether the principal, action and resource constraints of a policy hold
+    fn scope_matches(policy: &Policy, request: &CedarRequest, entities: &Entities) -> bool {
+        let (Some(principal), Some(action), Some(resource)) =
+            (request.principal(), request.action(), request.resource())
+        else {
+            return false;
+        };
 
-            match result {
-                Ok(response) => responses.push(response.into_inner()),
-                Err(status) => {
-                    // Convert error to a deny response
-                    responses.push(IsAuthorizedResponse {
-                        decision: Decision::Deny as i32,
-                        determining_policies: vec![],
-                        errors: vec![status.message().to_string()],
-                    });
-                }
+        let principal_matches = match policy.principal_constraint() {
+            PrincipalConstraint::Any => true,
+            PrincipalConstraint::Eq(uid) => principal == &uid,
+            PrincipalConstraint::In(uid) => Self::is_in(entities, principal, &uid),
+            PrincipalConstraint::Is(type_name) => principal.type_name() == &type_name,
+            PrincipalConstraint::IsIn(type_name, uid) => {
+                principal.type_name() == &type_name && Self::is_in(entities, principal, &uid)
+            }
+        };
+
+        let action_matches = match policy.action_constraint() {
+            ActionConstraint::Any => true,
+            ActionConstraint::Eq(uid) => action == &uid,
+            ActionConstraint::In(uids) => uids.iter().any(|uid| Self::is_in(entities, action, uid)),
+        };
+
+        let resource_matches = match policy.resource_constraint() {
+            ResourceConstraint::Any => true,
+            ResourceConstraint::Eq(uid) => resource == &uid,
+            ResourceConstraint::In(uid) => Self::is_in(entities, resource, &uid),
+            ResourceConstraint::Is(type_name) => resource.type_name() == &type_name,
+            ResourceConstraint::IsIn(type_name, uid) => {
+                resource.type_name() == &type_name && Self::is_in(entities, resource, &uid)
+            }
+        };
+
+        principal_matches && action_matches && resource_matches
+    }
+
+    /// Cedar `in`: the entity is the ancestor or one of its descendants
+    fn is_in(entities: &Entities, uid: &EntityUid, ancestor: &EntityUid) -> bool {
+        uid == ancestor
+            || entities
+                .ancestors(uid)
+                .is_some_and(|mut ancestors| ancestors.any(|a| a == ancestor))
+    }
+
+    /// Caller details kept with each decision in the decision log
+    fn caller_metadata<T>(request: &Request<T>) -> BTreeMap<String, String> {
+        let mut caller = BTreeMap::new();
+        if let Some(addr) = request.remote_addr() {
+            caller.insert("remote_addr".to_string(), addr.to_string());
+        }
+        for key in ["user-agent", "x-forwarded-for", "x-request-id"] {
+            if let Some(value) = request.metadata().get(key).and_then(|v| v.to_str().ok()) {
+                caller.insert(key.to_string(), value.to_string());
             }
         }
+        caller
+    }
+
+    /// Queues a decision for the decision log, if one is configured
+    fn record_decision(
+        decision_log: Option<&DecisionLogWriter>,
+        policy_store_id: &PolicyStoreId,
+        req: &IsAuthorizedRequest,
+        response: &IsAuthorizedResponse,
+        started: Instant,
+        caller: &BTreeMap<String, String>,
+    ) {
+        let Some(decision_log) = decision_log else {
+            return;
+        };
+        let uid = |identifier: &Option<EntityIdentifier>| {
+            identifier
+                .as_ref()
+                .map(|id| format!("{}::\"{}\"", id.entity_type, id.entity_id))
+                .unwrap_or_default()
+        };
 
-        Ok(Response::new(BatchIsAuthorizedResponse { responses }))
+        decision_log.record(DecisionLog {
+            id: None,
+            policy_store_id: policy_store_id.clone(),
+            principal: uid(&req.principal),
+            action: uid(&req.action),
+            resource: uid(&req.resource),
+            decision: response.decision().as_str_name().to_string(),
+            determining_policies: response.determining_policies.clone(),
+            errors: response.errors.clone(),
+            latency_micros: u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX),
+            caller: caller.clone(),
+            correlation_id: req.correlation_id.clone(),
+            timestamp: Utc::now(),
+        });
     }
 
-    async fn is_authorized_with_token(
+    /// Evaluates a single request and records the decision
+    async fn authorize(
         &self,
-        request: Request<IsAuthorizedWithTokenRequest>,
-    ) -> Result<Response<IsAuthorizedResponse>, Status> {
-        let req = request.into_inner();
+        req: IsAuthorizedRequest,
+        caller: BTreeMap<String, String>,
+    ) -> Result<IsAuthorizedResponse, Status> {
+        let started = Instant::now();
         info!(
-            "Authorization with token request for policy store: {} and identity source: {}",
-            req.policy_store_id, req.identity_source_id
+            "Authorization request for policy store: {}",
+            req.policy_store_id
         );
 
-        // 1. Load Identity Source configuration
+        // 1. Parse policy store ID
         let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
             .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
 
-        let identity_source = self
-            .repository
-            .get_identity_source(&policy_store_id, &req.identity_source_id)
-            .await
-            .map_err(|e| {
-                error!("Failed to load identity source: {}", e);
-                Status::not_found(format!("Identity source not found: {}", e))
-            })?;
+        // 2. Load the compiled policy set from the cache
+        let compiled = Self::load_compiled(&self.cache, &policy_store_id).await?;
 
-        // 2. Parse Identity Source configuration
-        let config_json: serde_json::Value =
-            serde_json::from_str(&identity_source.configuration_json)
-                .map_err(|e| Status::internal(format!("Invalid identity source config: {}", e)))?;
+        // 3. Evaluate against it; a request that cannot be evaluated is
+        //    recorded as the deny it amounts to
+        let result = Self::evaluate(&compiled, &req, None);
+        let response = match &result {
+            Ok(response) => response.clone(),
+            Err(status) => Self::error_response(status, req.correlation_id.clone()),
+        };
+        Self::record_decision(
+            self.decision_log.as_ref(),
+            &policy_store_id,
+            &req,
+            &response,
+            started,
+            &caller,
+        );
+
+        result
+    }
 
-        let issuer = config_json["issuer"]
-            .as_str()
-            .ok_or_else(|| Status::internal("Identity source missing 'issuer'"))?;
+    /// Records a request whose token was rejected as a deny without principal
+    ///
+    /// Only rejections of the token itself are recorded; other failures such
+    /// as an unknown policy store or identity source are not decisions.
+    fn record_rejected_token(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        req: &IsAuthorizedRequest,
+        status: &Status,
+        started: Instant,
+        caller: &BTreeMap<String, String>,
+    ) {
+        if status.code() != tonic::Code::Unauthenticated {
+            return;
+        }
+        let response = Self::error_response(status, req.correlation_id.clone());
+        Self::record_decision(
+            self.decision_log.as_ref(),
+            policy_store_id,
+            req,
+            &response,
+            started,
+            caller,
+        );
+    }
 
-        let client_ids = config_json["client_ids"]
-            .as_array()
-            .ok_or_else(|| Status::internal("Identity source missing 'client_ids'"))?
-            .iter()
-            .filter_map(|v| v.as_str().map(String::from))
-            .collect::<Vec<String>>();
+    /// Fills in what a batch item leaves unset from the batch-level values
+    ///
+    /// Batch entities are appended to an item's own slice unless the item
+    /// already carries an entity with the same UID.
+    fn apply_batch_defaults(item: &mut IsAuthorizedRequest, batch: &BatchIsAuthorizedRequest) {
+        if item.principal.is_none() {
+            item.principal = batch.principal.clone();
+        }
+        if item.resource.is_none() {
+            item.resource = batch.resource.clone();
+        }
+        if item.context.is_none() {
+            item.context = batch.context.clone();
+        }
 
-        let jwks_uri = config_json["jwks_uri"]
-            .as_str()
-            .ok_or_else(|| Status::internal("Identity source missing 'jwks_uri'"))?;
+        if !item.entities.is_empty() {
+            let own: HashSet<EntityKey> = item
+                .entities
+                .iter()
+                .filter_map(|entity| entity.identifier.as_ref())
+                .map(Self::entity_key)
+                .collect();
+            item.entities.extend(
+                batch
+                    .entities
+                    .iter()
+                    .filter(|entity| {
+                        entity
+                            .identifier
+                            .as_ref()
+                            .is_none_or(|id| !own.contains(&Self::entity_key(id)))
+                    })
+                    .cloned(),
+            );
+        }
+    }
 
-        info!(
-            "Validating token with issuer: {}, jwks_uri: {}",
-            issuer, jwks_uri
-        );
+    /// Deny response carrying the error of a failed batch or stream item
+    fn error_response(status: &Status, correlation_id: Option<String>) -> IsAuthorizedResponse {
+        IsAuthorizedResponse {
+            decision: Decision::Deny as i32,
+            determining_policies: vec![],
+            errors: vec![status.message().to_string()],
+            correlation_id,
+            explanation: None,
+        }
+    }
 
-        // 3. Validate JWT token (signature, issuer, audience, expiration)
-        let validated_claims = self
-            .jwt_validator
-            .validate_token(&req.access_token, issuer, &client_ids, jwks_uri)
-            .await
-            .map_err(|e| {
-                error!("JWT validation failed: {}", e);
-                Status::unauthenticated(format!("Invalid token: {}", e))
+    /// Builds the request context, checked against `action` when a schema is given
+    fn build_context(
+        context_json: Option<&str>,
+        schema: Option<&Schema>,
+        action: &EntityUid,
+    ) -> Result<Context, Status> {
+        if let Some(json_str) = context_json {
+            let value: serde_json::Value = serde_json::from_str(json_str).map_err(|e| {
+                error!("Failed to parse context JSON: {}", e);
+                Self::invalid_field("context", format!("malformed JSON: {}", e))
             })?;
 
+            Context::from_json_value(value, schema.map(|schema| (schema, action))).map_err(|e| {
+                error!("Failed to build context: {}", e);
+                Self::invalid_field("context", e)
+            })
+        } else {
+            Ok(Context::empty())
+        }
+    }
+
+    /// Evaluates the items of a batch against one policy store, concurrently
+    async fn authorize_batch(
+        &self,
+        mut req: BatchIsAuthorizedRequest,
+        caller: Arc<BTreeMap<String, String>>,
+    ) -> Result<BatchIsAuthorizedResponse, Status> {
         info!(
-            "Token validated successfully for subject: {}",
-            validated_claims.sub
+            "Batch authorization request with {} requests",
+            req.requests.len()
         );
 
-        // 4. Extract principal ID from claims
-        let principal_id = validated_claims.sub.clone();
-        let principal = EntityIdentifier {
-            entity_type: "User".to_string(),
-            entity_id: principal_id.clone(),
+        let requests = std::mem::take(&mut req.requests);
+        if requests.is_empty() {
+            return Ok(BatchIsAuthorizedResponse { responses: vec![] });
+        }
+
+        // 1. Resolve the policy store once for the whole batch
+        if req.policy_store_id.is_empty() {
+            req.policy_store_id = requests[0].policy_store_id.clone();
+        }
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+        let compiled = Self::load_compiled(&self.cache, &policy_store_id).await?;
+
+        // 2. Entities for items that bring none of their own; without batch
+        //    entities the store's entities are used as they are
+        let schema = Self::validation_schema(&compiled)?;
+        let shared_entities = if req.entities.is_empty() {
+            None
+        } else {
+            let built = Self::build_entities(&req.entities, compiled.entities(), schema)?;
+            Some(Arc::new(built.into_owned()))
         };
 
-        info!("Mapped principal: User::{}", principal_id);
+        // 3. Evaluate the items concurrently, at most `max_concurrency` at a time
+        let max_concurrency = req.max_concurrency.map_or(self.batch_max_concurrency, |n| {
+            (n as usize).clamp(1, self.batch_max_concurrency)
+        });
+        let semaphore = Arc::new(Semaphore::new(max_concurrency));
+        let mut tasks = JoinSet::new();
+        let count = requests.len();
 
-        // 5. Extract groups from claims and create entities
-        let mut entities = Vec::new();
+        for (index, mut item) in requests.into_iter().enumerate() {
+            Self::apply_batch_defaults(&mut item, &req);
 
-        // Create principal entity
-        let mut principal_attrs = HashMap::new();
+            let permit =
+                semaphore.clone().acquire_owned().await.map_err(|e| {
+                    Status::internal(format!("Failed to schedule batch item: {}", e))
+                })?;
+            let compiled = compiled.clone();
+            let shared_entities = shared_entities.clone();
+            let batch_store_id = req.policy_store_id.clone();
+            let policy_store_id = policy_store_id.clone();
+            let decision_log = self.decision_log.clone();
+            let caller = caller.clone();
 
-        // Add email if present
-        if let Some(email) = validated_claims.additional_claims.get("email") {
-            if let Some(email_str) = email.as_str() {
-                principal_attrs.insert("email".to_string(), format!("\"{}\"", email_str));
-            }
+            tasks.spawn_blocking(move || {
+                let _permit = permit;
+                let started = Instant::now();
+                let result =
+                    if !item.policy_store_id.is_empty() && item.policy_store_id != batch_store_id {
+                        Err(Status::invalid_argument(format!(
+                            "Policy store {} does not match batch policy store {}",
+                            item.policy_store_id, batch_store_id
+                        )))
+                    } else {
+                        let entities = shared_entities
+                            .as_deref()
+                            .filter(|_| item.entities.is_empty());
+                        Self::evaluate(&compiled, &item, entities)
+                    };
+                let response = result.unwrap_or_else(|status| {
+                    Self::error_response(&status, item.correlation_id.clone())
+                });
+                Self::record_decision(
+                    decision_log.as_ref(),
+                    &policy_store_id,
+                    &item,
+                    &response,
+                    started,
+                    &caller,
+                );
+                (index, response)
+            });
         }
 
-        // Extract groups as parent entities
-        let mut parents = Vec::new();
-        if let Some(groups_value) = validated_claims.additional_claims.get("groups") {
-            if let Some(groups_array) = groups_value.as_array() {
-                for group in groups_array {
-                    if let Some(group_str) = group.as_str() {
-                        parents.push(EntityIdentifier {
-                            entity_type: "Role".to_string(),
-                            entity_id: group_str.to_string(),
-                        });
-                    }
-                }
-            }
+        // 4. Collect the responses back into request order
+        let mut responses: Vec<Option<IsAuthorizedResponse>> = vec![None; count];
+        while let Some(joined) = tasks.join_next().await {
+            let (index, response) = joined.map_err(|e| {
+                error!("Batch item evaluation failed: {}", e);
+                Status::internal(format!("Batch item evaluation failed: {}", e))
+            })?;
+            responses[index] = Some(response);
         }
 
-        // Create principal entity with attributes and parents
-        entities.push(Entity {
-            identifier: Some(principal.clone()),
-            attributes: principal_attrs,
-            parents,
+        Ok(BatchIsAuthorizedResponse {
+            responses: responses.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Evaluates a stream of requests, sending each decision as it completes
+    ///
+    /// At most `batch_max_concurrency` requests are evaluated at once. While
+    /// the client does not read decisions, no further requests are read.
+    fn evaluate_stream<S>(
+        &self,
+        inbound: S,
+        caller: Arc<BTreeMap<String, String>>,
+    ) -> ReceiverStream<Result<IsAuthorizedResponse, Status>>
+    where
+        S: Stream<Item = Result<IsAuthorizedRequest, Status>> + Send + 'static,
+    {
+        let mut inbound = Box::pin(inbound);
+        let (tx, rx) = mpsc::channel(self.batch_max_concurrency);
+
+        let semaphore = Arc::new(Semaphore::new(self.batch_max_concurrency));
+        let cache = self.cache.clone();
+        let decision_log = self.decision_log.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let req = match inbound.next().await {
+                    Some(Ok(req)) => req,
+                    None => break,
+                    Some(Err(status)) => {
+                        error!("Authorization stream failed: {}", status);
+                        break;
+                    }
+                };
+
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+
+                // Same policy cache and evaluation path as unary is_authorized
+                let started = Instant::now();
+                let store = match PolicyStoreId::new(req.policy_store_id.clone()) {
+                    Ok(policy_store_id) => Self::load_compiled(&cache, &policy_store_id)
+                        .await
+                        .map(|compiled| (policy_store_id, compiled)),
+                    Err(e) => Err(Status::invalid_argument(format!(
+                        "Invalid policy store ID: {}",
+                        e
+                    ))),
+                };
+
+                let tx = tx.clone();
+                let decision_log = decision_log.clone();
+                let caller = caller.clone();
+                tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    let response = match store {
+                        Ok((policy_store_id, compiled)) => {
+                            let response =
+                                Self::evaluate(&compiled, &req, None).unwrap_or_else(|status| {
+                                    Self::error_response(&status, req.correlation_id.clone())
+                                });
+                            Self::record_decision(
+                                decision_log.as_ref(),
+                                &policy_store_id,
+                                &req,
+                                &response,
+                                started,
+                                &caller,
+                            );
+                            response
+                        }
+                        Err(status) => Self::error_response(&status, req.correlation_id.clone()),
+                    };
+                    // The client may have gone away; nothing left to do then
+                    let _ = tx.blocking_send(Ok(response));
+                });
+            }
+            info!("Authorization stream closed");
         });
 
-        // 6. Merge with any additional entities from request
-        entities.extend(req.entities);
+        ReceiverStream::new(rx)
+    }
 
-        // 7. Create authorization request
-        let auth_request = IsAuthorizedRequest {
-            policy_store_id: req.policy_store_id,
-            principal: Some(principal),
-            action: req.action,
-            resource: req.resource,
-            context: req.context,
-            entities,
+    /// Issuer, signing keys, algorithms and accepted client IDs of an
+    /// identity source
+    fn token_issuer(
+        configuration_type: &IdentitySourceType,
+        config_json: &serde_json::Value,
+    ) -> Result<TokenIssuer, Status> {
+        let allowed_algorithms: Vec<String> = config_json["allowed_algorithms"]
+            .as_array()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let invalid_algorithms =
+            |e: AuthorizationError| Status::internal(format!("Invalid allowed_algorithms: {}", e));
+        let policy = match config_json.get("token_validation") {
+            Some(policy) => serde_json::from_value::<TokenValidationPolicy>(policy.clone())
+                .map_err(|e| Status::internal(format!("Invalid token validation policy: {}", e)))?,
+            None => TokenValidationPolicy::default(),
         };
 
-        // 8. Evaluate with Cedar (real authorization)
-        self.is_authorized(Request::new(auth_request)).await
+        let issuer = match configuration_type {
+            IdentitySourceType::Oidc => {
+                let issuer = config_json["issuer"]
+                    .as_str()
+                    .ok_or_else(|| Status::internal("Identity source missing 'issuer'"))?;
+
+                let client_ids = config_json["client_ids"]
+                    .as_array()
+                    .ok_or_else(|| Status::internal("Identity source missing 'client_ids'"))?
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect::<Vec<String>>();
+
+                let jwks_uri = config_json["jwks_uri"]
+                    .as_str()
+                    .filter(|uri| !uri.is_empty());
+
+                TokenIssuer {
+                    issuer: issuer.to_string(),
+                    jwks_uri: jwks_uri.map(String::from),
+                    client_ids,
+                    algorithms: AlgorithmAllowlist::asymmetric(&allowed_algorithms)
+                        .map_err(invalid_algorithms)?,
+                    policy,
+                    secret: None,
+                    cognito: None,
+                    introspection: None,
+                }
+            }
+            IdentitySourceType::Cognito => {
+                // Issuer and JWKS URL are derived from the user pool ARN
+                let user_pool_arn = config_json["user_pool_arn"]
+                    .as_str()
+                    .ok_or_else(|| Status::internal("Identity source missing 'user_pool_arn'"))?;
+
+                let client_ids = config_json["client_ids"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<String>>();
+
+                let provider = client_ids.iter().fold(
+                    CognitoProvider::from_arn(user_pool_arn).map_err(|e| {
+                        Status::internal(format!("Invalid Cognito user pool ARN: {}", e))
+                    })?,
+                    |provider, client_id| provider.with_client_id(client_id.clone()),
+                );
+                let issuer = provider
+                    .issuer()
+                    .map_err(|e| Status::internal(e.to_string()))?;
+                let jwks_uri = provider
+                    .jwks_uri()
+                    .map_err(|e| Status::internal(e.to_string()))?;
+
+                let algorithms = if allowed_algorithms.is_empty() {
+                    vec![CognitoProvider::SIGNING_ALGORITHM.to_string()]
+                } else {
+                    allowed_algorithms
+                };
+
+                TokenIssuer {
+                    issuer,
+                    jwks_uri: Some(jwks_uri),
+                    client_ids,
+                    algorithms: AlgorithmAllowlist::asymmetric(&algorithms)
+                        .map_err(invalid_algorithms)?,
+                    // ID tokens carry the client in aud, access tokens in client_id
+                    policy: TokenValidationPolicy {
+                        audience_check: policy
+                            .audience_check
+                            .or(Some(AudienceCheck::AudOrClientId)),
+                        ..policy
+                    },
+                    secret: None,
+                    cognito: Some(provider),
+                    introspection: None,
+                }
+            }
+            IdentitySourceType::SharedSecret => {
+                let issuer = config_json["issuer"]
+                    .as_str()
+                    .ok_or_else(|| Status::internal("Identity source missing 'issuer'"))?;
+                let secret = config_json["secret"]
+                    .as_str()
+                    .filter(|secret| !secret.is_empty())
+                    .ok_or_else(|| Status::internal("Identity source missing 'secret'"))?;
+
+                let client_ids = config_json["client_ids"]
+                    .as_array()
+                    .map(|ids| {
+                        ids.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                TokenIssuer {
+                    issuer: issuer.to_string(),
+                    jwks_uri: None,
+                    client_ids,
+                    algorithms: AlgorithmAllowlist::symmetric(&allowed_algorithms)
+                        .map_err(invalid_algorithms)?,
+                    policy,
+                    secret: Some(secret.to_string()),
+                    cognito: None,
+                    introspection: None,
+                }
+            }
+            IdentitySourceType::Introspection => {
+                let config_str = |field: &str| {
+                    config_json[field]
+                        .as_str()
+                        .filter(|v| !v.is_empty())
+                        .ok_or_else(|| {
+                            Status::internal(format!("Identity source missing '{}'", field))
+                        })
+                };
+                let issuer = config_str("issuer")?;
+
+                let client_ids = config_json["client_ids"]
+                    .as_array()
+                    .map(|ids| {
+                        ids.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let cache_ttl = config_json["cache_ttl_seconds"]
+                    .as_u64()
+                    .unwrap_or(DEFAULT_INTROSPECTION_CACHE_TTL_SECONDS);
+
+                TokenIssuer {
+                    issuer: issuer.to_string(),
+                    jwks_uri: None,
+                    client_ids,
+                    algorithms: AlgorithmAllowlist::asymmetric(&allowed_algorithms)
+                        .map_err(invalid_algorithms)?,
+                    // Introspection responses name the client in client_id
+                    policy: TokenValidationPolicy {
+                        audience_check: policy
+                            .audience_check
+                            .or(Some(AudienceCheck::AudOrClientId)),
+                        ..policy
+                    },
+                    secret: None,
+                    cognito: None,
+                    introspection: Some(IntrospectionEndpoint {
+                        url: config_str("introspection_endpoint")?.to_string(),
+                        client_id: config_str("client_id")?.to_string(),
+                        client_secret: config_str("client_secret")?.to_string(),
+                        issuer: issuer.to_string(),
+                        cache_ttl: Duration::from_secs(cache_ttl),
+                    }),
+                }
+            }
+        };
+        Ok(issuer)
+    }
+
+    /// Picks the identity source of a token when the caller names none
+    ///
+    /// The identity source whose issuer equals the token's `iss` is used,
+    /// else the policy store's default identity source. Opaque tokens have
+    /// no `iss`; they go to the store's only introspection identity source,
+    /// else to the default one.
+    async fn select_identity_source(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        access_token: &str,
+    ) -> Result<IdentitySource, Status> {
+        let token_issuer = extract_issuer_from_token(access_token).ok();
+
+        let identity_sources = self
+            .repository
+            .list_identity_sources(policy_store_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to list identity sources: {}", e);
+                Status::internal(format!("Failed to list identity sources: {}", e))
+            })?;
+
+        let matches = |source: &IdentitySource| match &token_issuer {
+            Some(token_issuer) => serde_json::from_str(&source.configuration_json)
+                .ok()
+                .and_then(|config| Self::token_issuer(&source.configuration_type, &config).ok())
+                .is_some_and(|issuer| {
+                    issuer.issuer.trim_end_matches('/') == token_issuer.trim_end_matches('/')
+                }),
+            None => source.configuration_type == IdentitySourceType::Introspection,
+        };
+        let mut matching: Vec<IdentitySource> =
+            identity_sources.into_iter().filter(matches).collect();
+        let token_issuer = token_issuer.as_deref().unwrap_or("(opaque token)");
+
+        match matching.len() {
+            1 => Ok(matching.remove(0)),
+            0 => {
+                let store = self
+                    .repository
+                    .get_policy_store(policy_store_id)
+                    .await
+                    .map_err(|e| Status::not_found(format!("Policy store not found: {}", e)))?;
+                let default_id = store.default_identity_source_id.ok_or_else(|| {
+                    Status::unauthenticated(format!(
+                        "No identity source of policy store {} matches token issuer {}, and the store has no default identity source",
+                        policy_store_id, token_issuer
+                    ))
+                })?;
+                info!(
+                    "No identity source matches issuer {}, using default {}",
+                    token_issuer, default_id
+                );
+                self.repository
+                    .get_identity_source(policy_store_id, &default_id)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to load default identity source: {}", e);
+                        Status::not_found(format!("Default identity source not found: {}", e))
+                    })
+            }
+            _ => {
+                let ids: Vec<&str> = matching.iter().map(|source| source.id.as_str()).collect();
+                Err(Status::failed_precondition(format!(
+                    "Token issuer {} matches several identity sources ({}); pass identity_source_id",
+                    token_issuer,
+                    ids.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// Proof of possession sent with a sender-constrained token
+    fn possession_proof(proof: Option<ProofOfPossession>) -> PossessionProof {
+        let proof = proof.unwrap_or_default();
+        PossessionProof {
+            dpop_proof: proof.dpop_proof,
+            http_method: proof.http_method,
+            http_url: proof.http_url,
+            client_certificate_thumbprint: proof.client_certificate_thumbprint,
+        }
+    }
+
+    /// Validates a token against an identity source and maps its claims to
+    /// the principal entity, its attributes and parents
+    ///
+    /// An empty `identity_source_id` selects it by the token issuer. `proof`
+    /// is checked against the token's `cnf` binding when the identity source
+    /// enforces sender constraints.
+    async fn resolve_token_principal(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        identity_source_id: &str,
+        access_token: &str,
+        proof: &PossessionProof,
+    ) -> Result<TokenPrincipal, Status> {
+        // 1. Load Identity Source configuration
+        let identity_source = if identity_source_id.is_empty() {
+            self.select_identity_source(policy_store_id, access_token)
+                .await?
+        } else {
+            self.repository
+                .get_identity_source(policy_store_id, identity_source_id)
+                .await
+                .map_err(|e| {
+                    error!("Failed to load identity source: {}", e);
+                    Status::not_found(format!("Identity source not found: {}", e))
+                })?
+        };
+
+        // 2. Parse Identity Source configuration
+        let config_json: serde_json::Value =
+            serde_json::from_str(&identity_source.configuration_json)
+                .map_err(|e| Status::internal(format!("Invalid identity source config: {}", e)))?;
+
+        if identity_source.configuration_type == IdentitySourceType::SharedSecret
+            && !self.shared_secret_identity_sources
+        {
+            return Err(Status::failed_precondition(format!(
+                "Identity source {} uses a shared secret, and shared-secret identity sources are disabled",
+                identity_source.id
+            )));
+        }
+
+        let TokenIssuer {
+            issuer,
+            jwks_uri,
+            client_ids,
+            algorithms,
+            policy,
+            secret,
+            cognito,
+            introspection,
+        } = Self::token_issuer(&identity_source.configuration_type, &config_json)?;
+
+        // 3. Validate JWT token (algorithm, signature, issuer, expiration, and
+        //    the identity source's audience, token age and required claims),
+        //    or introspect an opaque token
+        let validated = match (&introspection, &secret) {
+            (Some(endpoint), _) => {
+                info!("Introspecting token at: {}", endpoint.url);
+                self.token_introspector
+                    .introspect(access_token, endpoint, &client_ids, &policy)
+                    .await
+            }
+            (None, Some(secret)) => {
+                info!("Validating shared-secret token with issuer: {}", issuer);
+                self.jwt_validator.validate_token_with_secret(
+                    access_token,
+                    &issuer,
+                    &client_ids,
+                    secret.as_bytes(),
+                    &algorithms,
+                    &policy,
+                )
+            }
+            (None, None) => {
+                info!(
+                    "Validating token with issuer: {}, jwks_uri: {}",
+                    issuer,
+                    jwks_uri.as_deref().unwrap_or("(discovered)")
+                );
+                self.jwt_validator
+                    .validate_token(
+                        access_token,
+                        &issuer,
+                        &client_ids,
+                        jwks_uri.as_deref(),
+                        &algorithms,
+                        &policy,
+                    )
+                    .await
+            }
+        };
+        let validated_claims = validated.map_err(|e| {
+            error!("JWT validation failed: {}", e);
+            Status::unauthenticated(format!("Invalid token: {}", e))
+        })?;
+
+        // Cognito access tokens carry client_id instead of aud, and token_use
+        // tells ID and access tokens apart
+        if let Some(provider) = &cognito {
+            provider.validate_claims(&validated_claims).map_err(|e| {
+                error!("Cognito token rejected: {}", e);
+                Status::unauthenticated(format!("Invalid token: {}", e))
+            })?;
+        }
+
+        // Bound tokens must come with proof of possession of the DPoP key or
+        // client certificate named in their cnf claim
+        self.sender_constraint_verifier
+            .verify(
+                access_token,
+                &validated_claims,
+                proof,
+                policy.sender_constraint,
+                policy.clock_skew_seconds,
+            )
+            .await
+            .map_err(|e| {
+                error!("Token binding verification failed: {}", e);
+                Status::unauthenticated(format!("Invalid token: {}", e))
+            })?;
+
+        // Revoked tokens are rejected from the in-memory denylist of the
+        // compiled store, so the check never reaches the database
+        let compiled = Self::load_compiled(&self.cache, policy_store_id).await?;
+        for claim in [RevokedClaim::Jti, RevokedClaim::Sub, RevokedClaim::Sid] {
+            let value = match claim {
+                RevokedClaim::Sub => Some(validated_claims.sub.as_str()),
+                _ => validated_claims
+                    .additional_claims
+                    .get(claim.claim_name())
+                    .and_then(|v| v.as_str()),
+            };
+            if let Some(value) = value.filter(|v| compiled.revocation(claim, v).is_some()) {
+                error!("Token rejected: {} '{}' has been revoked", claim, value);
+                return Err(Status::unauthenticated(format!(
+                    "Invalid token: {} '{}' has been revoked",
+                    claim, value
+                )));
+            }
+        }
+
+        info!(
+            "Token validated successfully for subject: {}",
+            validated_claims.sub
+        );
+
+        // 4. Map claims to the principal entity using the identity source's mapping
+        let mut claims_mapping = match identity_source.claims_mapping_json.as_deref() {
+            Some(json) => ClaimsMappingConfig::from_json(json)
+                .map_err(|e| Status::internal(format!("Invalid claims mapping: {}", e)))?,
+            None => ClaimsMappingConfig::default(),
+        };
+
+        // Group claim and entity types declared on the identity source win
+        let config_str = |field: &str| config_json[field].as_str().filter(|v| !v.is_empty());
+        if let Some(group_claim) = config_str("group_claim")
+            .or_else(|| config_str("group_configuration_group_claim"))
+            .or(cognito.as_ref().map(|_| CognitoProvider::GROUPS_CLAIM))
+        {
+            claims_mapping.group_claim = Some(group_claim.to_string());
+        }
+        if let Some(entity_type) = config_str("principal_entity_type") {
+            claims_mapping.principal_entity_type = entity_type.to_string();
+        }
+        if let Some(entity_type) = config_str("group_entity_type") {
+            claims_mapping.group_entity_type = entity_type.to_string();
+        }
+
+        // Mapped attributes without a declared type take the type the schema
+        // gives the principal entity type
+        if let Some(schema_types) = compiled.attribute_types(&claims_mapping.principal_entity_type)
+        {
+            for (attribute, attr_type) in schema_types {
+                if claims_mapping.attribute_mappings.contains_key(attribute) {
+                    claims_mapping
+                        .attribute_types
+                        .entry(attribute.clone())
+                        .or_insert_with(|| attr_type.clone());
+                }
+            }
+        }
+
+        let (principal, mapped_entities) = ClaimsMapper::map_to_principal(
+            &validated_claims,
+            &claims_mapping,
+            &claims_mapping.principal_entity_type,
+        )
+        .map_err(|e| {
+            error!("Failed to map token claims: {}", e);
+            Status::unauthenticated(format!("Failed to map token claims: {}", e))
+        })?;
+
+        info!(
+            "Mapped principal: {}::{}",
+            principal.entity_type, principal.entity_id
+        );
+
+        // 5. Convert the mapped entities (principal with attributes and parents)
+        let entity_identifier = |id: EntityId| EntityIdentifier {
+            entity_type: id.entity_type,
+            entity_id: id.entity_id,
+        };
+        let principal = entity_identifier(principal);
+        let entities = mapped_entities
+            .into_iter()
+            .map(|entity| Entity {
+                identifier: entity.identifier.map(entity_identifier),
+                attributes: entity.attributes,
+                parents: entity.parents.into_iter().map(entity_identifier).collect(),
+            })
+            .collect();
+
+        Ok(TokenPrincipal {
+            principal,
+            entities,
+            claims: validated_claims,
+            claims_mapping,
+        })
+    }
+
+    /// Rejects caller-supplied entities that would replace the token principal
+    ///
+    /// The principal's attributes and parents come from the token alone; an
+    /// entity with its UID would otherwise win over the mapped one.
+    fn ensure_not_token_principal(
+        entities: &[Entity],
+        principal: &EntityIdentifier,
+    ) -> Result<(), Status> {
+        if entities
+            .iter()
+            .any(|entity| entity.identifier.as_ref() == Some(principal))
+        {
+            return Err(Self::invalid_field(
+                "entities",
+                format!(
+                    "{}::\"{}\" is the token principal and cannot be supplied",
+                    principal.entity_type, principal.entity_id
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Merges the configured token claims into the request context
+    ///
+    /// The token claims take precedence over a caller-supplied value under
+    /// the same key.
+    fn token_context(
+        token: &TokenPrincipal,
+        context: Option<String>,
+    ) -> Result<Option<String>, Status> {
+        let Some(context_claims) = &token.claims_mapping.context_claims else {
+            return Ok(context);
+        };
+
+        let mut context = match context.as_deref() {
+            Some(json) if !json.is_empty() => match serde_json::from_str::<serde_json::Value>(json)
+            {
+                Ok(serde_json::Value::Object(context)) => context,
+                Ok(_) => return Err(Status::invalid_argument("Context must be a JSON object")),
+                Err(e) => {
+                    return Err(Status::invalid_argument(format!(
+                        "Invalid context JSON: {}",
+                        e
+                    )));
+                }
+            },
+            _ => serde_json::Map::new(),
+        };
+        context.insert(
+            context_claims.context_key.clone(),
+            ClaimsMapper::context_claims(&token.claims, context_claims),
+        );
+        Ok(Some(serde_json::Value::Object(context).to_string()))
+    }
+}
+
+#[async_trait]
+impl<R> AuthorizationData for AuthorizationDataService<R>
+where
+    R: PolicyRepository + Send + Sync + 'static,
+{
+    async fn is_authorized(
+        &self,
+        request: Request<IsAuthorizedRequest>,
+    ) -> Result<Response<IsAuthorizedResponse>, Status> {
+        let caller = Self::caller_metadata(&request);
+        self.authorize(request.into_inner(), caller)
+            .await
+            .map(Response::new)
+    }
+
+    async fn batch_is_authorized(
+        &self,
+        request: Request<BatchIsAuthorizedRequest>,
+    ) -> Result<Response<BatchIsAuthorizedResponse>, Status> {
+        let caller = Arc::new(Self::caller_metadata(&request));
+        self.authorize_batch(request.into_inner(), caller)
+            .await
+            .map(Response::new)
+    }
+
+    type AuthorizeStreamStream = ReceiverStream<Result<IsAuthorizedResponse, Status>>;
+
+    async fn authorize_stream(
+        &self,
+        request: Request<Streaming<IsAuthorizedRequest>>,
+    ) -> Result<Response<Self::AuthorizeStreamStream>, Status> {
+        info!("Authorization stream opened");
+
+        let caller = Arc::new(Self::caller_metadata(&request));
+        Ok(Response::new(
+            self.evaluate_stream(request.into_inner(), caller),
+        ))
+    }
+
+    async fn is_authorized_with_token(
+        &self,
+        request: Request<IsAuthorizedWithTokenRequest>,
+    ) -> Result<Response<IsAuthorizedResponse>, Status> {
+        let started = Instant::now();
+        let caller = Self::caller_metadata(&request);
+        let req = request.into_inner();
+        info!(
+            "Authorization with token request for policy store: {} and identity source: {}",
+            req.policy_store_id, req.identity_source_id
+        );
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        // 1. Validate the token and its binding, and map its claims to the
+        //    principal
+        let proof = Self::possession_proof(req.proof_of_possession);
+        let token = match self
+            .resolve_token_principal(
+                &policy_store_id,
+                &req.identity_source_id,
+                &req.access_token,
+                &proof,
+            )
+            .await
+        {
+            Ok(token) => token,
+            Err(status) => {
+                let rejected = IsAuthorizedRequest {
+                    policy_store_id: req.policy_store_id,
+                    principal: None,
+                    action: req.action,
+                    resource: req.resource,
+                    context: None,
+                    entities: vec![],
+                    correlation_id: None,
+                    explain: false,
+                };
+                self.record_rejected_token(&policy_store_id, &rejected, &status, started, &caller);
+                return Err(status);
+            }
+        };
+
+        // 2. Merge with any additional entities and context from request
+        Self::ensure_not_token_principal(&req.entities, &token.principal)?;
+        let mut entities = token.entities.clone();
+        entities.extend(req.entities);
+        let context = Self::token_context(&token, req.context)?;
+
+        // 3. Create authorization request
+        let auth_request = IsAuthorizedRequest {
+            policy_store_id: req.policy_store_id,
+            principal: Some(token.principal),
+            action: req.action,
+            resource: req.resource,
+            context,
+            entities,
+            correlation_id: None,
+            explain: req.explain,
+        };
+
+        // 4. Evaluate with Cedar (real authorization)
+        self.authorize(auth_request, caller)
+            .await
+            .map(Response::new)
+    }
+
+    async fn batch_is_authorized_with_token(
+        &self,
+        request: Request<BatchIsAuthorizedWithTokenRequest>,
+    ) -> Result<Response<BatchIsAuthorizedResponse>, Status> {
+        let started = Instant::now();
+        let caller = Arc::new(Self::caller_metadata(&request));
+        let req = request.into_inner();
+        info!(
+            "Batch authorization with token request with {} requests for policy store: {} and identity source: {}",
+            req.requests.len(),
+            req.policy_store_id,
+            req.identity_source_id
+        );
+
+        if req.requests.is_empty() {
+            return Ok(Response::new(BatchIsAuthorizedResponse {
+                responses: vec![],
+            }));
+        }
+
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        // 1. Validate the token and map its principal once for the whole batch
+        let proof = Self::possession_proof(req.proof_of_possession);
+        let token = match self
+            .resolve_token_principal(
+                &policy_store_id,
+                &req.identity_source_id,
+                &req.access_token,
+                &proof,
+            )
+            .await
+        {
+            Ok(token) => token,
+            Err(status) => {
+                for item in &req.requests {
+                    let rejected = IsAuthorizedRequest {
+                        policy_store_id: req.policy_store_id.clone(),
+                        principal: None,
+                        action: item.action.clone(),
+                        resource: item.resource.clone(),
+                        context: None,
+                        entities: vec![],
+                        correlation_id: item.correlation_id.clone(),
+                        explain: false,
+                    };
+                    self.record_rejected_token(
+                        &policy_store_id,
+                        &rejected,
+                        &status,
+                        started,
+                        &caller,
+                    );
+                }
+                return Err(status);
+            }
+        };
+
+        // 2. Build the items; one whose entities or context cannot be used
+        //    fails on its own
+        Self::ensure_not_token_principal(&req.entities, &token.principal)?;
+        let count = req.requests.len();
+        let mut responses: Vec<Option<IsAuthorizedResponse>> = vec![None; count];
+        let mut indices = Vec::with_capacity(count);
+        let mut items = Vec::with_capacity(count);
+        for (index, item) in req.requests.into_iter().enumerate() {
+            let context = Self::ensure_not_token_principal(&item.entities, &token.principal)
+                .and_then(|()| {
+                    Self::token_context(&token, item.context.or_else(|| req.context.clone()))
+                });
+            let mut evaluated = IsAuthorizedRequest {
+                policy_store_id: req.policy_store_id.clone(),
+                principal: Some(token.principal.clone()),
+                action: item.action,
+                resource: item.resource,
+                context: None,
+                entities: item.entities,
+                correlation_id: item.correlation_id,
+                explain: item.explain,
+            };
+            match context {
+                Ok(context) => {
+                    evaluated.context = context;
+                    indices.push(index);
+                    items.push(evaluated);
+                }
+                Err(status) => {
+                    let response = Self::error_response(&status, evaluated.correlation_id.clone());
+                    Self::record_decision(
+                        self.decision_log.as_ref(),
+                        &policy_store_id,
+                        &evaluated,
+                        &response,
+                        started,
+                        &caller,
+                    );
+                    responses[index] = Some(response);
+                }
+            }
+        }
+
+        // 3. Evaluate as a batch sharing the token entities
+        let mut entities = token.entities;
+        entities.extend(req.entities);
+        let batch = BatchIsAuthorizedRequest {
+            policy_store_id: req.policy_store_id,
+            requests: items,
+            entities,
+            principal: None,
+            resource: None,
+            context: None,
+            max_concurrency: req.max_concurrency,
+        };
+        let evaluated = self.authorize_batch(batch, caller).await?;
+
+        for (index, response) in indices.into_iter().zip(evaluated.responses) {
+            responses[index] = Some(response);
+        }
+
+        Ok(Response::new(BatchIsAuthorizedResponse {
+            responses: responses.into_iter().flatten().collect(),
+        }))
+    }
+
+    async fn is_authorized_partial(
+        &self,
+        request: Request<IsAuthorizedPartialRequest>,
+    ) -> Result<Response<IsAuthorizedPartialResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "Partial authorization request for policy store: {}",
+            req.policy_store_id
+        );
+
+        // 1. Parse policy store ID
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        // 2. Load the compiled policy set from the cache
+        let compiled = Self::load_compiled(&self.cache, &policy_store_id).await?;
+        let schema = Self::validation_schema(&compiled)?;
+
+        // 3. Build the request, leaving unset principal/resource unknown
+        let action = Self::build_entity_uid(
+            req.action
+                .as_ref()
+                .ok_or_else(|| Status::invalid_argument("Action is required"))?,
+            "action",
+        )?;
+
+        let mut builder = RequestBuilder::default().action(action.clone());
+
+        builder = match (&req.principal, &req.principal_type) {
+            (Some(principal), _) => {
+                builder.principal(Self::build_entity_uid(principal, "principal")?)
+            }
+            (None, Some(principal_type)) => builder.unknown_principal_with_type(
+                Self::build_entity_type(principal_type, "principal_type")?,
+            ),
+            (None, None) => builder,
+        };
+
+        builder = match (&req.resource, &req.resource_type) {
+            (Some(resource), _) => builder.resource(Self::build_entity_uid(resource, "resource")?),
+            (None, Some(resource_type)) => builder.unknown_resource_with_type(
+                Self::build_entity_type(resource_type, "resource_type")?,
+            ),
+            (None, None) => builder,
+        };
+
+        let context = Self::build_context(req.context.as_deref(), schema, &action)?;
+        builder = builder.context(context);
+
+        let entities = Self::build_entities(&req.entities, compiled.entities(), schema)?;
+
+        let cedar_request = match schema {
+            Some(schema) => builder.schema(schema).build().map_err(|e| {
+                error!("Failed to create Cedar request: {}", e);
+                Self::invalid_field(Self::request_error_field(&e), e)
+            })?,
+            None => builder.build(),
+        };
+
+        // 4. Partially evaluate with Cedar Authorizer
+        let authorizer = Authorizer::new();
+        let response =
+            authorizer.is_authorized_partial(&cedar_request, compiled.policy_set(), &entities);
+
+        // 5. Convert decision (unspecified while it depends on the unknowns)
+        let decision = match response.decision() {
+            Some(cedar_policy::Decision::Allow) => Decision::Allow,
+            Some(cedar_policy::Decision::Deny) => Decision::Deny,
+            None => Decision::Unspecified,
+        };
+
+        let determining_policies: Vec<String> = response
+            .definitely_satisfied()
+            .map(|policy| policy.id().to_string())
+            .collect();
+
+        let residuals = response
+            .nontrivial_residuals()
+            .map(|policy| Self::residual_policy(&policy))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let errors: Vec<String> = response
+            .definitely_errored()
+            .map(|policy_id| format!("Policy {} failed to evaluate", policy_id))
+            .collect();
+
+        info!(
+            "Partial authorization decision: {:?}, {} residual policies",
+            decision,
+            residuals.len()
+        );
+
+        Ok(Response::new(IsAuthorizedPartialResponse {
+            decision: decision as i32,
+            determining_policies,
+            residuals,
+            errors,
+        }))
+    }
+
+    async fn get_jwks_cache_metrics(
+        &self,
+        _request: Request<GetJwksCacheMetricsRequest>,
+    ) -> Result<Response<GetJwksCacheMetricsResponse>, Status> {
+        let jwks = self.jwt_validator.jwks_cache();
+        let metrics = jwks.metrics().await;
+
+        Ok(Response::new(GetJwksCacheMetricsResponse {
+            hits: metrics.hits,
+            misses: metrics.misses,
+            refreshes: metrics.refreshes,
+            errors: metrics.errors,
+            negative_hits: metrics.negative_hits,
+            rate_limited_refreshes: metrics.rate_limited,
+            hit_rate: metrics.hit_rate(),
+            cached_issuers: jwks.cached_issuers_count().await as u64,
+        }))
+    }
+
+    async fn list_allowed_actions(
+        &self,
+        request: Request<ListAllowedActionsRequest>,
+    ) -> Result<Response<ListAllowedActionsResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            "List allowed actions request for policy store: {}",
+            req.policy_store_id
+        );
+
+        // 1. Parse policy store ID
+        let policy_store_id = PolicyStoreId::new(req.policy_store_id.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid policy store ID: {}", e)))?;
+
+        // 2. Load the compiled policy set; the schema provides the actions
+        let compiled = Self::load_compiled(&self.cache, &policy_store_id).await?;
+        let action_schema = compiled.schema().ok_or_else(|| {
+            Status::failed_precondition("Listing allowed actions requires a policy store schema")
+        })?;
+        let schema = Self::validation_schema(&compiled)?;
+
+        let principal = Self::build_entity_uid(
+            req.principal
+                .as_ref()
+                .ok_or_else(|| Status::invalid_argument("Principal is required"))?,
+            "principal",
+        )?;
+
+        let resource = Self::build_entity_uid(
+            req.resource
+                .as_ref()
+                .ok_or_else(|| Status::invalid_argument("Resource is required"))?,
+            "resource",
+        )?;
+
+        // 3. Entities are built once and shared by every action
+        let entities = Self::build_entities(&req.entities, compiled.entities(), schema)?;
+
+        // 4. Evaluate each applicable action against the cached PolicySet
+        let authorizer = Authorizer::new();
+        let mut allowed_actions = Vec::new();
+        let mut errors = Vec::new();
+
+        for action in Self::applicable_actions(action_schema, &principal, &resource) {
+            // Under strict validation the context is checked per action and an
+            // action whose context type does not match is reported, not allowed
+            let cedar_request = Self::build_context(req.context.as_deref(), schema, action)
+                .and_then(|context| {
+                    CedarRequest::new(
+                        principal.clone(),
+                        action.clone(),
+                        resource.clone(),
+                        context,
+                        schema,
+                    )
+                    .map_err(|e| Self::invalid_field(Self::request_error_field(&e), e))
+                });
+
+            let cedar_request = match cedar_request {
+                Ok(cedar_request) => cedar_request,
+                Err(status) => {
+                    errors.push(format!("{}: {}", action, status.message()));
+                    continue;
+                }
+            };
+
+            let response =
+                authorizer.is_authorized(&cedar_request, compiled.policy_set(), &entities);
+
+            errors.extend(
+                response
+                    .diagnostics()
+                    .errors()
+                    .map(|err| format!("{}: {}", action, err)),
+            );
+
+            if response.decision() == cedar_policy::Decision::Allow {
+                allowed_actions.push(AllowedAction {
+                    action: Some(EntityIdentifier {
+                        entity_type: action.type_name().to_string(),
+                        entity_id: action.id().unescaped().to_string(),
+                    }),
+                    determining_policies: response
+                        .diagnostics()
+                        .reason()
+                        .map(|policy_id| policy_id.to_string())
+                        .collect(),
+                });
+            }
+        }
+
+        info!(
+            "Allowed actions for {} on {}: {}",
+            principal,
+            resource,
+            allowed_actions.len()
+        );
+
+        Ok(Response::new(ListAllowedActionsResponse {
+            allowed_actions,
+            errors,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hodei_domain::{CedarPolicy, DecisionLogFilter, PolicyId};
+    use hodei_infrastructure::repository::RepositoryAdapter;
+    use tonic::Code;
+
+    const SCHEMA: &str = r#"
+        entity Group;
+        entity User in [Group] { level: Long };
+        entity Doc { owner: User };
+        action view, edit appliesTo { principal: User, resource: Doc };
+        action delete appliesTo { principal: User, resource: Doc, context: { mfa: Bool } };
+    "#;
+
+    type Service = AuthorizationDataService<RepositoryAdapter>;
+
+    async fn create_service() -> (Service, Arc<RepositoryAdapter>) {
+        let repository = Arc::new(RepositoryAdapter::new(":memory:").await.unwrap());
+        let cache = Arc::new(CacheManager::new(repository.clone()));
+        (
+            AuthorizationDataService::new(repository.clone(), cache),
+            repository,
+        )
+    }
+
+    async fn create_store(repo: &RepositoryAdapter) -> PolicyStoreId {
+        repo.create_policy_store("Store".to_string(), None, vec![], "test".to_string())
+            .await
+            .unwrap()
+            .id
+    }
+
+    /// Creates a store with the test schema, strict validation and policies
+    async fn create_strict_store(
+        repo: &RepositoryAdapter,
+        policies: &[(&str, &str)],
+    ) -> PolicyStoreId {
+        let store = create_store(repo).await;
+        repo.put_schema(&store, SCHEMA.to_string()).await.unwrap();
+        repo.update_policy_store(&store, None, None, None, Some(true), None)
+            .await
+            .unwrap();
+        for (policy_id, statement) in policies {
+            create_policy(repo, &store, policy_id, statement).await;
+        }
+        store
+    }
+
+    async fn create_policy(
+        repo: &RepositoryAdapter,
+        store: &PolicyStoreId,
+        id: &str,
+        statement: &str,
+    ) {
+        repo.create_policy(
+            store,
+            &PolicyId::new(id.to_string()).unwrap(),
+            &CedarPolicy::new(statement.to_string()).unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    fn uid(entity_type: &str, entity_id: &str) -> EntityIdentifier {
+        EntityIdentifier {
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+        }
+    }
+
+    fn request(
+        store: &PolicyStoreId,
+        principal: &str,
+        action: &str,
+        resource: &str,
+    ) -> IsAuthorizedRequest {
+        IsAuthorizedRequest {
+            policy_store_id: store.to_string(),
+            principal: Some(uid("User", principal)),
+            action: Some(uid("Action", action)),
+            resource: Some(uid("Doc", resource)),
+            context: None,
+            entities: vec![],
+            correlation_id: None,
+            explain: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_strict_store_validates_before_denying() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[]).await;
+
+        let err = service
+            .is_authorized(Request::new(request(&store, "alice", "undeclared", "d1")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(err.metadata().get("x-invalid-field").unwrap(), "action");
+
+        let response = service
+            .is_authorized(Request::new(request(&store, "alice", "view", "d1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(
+            response.errors,
+            vec!["No policies found in policy store".to_string()]
+        );
+    }
+
+    fn entity(
+        identifier: EntityIdentifier,
+        attributes: &[(&str, &str)],
+        parents: Vec<EntityIdentifier>,
+    ) -> Entity {
+        Entity {
+            identifier: Some(identifier),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            parents,
+        }
+    }
+
+    /// `Doc::"<id>"` owned by `User::"<owner>"`
+    fn doc(id: &str, owner: &str) -> Entity {
+        let owner = format!(r#"{{"__entity": {{"type": "User", "id": "{}"}}}}"#, owner);
+        entity(uid("Doc", id), &[("owner", owner.as_str())], vec![])
+    }
+
+    const OWNER_CAN_VIEW: (&str, &str) = (
+        "owner-view",
+        r#"permit(principal, action == Action::"view", resource) when { resource.owner == principal };"#,
+    );
+    const ADMINS_CAN_DO_ANYTHING: (&str, &str) = (
+        "admins",
+        r#"permit(principal in Group::"admins", action, resource);"#,
+    );
+
+    #[tokio::test]
+    async fn test_partial_authorization_returns_residuals_for_unknown_resource() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW, ADMINS_CAN_DO_ANYTHING]).await;
+
+        let partial = |resource: Option<EntityIdentifier>, entities: Vec<Entity>| {
+            IsAuthorizedPartialRequest {
+                policy_store_id: store.to_string(),
+                principal: Some(uid("User", "alice")),
+                action: Some(uid("Action", "view")),
+                resource,
+                context: None,
+                entities,
+                principal_type: None,
+                resource_type: Some("Doc".to_string()),
+            }
+        };
+
+        // The owner policy depends on the unknown resource; alice is not an
+        // admin, so the admins policy is trivially false and not a residual
+        let response = service
+            .is_authorized_partial(Request::new(partial(None, vec![])))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.decision(), Decision::Unspecified);
+        assert!(response.determining_policies.is_empty());
+        let residual_ids: Vec<&str> = response
+            .residuals
+            .iter()
+            .map(|residual| residual.policy_id.as_str())
+            .collect();
+        assert_eq!(residual_ids, vec!["owner-view"]);
+        assert!(response.residuals[0].cedar.contains("resource"));
+        assert!(serde_json::from_str::<serde_json::Value>(&response.residuals[0].json).is_ok());
+
+        // A known resource leaves nothing to decide
+        let response = service
+            .is_authorized_partial(Request::new(partial(
+                Some(uid("Doc", "d1")),
+                vec![doc("d1", "alice")],
+            )))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(
+            response.determining_policies,
+            vec!["owner-view".to_string()]
+        );
+        assert!(response.residuals.is_empty());
+    }
+
+    fn user(id: &str, level: i64, groups: &[&str]) -> Entity {
+        let level = level.to_string();
+        entity(
+            uid("User", id),
+            &[("level", level.as_str())],
+            groups.iter().map(|group| uid("Group", group)).collect(),
+        )
+    }
+
+    const HIGH_LEVEL_CAN_EDIT: (&str, &str) = (
+        "high-level-edit",
+        r#"permit(principal, action == Action::"edit", resource) when { principal.level > 5 };"#,
+    );
+
+    #[tokio::test]
+    async fn test_list_allowed_actions_returns_only_allowed_schema_actions() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW, HIGH_LEVEL_CAN_EDIT]).await;
+
+        let list = |level: i64| ListAllowedActionsRequest {
+            policy_store_id: store.to_string(),
+            principal: Some(uid("User", "alice")),
+            resource: Some(uid("Doc", "d1")),
+            context: None,
+            entities: vec![user("alice", level, &[]), doc("d1", "alice")],
+        };
+        let allowed = |response: &ListAllowedActionsResponse| -> Vec<String> {
+            response
+                .allowed_actions
+                .iter()
+                .map(|allowed| allowed.action.as_ref().unwrap().entity_id.clone())
+                .collect()
+        };
+
+        let response = service
+            .list_allowed_actions(Request::new(list(3)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(allowed(&response), vec!["view".to_string()]);
+        assert_eq!(
+            response.allowed_actions[0].determining_policies,
+            vec!["owner-view".to_string()]
+        );
+        // delete needs an `mfa` context the request does not send
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].starts_with(r#"Action::"delete""#));
+
+        let response = service
+            .list_allowed_actions(Request::new(list(9)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            allowed(&response),
+            vec!["edit".to_string(), "view".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_keeps_item_order_and_correlation_ids() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW]).await;
+
+        // Even items view a document alice owns, odd items one she does not
+        let items: Vec<IsAuthorizedRequest> = (0..20)
+            .map(|i| {
+                let resource = if i % 2 == 0 { "mine" } else { "theirs" };
+                IsAuthorizedRequest {
+                    correlation_id: Some(format!("item-{}", i)),
+                    ..request(&store, "alice", "view", resource)
+                }
+            })
+            .collect();
+
+        let response = service
+            .batch_is_authorized(Request::new(BatchIsAuthorizedRequest {
+                policy_store_id: store.to_string(),
+                requests: items,
+                entities: vec![doc("mine", "alice"), doc("theirs", "bob")],
+                principal: None,
+                resource: None,
+                context: None,
+                max_concurrency: Some(4),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.responses.len(), 20);
+        for (i, item) in response.responses.iter().enumerate() {
+            assert_eq!(
+                item.correlation_id.as_deref(),
+                Some(format!("item-{}", i).as_str())
+            );
+            let expected = if i % 2 == 0 {
+                Decision::Allow
+            } else {
+                Decision::Deny
+            };
+            assert_eq!(item.decision(), expected, "item {}", i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_item_error_does_not_fail_other_items() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW]).await;
+
+        let response = service
+            .batch_is_authorized(Request::new(BatchIsAuthorizedRequest {
+                policy_store_id: store.to_string(),
+                requests: vec![
+                    request(&store, "alice", "view", "mine"),
+                    request(&store, "alice", "undeclared", "mine"),
+                    IsAuthorizedRequest {
+                        policy_store_id: "ps_other".to_string(),
+                        ..request(&store, "alice", "view", "mine")
+                    },
+                    // Own entities win over the batch entities
+                    IsAuthorizedRequest {
+                        entities: vec![doc("mine", "bob")],
+                        ..request(&store, "alice", "view", "mine")
+                    },
+                ],
+                entities: vec![doc("mine", "alice")],
+                principal: None,
+                resource: None,
+                context: None,
+                max_concurrency: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let decisions: Vec<Decision> = response.responses.iter().map(|r| r.decision()).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Allow,
+                Decision::Deny,
+                Decision::Deny,
+                Decision::Deny
+            ]
+        );
+        assert!(response.responses[0].errors.is_empty());
+        assert!(response.responses[1].errors[0].contains("action"));
+        assert!(response.responses[2].errors[0].contains("does not match batch policy store"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_reports_deny_reason_and_policy_outcomes() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(
+            &repo,
+            &[
+                OWNER_CAN_VIEW,
+                (
+                    "no-level-0",
+                    "forbid(principal, action, resource) when { principal.level == 0 };",
+                ),
+                (
+                    "broken",
+                    r#"permit(principal, action == Action::"view", resource) when { principal.missing == 1 };"#,
+                ),
+            ],
+        )
+        .await;
+
+        let explain = |level: i64, resource: &str| {
+            let service = &service;
+            let req = IsAuthorizedRequest {
+                entities: vec![
+                    user("alice", level, &[]),
+                    doc("mine", "alice"),
+                    doc("theirs", "bob"),
+                ],
+                explain: true,
+                ..request(&store, "alice", "view", resource)
+            };
+            async move {
+                service
+                    .is_authorized(Request::new(req))
+                    .await
+                    .unwrap()
+                    .into_inner()
+            }
+        };
+        let policy = |explanation: &DecisionExplanation, id: &str| {
+            explanation
+                .policies
+                .iter()
+                .find(|policy| policy.policy_id == id)
+                .cloned()
+                .unwrap()
+        };
+
+        // Allowed: no deny reason, the failing policy is reported
+        let response = explain(3, "mine").await;
+        assert_eq!(response.decision(), Decision::Allow);
+        let explanation = response.explanation.unwrap();
+        assert_eq!(explanation.deny_reason(), DenyReason::Unspecified);
+        assert!(policy(&explanation, "owner-view").determining);
+        let broken = policy(&explanation, "broken");
+        assert!(broken.errored && broken.scope_matched && !broken.determining);
+        let errored: Vec<&str> = explanation
+            .policy_errors
+            .iter()
+            .map(|err| err.policy_id.as_str())
+            .collect();
+        assert_eq!(errored, vec!["broken"]);
+
+        // A satisfied forbid denies explicitly
+        let response = explain(0, "mine").await;
+        let explanation = response.explanation.unwrap();
+        assert_eq!(explanation.deny_reason(), DenyReason::Explicit);
+        let forbid = policy(&explanation, "no-level-0");
+        assert_eq!(forbid.effect, "forbid");
+        assert!(forbid.determining);
+        assert_eq!(
+            response.determining_policies,
+            vec!["no-level-0".to_string()]
+        );
+
+        // No satisfied permit denies implicitly
+        let response = explain(3, "theirs").await;
+        let explanation = response.explanation.unwrap();
+        assert_eq!(explanation.deny_reason(), DenyReason::Implicit);
+        let owner = policy(&explanation, "owner-view");
+        assert!(owner.scope_matched && !owner.determining);
+        assert!(response.determining_policies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_returns_every_decision_with_its_correlation_id() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW]).await;
+
+        let requests: Vec<Result<IsAuthorizedRequest, Status>> = (0..20)
+            .map(|i| {
+                let resource = if i % 2 == 0 { "mine" } else { "theirs" };
+                Ok(IsAuthorizedRequest {
+                    correlation_id: Some(format!("item-{}", i)),
+                    entities: vec![doc("mine", "alice"), doc("theirs", "bob")],
+                    ..request(&store, "alice", "view", resource)
+                })
+            })
+            .collect();
+        let inbound = tokio_stream::iter(requests);
+
+        let responses: Vec<IsAuthorizedResponse> = service
+            .evaluate_stream(inbound, Arc::default())
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        // Decisions arrive as they complete, so match them by correlation id
+        let decisions: BTreeMap<String, Decision> = responses
+            .iter()
+            .map(|r| (r.correlation_id.clone().unwrap(), r.decision()))
+            .collect();
+        assert_eq!(decisions.len(), 20);
+        for i in 0..20 {
+            let expected = if i % 2 == 0 {
+                Decision::Allow
+            } else {
+                Decision::Deny
+            };
+            assert_eq!(decisions[&format!("item-{}", i)], expected, "item {}", i);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_stops_reading_while_decisions_are_not_consumed() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW]).await;
+        let service = service.with_batch_max_concurrency(2);
+
+        let (inbound_tx, inbound_rx) = mpsc::channel(1);
+        let mut outbound =
+            service.evaluate_stream(ReceiverStream::new(inbound_rx).map(Ok), Arc::default());
+
+        // Send without reading any decision until the service stops accepting
+        let mut accepted = 0;
+        for i in 0..20 {
+            let req = IsAuthorizedRequest {
+                correlation_id: Some(format!("item-{}", i)),
+                entities: vec![doc("mine", "alice")],
+                ..request(&store, "alice", "view", "mine")
+            };
+            let sent = tokio::time::timeout(Duration::from_millis(200), inbound_tx.send(req)).await;
+            if sent.is_err() {
+                break;
+            }
+            accepted += 1;
+        }
+        assert!(accepted < 20, "accepted all {} requests", accepted);
+
+        // Draining the decisions lets the remaining requests through
+        let sender = tokio::spawn(async move {
+            for i in accepted..20 {
+                let req = IsAuthorizedRequest {
+                    correlation_id: Some(format!("item-{}", i)),
+                    entities: vec![doc("mine", "alice")],
+                    ..request(&store, "alice", "view", "mine")
+                };
+                inbound_tx.send(req).await.unwrap();
+            }
+        });
+        let mut correlation_ids = HashSet::new();
+        while let Some(response) = outbound.next().await {
+            let response = response.unwrap();
+            assert_eq!(response.decision(), Decision::Allow);
+            correlation_ids.insert(response.correlation_id.unwrap());
+        }
+        sender.await.unwrap();
+        assert_eq!(correlation_ids.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_failed_evaluation_is_recorded_as_deny() {
+        let (service, repo) = create_service().await;
+        let store = create_strict_store(&repo, &[OWNER_CAN_VIEW]).await;
+        let (writer, handle) = DecisionLogWriter::spawn(repo.clone(), Default::default());
+        let service = service.with_decision_log(writer);
+
+        let err = service
+            .is_authorized(Request::new(request(&store, "alice", "undeclared", "mine")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        // Dropping the service flushes the decision log
+        drop(service);
+        handle.await.unwrap();
+
+        let logs = repo
+            .query_decision_logs(
+                &store,
+                &DecisionLogFilter {
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].decision, "DENY");
+        assert_eq!(logs[0].action, "Action::\"undeclared\"");
+        assert_eq!(logs[0].errors, vec![err.message().to_string()]);
+    }
+
+    const ISSUER: &str = "https://issuer.example.com";
+    const SECRET: &str = "test-secret";
+
+    /// Creates a shared-secret identity source for `issuer`
+    async fn create_identity_source(
+        repo: &RepositoryAdapter,
+        store: &PolicyStoreId,
+        issuer: &str,
+        claims_mapping: Option<&str>,
+    ) -> String {
+        repo.create_identity_source(
+            store,
+            &IdentitySourceType::SharedSecret,
+            serde_json::json!({ "issuer": issuer, "secret": SECRET }).to_string(),
+            claims_mapping.map(str::to_string),
+            None,
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    /// HS256 token of `sub` issued by `issuer`, with an email claim
+    fn token(issuer: &str, sub: &str) -> String {
+        let now = jsonwebtoken::get_current_timestamp();
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({
+                "iss": issuer,
+                "sub": sub,
+                "iat": now,
+                "exp": now + 300,
+                "email": format!("{}@example.com", sub),
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(SECRET.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn token_request(
+        store: &PolicyStoreId,
+        access_token: String,
+        action: &str,
+        resource: &str,
+    ) -> IsAuthorizedWithTokenRequest {
+        IsAuthorizedWithTokenRequest {
+            policy_store_id: store.to_string(),
+            identity_source_id: String::new(),
+            access_token,
+            action: Some(uid("Action", action)),
+            resource: Some(uid("Doc", resource)),
+            context: None,
+            entities: vec![],
+            explain: false,
+            proof_of_possession: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_without_claims_mapping_maps_email() {
+        let (service, repo) = create_service().await;
+        let service = service.with_shared_secret_identity_sources(true);
+        let store = create_store(&repo).await;
+        create_policy(
+            &repo,
+            &store,
+            "alice-email",
+            r#"permit(principal, action, resource) when { principal.email == "alice@example.com" };"#,
+        )
+        .await;
+        create_identity_source(&repo, &store, ISSUER, None).await;
+
+        for (sub, expected) in [("alice", Decision::Allow), ("bob", Decision::Deny)] {
+            let response = service
+                .is_authorized_with_token(Request::new(token_request(
+                    &store,
+                    token(ISSUER, sub),
+                    "view",
+                    "doc",
+                )))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(response.decision(), expected, "{}", sub);
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+        }
+    }
+
+    /// `User::"<id>"` claiming membership of `Group::"admins"`
+    fn forged_admin(id: &str) -> Entity {
+        entity(uid("User", id), &[], vec![uid("Group", "admins")])
+    }
+
+    fn token_item(action: &str, resource: &str, correlation_id: &str) -> TokenAuthorizationItem {
+        TokenAuthorizationItem {
+            action: Some(uid("Action", action)),
+            resource: Some(uid("Doc", resource)),
+            context: None,
+            entities: vec![],
+            correlation_id: Some(correlation_id.to_string()),
+            explain: false,
+        }
+    }
+
+    fn token_batch(
+        store: &PolicyStoreId,
+        access_token: String,
+        requests: Vec<TokenAuthorizationItem>,
+        entities: Vec<Entity>,
+    ) -> BatchIsAuthorizedWithTokenRequest {
+        BatchIsAuthorizedWithTokenRequest {
+            policy_store_id: store.to_string(),
+            identity_source_id: String::new(),
+            access_token,
+            requests,
+            entities,
+            context: None,
+            max_concurrency: None,
+            proof_of_possession: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_principal_cannot_be_replaced_by_request_entities() {
+        let (service, repo) = create_service().await;
+        let service = service.with_shared_secret_identity_sources(true);
+        let store = create_store(&repo).await;
+        create_policy(
+            &repo,
+            &store,
+            ADMINS_CAN_DO_ANYTHING.0,
+            ADMINS_CAN_DO_ANYTHING.1,
+        )
+        .await;
+        create_identity_source(&repo, &store, ISSUER, None).await;
+
+        let err = service
+            .is_authorized_with_token(Request::new(IsAuthorizedWithTokenRequest {
+                entities: vec![forged_admin("alice")],
+                ..token_request(&store, token(ISSUER, "alice"), "delete", "doc")
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        // Neither in the shared batch entities nor in an item's own
+        let err = service
+            .batch_is_authorized_with_token(Request::new(token_batch(
+                &store,
+                token(ISSUER, "alice"),
+                vec![token_item("delete", "doc", "item-0")],
+                vec![forged_admin("alice")],
+            )))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let response = service
+            .batch_is_authorized_with_token(Request::new(token_batch(
+                &store,
+                token(ISSUER, "alice"),
+                vec![TokenAuthorizationItem {
+                    entities: vec![forged_admin("alice")],
+                    ..token_item("delete", "doc", "item-0")
+                }],
+                vec![],
+            )))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.responses[0].decision(), Decision::Deny);
+        assert!(response.responses[0].errors[0].contains("token principal"));
+
+        // Other entities are still taken from the request
+        let response = service
+            .is_authorized_with_token(Request::new(IsAuthorizedWithTokenRequest {
+                entities: vec![forged_admin("bob")],
+                ..token_request(&store, token(ISSUER, "alice"), "delete", "doc")
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.decision(), Decision::Deny);
+        assert!(response.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_token_batch_keeps_order_and_isolates_item_errors() {
+        let (service, repo) = create_service().await;
+        let service = service.with_shared_secret_identity_sources(true);
+        let store = create_store(&repo).await;
+        create_policy(&repo, &store, OWNER_CAN_VIEW.0, OWNER_CAN_VIEW.1).await;
+        create_identity_source(&repo, &store, ISSUER, None).await;
+
+        let requests = vec![
+            token_item("view", "mine", "item-0"),
+            TokenAuthorizationItem {
+                context: Some("not json".to_string()),
+                ..token_item("view", "mine", "item-1")
+            },
+            token_item("view", "theirs", "item-2"),
+            TokenAuthorizationItem {
+                entities: vec![forged_admin("alice")],
+                ..token_item("view", "mine", "item-3")
+            },
+            token_item("view", "mine", "item-4"),
+        ];
+        let response = service
+            .batch_is_authorized_with_token(Request::new(token_batch(
+                &store,
+                token(ISSUER, "alice"),
+                requests,
+                vec![doc("mine", "alice"), doc("theirs", "bob")],
+            )))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let results: Vec<(String, Decision, bool)> = response
+            .responses
+            .iter()
+            .map(|r| {
+                (
+                    r.correlation_id.clone().unwrap(),
+                    r.decision(),
+                    r.errors.is_empty(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("item-0".to_string(), Decision::Allow, true),
+                ("item-1".to_string(), Decision::Deny, false),
+                ("item-2".to_string(), Decision::Deny, true),
+                ("item-3".to_string(), Decision::Deny, false),
+                ("item-4".to_string(), Decision::Allow, true),
+            ]
+        );
+
+        // The token is validated once for the batch, so a bad token fails
+        // the call instead of every item
+        let err = service
+            .batch_is_authorized_with_token(Request::new(token_batch(
+                &store,
+                token("https://other.example.com", "alice"),
+                vec![token_item("view", "mine", "item-0")],
+                vec![],
+            )))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+    }
+
+    #[tokio::test]
+    async fn test_identity_source_is_selected_by_token_issuer() {
+        const OTHER_ISSUER: &str = "https://other.example.com";
+        const UNKNOWN_ISSUER: &str = "https://unknown.example.com";
+
+        let (service, repo) = create_service().await;
+        let service = service.with_shared_secret_identity_sources(true);
+        let store = create_store(&repo).await;
+        create_policy(
+            &repo,
+            &store,
+            "anyone",
+            "permit(principal, action, resource);",
+        )
+        .await;
+        create_identity_source(&repo, &store, ISSUER, None).await;
+        let other = create_identity_source(&repo, &store, OTHER_ISSUER, None).await;
+        let authorize = |issuer: &str| {
+            service.is_authorized_with_token(Request::new(token_request(
+                &store,
+                token(issuer, "alice"),
+                "view",
+                "doc",
+            )))
+        };
+
+        // Matched: each token is validated by the source of its issuer
+        for issuer in [ISSUER, OTHER_ISSUER] {
+            let response = authorize(issuer).await.unwrap().into_inner();
+            assert_eq!(response.decision(), Decision::Allow, "{}", issuer);
+        }
+
+        // Unmatched without a default
+        let err = authorize(UNKNOWN_ISSUER).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+        assert!(err.message().contains("no default identity source"));
+
+        // Unmatched with a default: the default source validates the token
+        // and rejects its issuer
+        repo.update_policy_store(&store, None, None, None, None, Some(Some(other.clone())))
+            .await
+            .unwrap();
+        let err = authorize(UNKNOWN_ISSUER).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+        assert!(
+            err.message().starts_with("Invalid token"),
+            "{}",
+            err.message()
+        );
+        let response = authorize(ISSUER).await.unwrap().into_inner();
+        assert_eq!(response.decision(), Decision::Allow);
+
+        // Ambiguous: two sources share the issuer
+        let duplicate = create_identity_source(&repo, &store, OTHER_ISSUER, None).await;
+        let err = authorize(OTHER_ISSUER).await.unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        assert!(err.message().contains(&other) && err.message().contains(&duplicate));
     }
 }
@@ -103,7 +103,7 @@ pub struct AuthorizationResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateIdentitySourceRequest {
     pub policy_store_id: String,
-    pub configuration_type: String, // "cognito" or "oidc"
+    pub configuration_type: String, // "cognito", "oidc", "shared_secret" or "introspection"
     pub configuration_json: String,
     pub claims_mapping_json: Option<String>,
     pub description: Option<String>,
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::value_objects::*;
 
@@ -22,6 +23,9 @@ pub struct PolicyStore {
     pub identity_source_ids: Vec<String>,
     /// Default identity source ID to use when not explicitly specified
     pub default_identity_source_id: Option<String>,
+    /// Validate authorization requests against the schema
+    #[serde(default)]
+    pub strict_validation: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -44,6 +48,7 @@ impl PolicyStore {
             tags,
             identity_source_ids: Vec::new(),
             default_identity_source_id: None,
+            strict_validation: false,
             created_at: now,
             updated_at: now,
         }
@@ -110,16 +115,30 @@ impl Schema {
 }
 
 /// Policy entity - Represents a Cedar policy
+///
+/// Template-linked policies carry a [`TemplateLink`]; their `statement` is the
+/// rendered template, kept for display and snapshots only.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Policy {
     pub policy_store_id: PolicyStoreId,
     pub policy_id: PolicyId,
     pub statement: CedarPolicy,
     pub description: Option<String>,
+    pub template_link: Option<TemplateLink>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Link from a policy to the template it instantiates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateLink {
+    pub policy_template_id: String,
+    /// Entity bound to `?principal`, as a Cedar UID (e.g. `User::"alice"`)
+    pub principal: Option<Principal>,
+    /// Entity bound to `?resource`, as a Cedar UID (e.g. `Document::"doc1"`)
+    pub resource: Option<Resource>,
+}
+
 impl Policy {
     pub fn new(
         policy_store_id: PolicyStoreId,
@@ -133,10 +152,16 @@ impl Policy {
             policy_id,
             statement,
             description,
+            template_link: None,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Returns true if this policy is linked to a template
+    pub fn is_template_linked(&self) -> bool {
+        self.template_link.is_some()
+    }
 }
 
 /// Identity Source entity - Represents a source of identity information (Cognito, OIDC)
@@ -175,6 +200,132 @@ impl IdentitySource {
     }
 }
 
+/// Identifier of an entity in a policy store's entity store
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EntityKey {
+    pub entity_type: String,
+    pub entity_id: String,
+}
+
+impl EntityKey {
+    pub fn new(entity_type: String, entity_id: String) -> Self {
+        Self {
+            entity_type,
+            entity_id,
+        }
+    }
+}
+
+/// Stored Entity - A Cedar entity kept server-side for a policy store
+///
+/// Attribute values use Cedar's entity JSON format, so stored entities can be
+/// merged with the entities sent on each authorization request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEntity {
+    pub policy_store_id: PolicyStoreId,
+    pub key: EntityKey,
+    pub attributes: BTreeMap<String, serde_json::Value>,
+    pub parents: Vec<EntityKey>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl StoredEntity {
+    pub fn new(
+        policy_store_id: PolicyStoreId,
+        key: EntityKey,
+        attributes: BTreeMap<String, serde_json::Value>,
+        parents: Vec<EntityKey>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            policy_store_id,
+            key,
+            attributes,
+            parents,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Token Revocation - Denies the tokens whose `jti`, `sub` or `sid` claim
+/// has a given value
+///
+/// A revocation applies until `expires_at` (forever when unset), usually the
+/// `exp` of the revoked token or the end of the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRevocation {
+    pub policy_store_id: PolicyStoreId,
+    pub claim: RevokedClaim,
+    pub value: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TokenRevocation {
+    pub fn new(
+        policy_store_id: PolicyStoreId,
+        claim: RevokedClaim,
+        value: String,
+        expires_at: Option<DateTime<Utc>>,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            policy_store_id,
+            claim,
+            value,
+            expires_at,
+            reason,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Whether the revocation no longer applies at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Decision Log - An authorization decision recorded by the data plane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionLog {
+    /// Assigned by the repository when the log is stored
+    pub id: Option<i64>,
+    pub policy_store_id: PolicyStoreId,
+    /// Entity UIDs in Cedar syntax, e.g. `User::"alice"`
+    pub principal: String,
+    pub action: String,
+    pub resource: String,
+    /// `ALLOW` or `DENY`
+    pub decision: String,
+    pub determining_policies: Vec<String>,
+    pub errors: Vec<String>,
+    /// Time spent evaluating the request
+    pub latency_micros: u64,
+    /// Caller metadata such as remote address and user agent
+    pub caller: BTreeMap<String, String>,
+    pub correlation_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Filter for querying decision logs; unset fields match every log
+#[derive(Debug, Clone, Default)]
+pub struct DecisionLogFilter {
+    /// Inclusive lower bound
+    pub start_time: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub end_time: Option<DateTime<Utc>>,
+    pub principal: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub decision: Option<String>,
+    /// Only logs older than this id (pagination cursor)
+    pub before_id: Option<i64>,
+    pub limit: u32,
+}
+
 /// Policy Template entity - Represents a reusable policy template
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyTemplate {
@@ -28,13 +28,19 @@ pub trait PolicyRepository: Send + Sync {
     /// Lists all Policy Stores
     async fn list_policy_stores(&self) -> DomainResult<Vec<PolicyStore>>;
 
-    /// Updates a Policy Store description
+    /// Updates a Policy Store's description and settings in one write
+    ///
+    /// `strict_validation` turns strict request validation on or off and
+    /// `default_identity_source_id` sets (`Some(Some(_))`) or clears
+    /// (`Some(None)`) the default identity source; `None` leaves either unchanged.
     async fn update_policy_store(
         &self,
         id: &PolicyStoreId,
         name: Option<String>,
         description: Option<String>,
         status: Option<String>,
+        strict_validation: Option<bool>,
+        default_identity_source_id: Option<Option<String>>,
     ) -> DomainResult<PolicyStore>;
 
     /// Deletes a Policy Store and all its content (cascade)
@@ -90,6 +96,28 @@ pub trait PolicyRepository: Send + Sync {
         description: Option<String>,
     ) -> DomainResult<Policy>;
 
+    /// Creates a policy linked to a policy template
+    ///
+    /// `statement` is the rendered template, stored for display only.
+    async fn create_template_linked_policy(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_id: &PolicyId,
+        link: &TemplateLink,
+        statement: &CedarPolicy,
+        description: Option<String>,
+    ) -> DomainResult<Policy>;
+
+    /// Replaces a policy with a link to a policy template
+    async fn update_template_linked_policy(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_id: &PolicyId,
+        link: &TemplateLink,
+        statement: &CedarPolicy,
+        description: Option<String>,
+    ) -> DomainResult<Policy>;
+
     /// Deletes a policy
     async fn delete_policy(
         &self,
@@ -164,6 +192,75 @@ pub trait PolicyRepository: Send + Sync {
         template_id: &str,
     ) -> DomainResult<()>;
 
+    // ============================================================================
+    // Entity Store Operations
+    // ============================================================================
+
+    /// Creates or replaces entities in a Policy Store's entity store
+    async fn put_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        entities: &[StoredEntity],
+    ) -> DomainResult<()>;
+
+    /// Gets stored entities by key, skipping keys that are not stored
+    async fn get_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[EntityKey],
+    ) -> DomainResult<Vec<StoredEntity>>;
+
+    /// Lists all stored entities for a Policy Store
+    async fn list_entities(&self, policy_store_id: &PolicyStoreId)
+    -> DomainResult<Vec<StoredEntity>>;
+
+    /// Deletes stored entities, returning how many were removed
+    async fn delete_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[EntityKey],
+    ) -> DomainResult<u64>;
+
+    // ============================================================================
+    // Token Revocation Operations
+    // ============================================================================
+
+    /// Creates or replaces token revocations (one per claim and value), and
+    /// purges the Policy Store's expired revocations
+    async fn put_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        revocations: &[TokenRevocation],
+    ) -> DomainResult<()>;
+
+    /// Lists the unexpired token revocations of a Policy Store
+    async fn list_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+    ) -> DomainResult<Vec<TokenRevocation>>;
+
+    /// Deletes token revocations by claim and value, returning how many were
+    /// removed
+    async fn delete_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[(RevokedClaim, String)],
+    ) -> DomainResult<u64>;
+
+    // ============================================================================
+    // Decision Log Operations
+    // ============================================================================
+
+    /// Stores a batch of authorization decisions
+    async fn record_decisions(&self, logs: &[DecisionLog]) -> DomainResult<()>;
+
+    /// Queries the decisions of a Policy Store, newest first
+    async fn query_decision_logs(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        filter: &DecisionLogFilter,
+    ) -> DomainResult<Vec<DecisionLog>>;
+
     // ============================================================================
     // Snapshot / Version Control Operations
     // ============================================================================
@@ -231,6 +231,11 @@ impl fmt::Display for AuthorizationDecision {
 pub enum IdentitySourceType {
     Cognito,
     Oidc,
+    /// HMAC-signed tokens verified with a secret stored on the server
+    /// (development and testing only)
+    SharedSecret,
+    /// Opaque tokens validated through OAuth 2.0 token introspection
+    Introspection,
 }
 
 impl fmt::Display for IdentitySourceType {
@@ -238,6 +243,8 @@ impl fmt::Display for IdentitySourceType {
         match self {
             Self::Cognito => write!(f, "cognito"),
             Self::Oidc => write!(f, "oidc"),
+            Self::SharedSecret => write!(f, "shared_secret"),
+            Self::Introspection => write!(f, "introspection"),
         }
     }
 }
@@ -249,6 +256,8 @@ impl TryFrom<String> for IdentitySourceType {
         match value.to_lowercase().as_str() {
             "cognito" => Ok(Self::Cognito),
             "oidc" => Ok(Self::Oidc),
+            "shared_secret" => Ok(Self::SharedSecret),
+            "introspection" => Ok(Self::Introspection),
             _ => Err(DomainError::InvalidEntityIdentifier(format!(
                 "Invalid identity source type: {}",
                 value
@@ -256,3 +265,47 @@ impl TryFrom<String> for IdentitySourceType {
         }
     }
 }
+
+/// Token claim matched by a token revocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RevokedClaim {
+    /// A single token, by its `jti`
+    Jti,
+    /// Every token of a subject, by its `sub`
+    Sub,
+    /// Every token of a session, by its `sid`
+    Sid,
+}
+
+impl RevokedClaim {
+    /// Name of the claim in a token
+    pub fn claim_name(&self) -> &'static str {
+        match self {
+            Self::Jti => "jti",
+            Self::Sub => "sub",
+            Self::Sid => "sid",
+        }
+    }
+}
+
+impl fmt::Display for RevokedClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.claim_name())
+    }
+}
+
+impl TryFrom<String> for RevokedClaim {
+    type Error = DomainError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "jti" => Ok(Self::Jti),
+            "sub" => Ok(Self::Sub),
+            "sid" => Ok(Self::Sid),
+            _ => Err(DomainError::InvalidEntityIdentifier(format!(
+                "Invalid revoked claim: {}",
+                value
+            ))),
+        }
+    }
+}
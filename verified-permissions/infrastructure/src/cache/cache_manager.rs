@@ -1,21 +1,22 @@
-//! Cache Manager - Gestiona todos los caches de Policy Stores
+//! Cache Manager - Manages the caches of all Policy Stores
 
+use crate::cache::policy_store_cache::{CacheStats, CompiledPolicyStore};
 use crate::cache::PolicyStoreCache;
-use crate::error::Result;
-use crate::storage::{PolicyRepository, models::{PolicyStore, Policy}};
+use crate::error::{AuthorizationError, Result};
+use hodei_domain::{DomainError, PolicyRepository, PolicyStoreId};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-
-/// Gestor global de caches para todos los Policy Stores
-/// 
-/// Responsabilidades:
-/// - Mantener un cache por cada Policy Store
-/// - Sincronizar cambios entre DB y cache
-/// - Proporcionar acceso thread-safe a los caches
-/// 
-/// # Arquitectura
-/// 
+use tokio::sync::{Mutex, RwLock};
+
+/// Global cache manager for all Policy Stores
+///
+/// Responsibilities:
+/// - Keep one compiled cache per Policy Store
+/// - Rebuild a store's cache when the control plane changes it
+/// - Provide thread-safe access to the compiled snapshots
+///
+/// # Architecture
+///
 /// ```text
 /// CacheManager
 ///   ├─ Store1 Cache (PolicySet + Schema)
@@ -23,41 +24,40 @@ use tokio::sync::RwLock;
 ///   └─ Store3 Cache (PolicySet + Schema)
 /// ```
 pub struct CacheManager {
-    /// Mapa de caches por policy_store_id
+    /// Caches keyed by policy_store_id
     caches: Arc<RwLock<HashMap<String, Arc<PolicyStoreCache>>>>,
-    
-    /// Repository para persistencia
+
+    /// Per-store locks serializing reloads, so snapshots are swapped in the
+    /// order they were read from the database
+    reload_locks: std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>,
+
+    /// Repository used to (re)build caches
     pub(crate) repository: Arc<dyn PolicyRepository>,
 }
 
 impl CacheManager {
-    /// Crea un nuevo CacheManager
-    /// 
-    /// # Arguments
-    /// * `repository` - Repository para acceso a la base de datos
+    /// Creates a new CacheManager
     pub fn new(repository: Arc<dyn PolicyRepository>) -> Self {
         Self {
             caches: Arc::new(RwLock::new(HashMap::new())),
+            reload_locks: std::sync::Mutex::new(HashMap::new()),
             repository,
         }
     }
-    
-    /// Inicializa el cache manager cargando todos los policy stores desde DB
-    /// 
-    /// Este método debe ser llamado al inicio del servidor para cargar
-    /// todos los datos en memoria.
-    /// 
-    /// # Errors
-    /// Retorna error si no se pueden cargar los stores desde DB
+
+    /// Loads every policy store from the database into memory
+    ///
+    /// Should be called at server startup. Stores that fail to load are
+    /// logged and loaded lazily on first use instead.
     pub async fn initialize(&self) -> Result<()> {
         tracing::info!("Initializing cache manager...");
-        
+
         let stores = self.repository.list_policy_stores().await?;
         let mut loaded = 0;
         let mut errors = 0;
-        
+
         for store in stores {
-            match self.load_store_cache(&store.id).await {
+            match self.reload(&store.id).await {
                 Ok(_) => loaded += 1,
                 Err(e) => {
                     tracing::error!("Failed to load cache for store {}: {}", store.id, e);
@@ -65,238 +65,140 @@ impl CacheManager {
                 }
             }
         }
-        
+
         tracing::info!(
             "Cache manager initialized: {} stores loaded, {} errors",
-            loaded, errors
-        );
-        
-        Ok(())
-    }
-    
-    /// Carga el cache para un policy store específico
-    async fn load_store_cache(&self, policy_store_id: &str) -> Result<()> {
-        let cache = Arc::new(PolicyStoreCache::new(policy_store_id.to_string()));
-        cache.load_from_db(self.repository.as_ref()).await?;
-        
-        self.caches.write().await.insert(
-            policy_store_id.to_string(),
-            cache
+            loaded,
+            errors
         );
-        
+
         Ok(())
     }
-    
-    /// Obtiene el cache de un policy store
-    /// 
-    /// # Arguments
-    /// * `policy_store_id` - ID del policy store
-    /// 
-    /// # Returns
-    /// Arc al cache del store
-    /// 
-    /// # Errors
-    /// Retorna error si el store no existe
+
+    /// Gets the cache of a policy store, if it is loaded
     pub async fn get_cache(&self, policy_store_id: &str) -> Result<Arc<PolicyStoreCache>> {
-        let caches = self.caches.read().await;
-        
-        caches
+        self.caches
+            .read()
+            .await
             .get(policy_store_id)
             .cloned()
             .ok_or_else(|| {
-                crate::error::AuthorizationError::NotFound(
-                    format!("Policy store not found: {}", policy_store_id)
-                )
+                AuthorizationError::NotFound(format!(
+                    "Policy store not found: {}",
+                    policy_store_id
+                ))
             })
     }
-    
-    // ========================================================================
-    // Policy Store Operations (DB + Cache)
-    // ========================================================================
-    
-    /// Crea un nuevo policy store (DB + Cache)
-    /// 
-    /// # Arguments
-    /// * `description` - Descripción opcional
-    /// 
-    /// # Returns
-    /// El policy store creado
-    pub async fn create_policy_store(&self, description: Option<String>) -> Result<PolicyStore> {
-        // 1. Crear en DB
-        let store = self.repository.create_policy_store(description).await?;
-        
-        // 2. Crear cache vacío
-        let cache = Arc::new(PolicyStoreCache::new(store.id.clone()));
-        self.caches.write().await.insert(store.id.clone(), cache);
-        
-        tracing::info!("Policy store created: {}", store.id);
-        
-        Ok(store)
-    }
-    
-    /// Elimina un policy store (DB + Cache)
-    /// 
-    /// # Arguments
-    /// * `policy_store_id` - ID del policy store
-    pub async fn delete_policy_store(&self, policy_store_id: &str) -> Result<()> {
-        // 1. Eliminar de DB (cascade)
-        self.repository.delete_policy_store(policy_store_id).await?;
-        
-        // 2. Eliminar cache
-        self.caches.write().await.remove(policy_store_id);
-        
-        tracing::info!("Policy store deleted: {}", policy_store_id);
-        
-        Ok(())
+
+    /// Gets the compiled snapshot of a policy store
+    ///
+    /// Served from memory. On a miss the store is loaded from the database,
+    /// which only happens for stores created before this process started or
+    /// whose cache was invalidated.
+    ///
+    /// # Errors
+    /// Returns `NotFound` if the policy store does not exist, and `Internal`
+    /// if it cannot be read from the database
+    pub async fn get_compiled(
+        &self,
+        policy_store_id: &PolicyStoreId,
+    ) -> Result<Arc<CompiledPolicyStore>> {
+        if let Ok(cache) = self.get_cache(policy_store_id.as_str()).await {
+            return Ok(cache.snapshot().await);
+        }
+
+        self.repository
+            .get_policy_store(policy_store_id)
+            .await
+            .map_err(|e| match e {
+                DomainError::PolicyStoreNotFound(_) => AuthorizationError::NotFound(e.to_string()),
+                e => AuthorizationError::Internal(e.to_string()),
+            })?;
+
+        self.reload(policy_store_id).await?;
+        Ok(self.get_cache(policy_store_id.as_str()).await?.snapshot().await)
     }
-    
+
     // ========================================================================
-    // Schema Operations (DB + Cache)
+    // Synchronization
     // ========================================================================
-    
-    /// Guarda o actualiza el schema (DB + Cache)
-    /// 
-    /// # Arguments
-    /// * `policy_store_id` - ID del policy store
-    /// * `schema` - Schema en formato JSON
-    pub async fn put_schema(&self, policy_store_id: &str, schema: String) -> Result<()> {
-        // 1. Guardar en DB
-        self.repository.put_schema(policy_store_id, schema.clone()).await?;
-        
-        // 2. Actualizar cache
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.update_schema(&schema).await?;
-        
-        tracing::info!("Schema updated for store: {}", policy_store_id);
-        
-        Ok(())
-    }
-    
-    /// Elimina el schema (DB + Cache)
-    pub async fn delete_schema(&self, policy_store_id: &str) -> Result<()> {
-        // 1. Eliminar de DB
-        self.repository.delete_schema(policy_store_id).await?;
-        
-        // 2. Eliminar de cache
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.remove_schema().await;
-        
-        tracing::info!("Schema deleted for store: {}", policy_store_id);
-        
+
+    /// Rebuilds the cache of a policy store from the database
+    ///
+    /// The new snapshot is compiled off to the side and swapped in atomically;
+    /// on failure the previous snapshot stays in place. Reloads of the same
+    /// store run one at a time, so an older read of the database never
+    /// replaces a newer one.
+    pub async fn reload(&self, policy_store_id: &PolicyStoreId) -> Result<()> {
+        let reload_lock = self.reload_lock(policy_store_id);
+        let _reloading = reload_lock.lock().await;
+
+        let compiled =
+            PolicyStoreCache::compile(policy_store_id.as_str(), self.repository.as_ref()).await?;
+
+        let existing = self.caches.read().await.get(policy_store_id.as_str()).cloned();
+        match existing {
+            Some(cache) => cache.replace(compiled).await,
+            None => {
+                let cache = Arc::new(PolicyStoreCache::new(policy_store_id.to_string()));
+                cache.replace(compiled).await;
+                self.caches
+                    .write()
+                    .await
+                    .insert(policy_store_id.to_string(), cache);
+            }
+        }
+
+        tracing::debug!("Cache reloaded for store: {}", policy_store_id);
+
         Ok(())
     }
-    
-    // ========================================================================
-    // Policy Operations (DB + Cache)
-    // ========================================================================
-    
-    /// Crea una política (DB + Cache)
-    /// 
-    /// # Arguments
-    /// * `policy_store_id` - ID del policy store
-    /// * `policy_id` - ID de la política
-    /// * `statement` - Statement de Cedar
-    /// * `description` - Descripción opcional
-    /// 
-    /// # Returns
-    /// La política creada
-    pub async fn create_policy(
-        &self,
-        policy_store_id: &str,
-        policy_id: &str,
-        statement: String,
-        description: Option<String>,
-    ) -> Result<Policy> {
-        // 1. Guardar en DB
-        let policy = self.repository.create_policy(
-            policy_store_id,
-            policy_id,
-            statement.clone(),
-            description,
-        ).await?;
-        
-        // 2. Actualizar cache
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.add_policy(policy_id, &statement).await?;
-        
-        tracing::info!("Policy created: {} in store {}", policy_id, policy_store_id);
-        
-        Ok(policy)
-    }
-    
-    /// Actualiza una política (DB + Cache)
-    pub async fn update_policy(
-        &self,
-        policy_store_id: &str,
-        policy_id: &str,
-        statement: String,
-        description: Option<String>,
-    ) -> Result<Policy> {
-        // 1. Actualizar en DB
-        let policy = self.repository.update_policy(
-            policy_store_id,
-            policy_id,
-            statement.clone(),
-            description,
-        ).await?;
-        
-        // 2. Actualizar cache (remove + add)
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.remove_policy(policy_id).await?;
-        cache.add_policy(policy_id, &statement).await?;
-        
-        tracing::info!("Policy updated: {} in store {}", policy_id, policy_store_id);
-        
-        Ok(policy)
+
+    /// Lock held while the cache of a policy store is being rebuilt
+    fn reload_lock(&self, policy_store_id: &PolicyStoreId) -> Arc<Mutex<()>> {
+        self.reload_locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(policy_store_id.to_string())
+            .or_default()
+            .clone()
     }
-    
-    /// Elimina una política (DB + Cache)
-    pub async fn delete_policy(&self, policy_store_id: &str, policy_id: &str) -> Result<()> {
-        // 1. Eliminar de DB
-        self.repository.delete_policy(policy_store_id, policy_id).await?;
-        
-        // 2. Eliminar de cache
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.remove_policy(policy_id).await?;
-        
-        tracing::info!("Policy deleted: {} from store {}", policy_id, policy_store_id);
-        
-        Ok(())
+
+    /// Drops the cache of a policy store
+    ///
+    /// The next evaluation for the store reloads it from the database.
+    pub async fn invalidate(&self, policy_store_id: &PolicyStoreId) {
+        self.caches.write().await.remove(policy_store_id.as_str());
+        tracing::debug!("Cache invalidated for store: {}", policy_store_id);
     }
-    
-    // ========================================================================
-    // Synchronization
-    // ========================================================================
-    
-    /// Recarga el cache de un policy store desde la DB
-    /// 
-    /// Útil para sincronizar cambios realizados externamente.
-    /// 
-    /// # Arguments
-    /// * `policy_store_id` - ID del policy store
-    pub async fn reload_cache(&self, policy_store_id: &str) -> Result<()> {
-        let cache = self.get_cache(policy_store_id).await?;
-        cache.load_from_db(self.repository.as_ref()).await?;
-        
-        tracing::info!("Cache reloaded for store: {}", policy_store_id);
-        
-        Ok(())
+
+    /// Reloads the cache of a policy store, invalidating it if that fails
+    ///
+    /// Used after writes: a store must never keep serving a snapshot that is
+    /// older than a change that has already been committed.
+    pub async fn refresh(&self, policy_store_id: &PolicyStoreId) {
+        if let Err(e) = self.reload(policy_store_id).await {
+            tracing::warn!(
+                "Failed to reload cache for store {}, invalidating: {}",
+                policy_store_id,
+                e
+            );
+            self.invalidate(policy_store_id).await;
+        }
     }
-    
-    /// Recarga todos los caches desde la DB
+
+    /// Reloads every cached store from the database
     pub async fn reload_all_caches(&self) -> Result<()> {
         tracing::info!("Reloading all caches...");
-        
-        let cache_ids: Vec<String> = {
-            let caches = self.caches.read().await;
-            caches.keys().cloned().collect()
-        };
-        
+
+        let cache_ids: Vec<String> = self.caches.read().await.keys().cloned().collect();
+
         let mut reloaded = 0;
         let mut errors = 0;
-        
-        for policy_store_id in cache_ids {
-            match self.reload_cache(&policy_store_id).await {
+
+        for id in cache_ids {
+            let policy_store_id = PolicyStoreId::new(id)?;
+            match self.reload(&policy_store_id).await {
                 Ok(_) => reloaded += 1,
                 Err(e) => {
                     tracing::error!("Failed to reload cache for {}: {}", policy_store_id, e);
@@ -304,29 +206,30 @@ impl CacheManager {
                 }
             }
         }
-        
+
         tracing::info!("All caches reloaded: {} success, {} errors", reloaded, errors);
-        
+
         Ok(())
     }
-    
+
     // ========================================================================
     // Statistics
     // ========================================================================
-    
-    /// Obtiene estadísticas de todos los caches
-    pub async fn stats(&self) -> Vec<crate::cache::policy_store_cache::CacheStats> {
-        let caches = self.caches.read().await;
-        let mut stats = Vec::new();
-        
-        for cache in caches.values() {
+
+    /// Gets statistics for all caches
+    pub async fn stats(&self) -> Vec<CacheStats> {
+        let caches: Vec<Arc<PolicyStoreCache>> =
+            self.caches.read().await.values().cloned().collect();
+        let mut stats = Vec::with_capacity(caches.len());
+
+        for cache in caches {
             stats.push(cache.stats().await);
         }
-        
+
         stats
     }
-    
-    /// Obtiene el número de policy stores en cache
+
+    /// Gets the number of cached policy stores
     pub async fn cache_count(&self) -> usize {
         self.caches.read().await.len()
     }
@@ -335,57 +238,307 @@ impl CacheManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::Repository;
-    
-    async fn create_test_manager() -> CacheManager {
-        let repo = Repository::new(":memory:").await.unwrap();
-        CacheManager::new(Arc::new(repo))
+    use crate::repository::RepositoryAdapter;
+    use hodei_domain::{CedarPolicy, PolicyId};
+
+    async fn create_test_repo() -> Arc<RepositoryAdapter> {
+        Arc::new(RepositoryAdapter::new(":memory:").await.unwrap())
+    }
+
+    async fn create_store(repo: &RepositoryAdapter, name: &str) -> PolicyStoreId {
+        repo.create_policy_store(name.to_string(), None, vec![], "test".to_string())
+            .await
+            .unwrap()
+            .id
     }
-    
+
+    async fn create_policy(repo: &RepositoryAdapter, store: &PolicyStoreId, id: &str) {
+        repo.create_policy(
+            store,
+            &PolicyId::new(id.to_string()).unwrap(),
+            &CedarPolicy::new("permit(principal, action, resource);".to_string()).unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn test_cache_manager_creation() {
-        let manager = create_test_manager().await;
+        let manager = CacheManager::new(create_test_repo().await);
         assert_eq!(manager.cache_count().await, 0);
     }
-    
+
     #[tokio::test]
-    async fn test_create_policy_store() {
-        let manager = create_test_manager().await;
-        
-        let store = manager.create_policy_store(Some("Test".to_string())).await.unwrap();
-        assert!(!store.id.is_empty());
+    async fn test_initialize() {
+        let repo = create_test_repo().await;
+        create_store(&repo, "Store 1").await;
+        create_store(&repo, "Store 2").await;
+
+        let manager = CacheManager::new(repo);
+        manager.initialize().await.unwrap();
+
+        assert_eq!(manager.cache_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_compiled_loads_on_miss() {
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+        create_policy(&repo, &store, "p1").await;
+
+        let manager = CacheManager::new(repo);
+        let compiled = manager.get_compiled(&store).await.unwrap();
+
+        assert_eq!(compiled.policy_count(), 1);
         assert_eq!(manager.cache_count().await, 1);
     }
-    
+
+    #[tokio::test]
+    async fn test_get_compiled_unknown_store() {
+        let manager = CacheManager::new(create_test_repo().await);
+        let missing = PolicyStoreId::new("ps_missing".to_string()).unwrap();
+
+        let result = manager.get_compiled(&missing).await;
+        assert!(matches!(result, Err(AuthorizationError::NotFound(_))));
+        assert_eq!(manager.cache_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_picks_up_changes() {
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+
+        let manager = CacheManager::new(repo.clone());
+        let before = manager.get_compiled(&store).await.unwrap();
+        assert!(before.is_empty());
+
+        create_policy(&repo, &store, "p1").await;
+        manager.refresh(&store).await;
+
+        let after = manager.get_compiled(&store).await.unwrap();
+        assert_eq!(after.policy_count(), 1);
+        // Snapshots handed out earlier are never mutated
+        assert!(before.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_keep_latest_snapshot() {
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+        let manager = Arc::new(CacheManager::new(repo.clone()));
+        manager.reload(&store).await.unwrap();
+
+        let mut refreshes = Vec::new();
+        for i in 0..5 {
+            create_policy(&repo, &store, &format!("p{}", i)).await;
+            let manager = manager.clone();
+            let store = store.clone();
+            refreshes.push(tokio::spawn(async move { manager.refresh(&store).await }));
+        }
+        for refresh in refreshes {
+            refresh.await.unwrap();
+        }
+
+        assert_eq!(
+            manager.get_compiled(&store).await.unwrap().policy_count(),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn test_template_linked_policy_is_evaluated_under_link_id() {
+        use cedar_policy::{Authorizer, Context, Decision, Entities, EntityUid, Request};
+        use hodei_domain::{Principal, Resource, TemplateLink};
+        use std::str::FromStr;
+
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+        repo.create_policy_template(
+            &store,
+            "share".to_string(),
+            r#"permit(principal == ?principal, action, resource == ?resource);"#.to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let link = TemplateLink {
+            policy_template_id: "share".to_string(),
+            principal: Some(Principal::new(r#"User::"alice""#.to_string()).unwrap()),
+            resource: Some(Resource::new(r#"Doc::"d1""#.to_string()).unwrap()),
+        };
+        let rendered = CedarPolicy::new(
+            r#"permit(principal == User::"alice", action, resource == Doc::"d1");"#.to_string(),
+        )
+        .unwrap();
+        repo.create_template_linked_policy(
+            &store,
+            &PolicyId::new("alice-d1".to_string()).unwrap(),
+            &link,
+            &rendered,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let manager = CacheManager::new(repo);
+        let compiled = manager.get_compiled(&store).await.unwrap();
+
+        let request = Request::new(
+            EntityUid::from_str(r#"User::"alice""#).unwrap(),
+            EntityUid::from_str(r#"Action::"view""#).unwrap(),
+            EntityUid::from_str(r#"Doc::"d1""#).unwrap(),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let response =
+            Authorizer::new().is_authorized(&request, compiled.policy_set(), &Entities::empty());
+
+        assert_eq!(response.decision(), Decision::Allow);
+        let reasons: Vec<String> = response.diagnostics().reason().map(|id| id.to_string()).collect();
+        assert_eq!(reasons, vec!["alice-d1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_stored_entities_are_compiled_and_refreshed() {
+        use cedar_policy::{EntityUid, EvalResult};
+        use hodei_domain::{EntityKey, StoredEntity};
+        use std::collections::BTreeMap;
+        use std::str::FromStr;
+
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+
+        let alice = EntityKey::new("User".to_string(), "alice".to_string());
+        let admins = EntityKey::new("Group".to_string(), "admins".to_string());
+        let entity = |level: i64| {
+            StoredEntity::new(
+                store.clone(),
+                alice.clone(),
+                BTreeMap::from([("level".to_string(), serde_json::json!(level))]),
+                vec![admins.clone()],
+            )
+        };
+
+        repo.put_entities(&store, &[entity(1)]).await.unwrap();
+        // Upsert replaces the stored entity
+        repo.put_entities(&store, &[entity(2)]).await.unwrap();
+
+        let stored = repo.get_entities(&store, &[alice.clone(), admins.clone()]).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].attributes["level"], serde_json::json!(2));
+        assert_eq!(stored[0].parents, vec![admins.clone()]);
+
+        let manager = CacheManager::new(repo.clone());
+        let compiled = manager.get_compiled(&store).await.unwrap();
+        let alice_uid = EntityUid::from_str(r#"User::"alice""#).unwrap();
+        let admins_uid = EntityUid::from_str(r#"Group::"admins""#).unwrap();
+        let compiled_alice = compiled.entities().get(&alice_uid).unwrap();
+        assert!(matches!(
+            compiled_alice.attr("level"),
+            Some(Ok(EvalResult::Long(2)))
+        ));
+        let ancestors: Vec<&EntityUid> =
+            compiled.entities().ancestors(&alice_uid).unwrap().collect();
+        assert_eq!(ancestors, vec![&admins_uid]);
+
+        assert_eq!(repo.delete_entities(&store, std::slice::from_ref(&alice)).await.unwrap(), 1);
+        manager.refresh(&store).await;
+        let compiled = manager.get_compiled(&store).await.unwrap();
+        assert!(compiled.entities().iter().next().is_none());
+    }
+
     #[tokio::test]
-    async fn test_create_and_get_policy() {
-        let manager = create_test_manager().await;
-        
-        let store = manager.create_policy_store(None).await.unwrap();
-        
-        let policy = r#"permit(principal, action, resource);"#;
-        manager.create_policy(&store.id, "p1", policy.to_string(), None)
+    async fn test_token_revocations_are_compiled_and_expire() {
+        use chrono::{Duration, Utc};
+        use hodei_domain::{RevokedClaim, TokenRevocation};
+
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+
+        let revocation = |claim, value: &str, expires_in: Option<i64>| {
+            TokenRevocation::new(
+                store.clone(),
+                claim,
+                value.to_string(),
+                expires_in.map(|seconds| Utc::now() + Duration::seconds(seconds)),
+                Some("user disabled".to_string()),
+            )
+        };
+        repo.put_token_revocations(
+            &store,
+            &[
+                revocation(RevokedClaim::Sub, "alice", None),
+                revocation(RevokedClaim::Jti, "token-1", Some(3600)),
+                revocation(RevokedClaim::Sid, "session-1", Some(-60)),
+            ],
+        )
+        .await
+        .unwrap();
+
+        // Expired revocations are neither listed nor compiled
+        let stored = repo.list_token_revocations(&store).await.unwrap();
+        assert_eq!(stored.len(), 2);
+
+        let manager = CacheManager::new(repo.clone());
+        let compiled = manager.get_compiled(&store).await.unwrap();
+        let sub = compiled.revocation(RevokedClaim::Sub, "alice").unwrap();
+        assert_eq!(sub.reason.as_deref(), Some("user disabled"));
+        assert!(sub.expires_at.is_none());
+        assert!(compiled.revocation(RevokedClaim::Jti, "token-1").is_some());
+        assert!(compiled.revocation(RevokedClaim::Sid, "session-1").is_none());
+        // Claims are matched separately
+        assert!(compiled.revocation(RevokedClaim::Jti, "alice").is_none());
+
+        // Re-revoking updates the expiry
+        repo.put_token_revocations(
+            &store,
+            &[revocation(RevokedClaim::Jti, "token-1", Some(-1))],
+        )
+        .await
+        .unwrap();
+        manager.refresh(&store).await;
+        let compiled = manager.get_compiled(&store).await.unwrap();
+        assert!(compiled.revocation(RevokedClaim::Jti, "token-1").is_none());
+
+        let keys = [(RevokedClaim::Sub, "alice".to_string())];
+        let deleted = repo.delete_token_revocations(&store, &keys).await.unwrap();
+        assert_eq!(deleted, 1);
+        manager.refresh(&store).await;
+        let compiled = manager.get_compiled(&store).await.unwrap();
+        assert!(compiled.revocation(RevokedClaim::Sub, "alice").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_strict_validation_setting_is_compiled() {
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+
+        let manager = CacheManager::new(repo.clone());
+        assert!(!manager.get_compiled(&store).await.unwrap().strict_validation());
+
+        let updated = repo
+            .update_policy_store(&store, None, None, None, Some(true), None)
             .await
             .unwrap();
-        
-        let cache = manager.get_cache(&store.id).await.unwrap();
-        let stats = cache.stats().await;
-        assert_eq!(stats.policy_count, 1);
+        assert!(updated.strict_validation);
+        manager.refresh(&store).await;
+
+        assert!(manager.get_compiled(&store).await.unwrap().strict_validation());
     }
-    
+
     #[tokio::test]
-    async fn test_initialize() {
-        let repo = Repository::new(":memory:").await.unwrap();
-        let repo_arc = Arc::new(repo);
-        
-        // Crear algunos stores directamente en el repo
-        repo_arc.create_policy_store(Some("Store 1".to_string())).await.unwrap();
-        repo_arc.create_policy_store(Some("Store 2".to_string())).await.unwrap();
-        
-        // Crear manager e inicializar
-        let manager = CacheManager::new(repo_arc);
-        manager.initialize().await.unwrap();
-        
-        assert_eq!(manager.cache_count().await, 2);
+    async fn test_invalidate() {
+        let repo = create_test_repo().await;
+        let store = create_store(&repo, "Store").await;
+
+        let manager = CacheManager::new(repo);
+        manager.reload(&store).await.unwrap();
+        assert_eq!(manager.cache_count().await, 1);
+
+        manager.invalidate(&store).await;
+        assert_eq!(manager.cache_count().await, 0);
     }
 }
@@ -1,12 +1,12 @@
 //! Cache layer for in-memory policy evaluation
-//! 
-//! Este módulo implementa el sistema de cache que mantiene los PolicySets
-//! de Cedar en memoria para evaluación ultra-rápida (~100μs).
+//!
+//! Keeps a compiled Cedar PolicySet and Schema per policy store so the data
+//! plane never has to hit the database or re-parse policies per request.
 
 pub mod policy_store_cache;
 pub mod cache_manager;
 pub mod reload_task;
 
-pub use policy_store_cache::PolicyStoreCache;
+pub use policy_store_cache::{CacheStats, CompiledPolicyStore, PolicyStoreCache};
 pub use cache_manager::CacheManager;
 pub use reload_task::{ReloadTask, ReloadConfig};
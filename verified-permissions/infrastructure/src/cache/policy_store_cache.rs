@@ -1,289 +1,612 @@
-//! Policy Store Cache - Mantiene PolicySet y Schema en memoria
+//! Policy Store Cache - Keeps a compiled PolicySet and Schema in memory
 
-use cedar_policy::{PolicySet, Schema, Policy};
+use crate::error::{AuthorizationError, Result};
+use crate::jwt::{AttributeType, schema_attribute_types};
+use cedar_policy::{
+    Entities, EntityUid, Policy, PolicyId as CedarPolicyId, PolicySet, Schema, SchemaFragment,
+    SlotId, Template,
+};
 use chrono::{DateTime, Utc};
-use std::sync::Arc;
+use hodei_domain::{
+    EntityKey, PolicyRepository, PolicyStoreId, PolicyTemplate, RevokedClaim, StoredEntity,
+    TemplateLink, TokenRevocation,
+};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio::sync::RwLock;
-use crate::error::Result;
-use crate::storage::PolicyRepository;
-
-/// Cache en memoria para un Policy Store específico
-/// 
-/// Mantiene:
-/// - PolicySet de Cedar (todas las políticas compiladas)
-/// - Schema de Cedar (para validación)
-/// - Timestamp de última actualización
-/// 
-/// # Concurrencia
-/// 
-/// Usa RwLock para permitir:
-/// - Múltiples lecturas concurrentes (evaluación de políticas)
-/// - Escrituras exclusivas (actualización de políticas)
+
+/// Immutable, fully compiled view of a policy store
+///
+/// Evaluation paths clone the `Arc` and work on it without holding any lock,
+/// so a concurrent reload never blocks or mutates a request in flight.
+#[derive(Debug, Clone)]
+pub struct CompiledPolicyStore {
+    policy_set: PolicySet,
+    schema: Option<Schema>,
+    /// Attribute types declared in the schema, by entity type
+    attribute_types: HashMap<String, HashMap<String, AttributeType>>,
+    /// Stored entities, parsed once per snapshot
+    entities: Entities,
+    /// Token denylist, by revoked claim and value
+    revocations: HashMap<(RevokedClaim, String), TokenRevocation>,
+    strict_validation: bool,
+    loaded_at: DateTime<Utc>,
+}
+
+impl CompiledPolicyStore {
+    /// Creates an empty compiled store (no policies, no schema)
+    pub fn empty() -> Self {
+        Self {
+            policy_set: PolicySet::new(),
+            schema: None,
+            attribute_types: HashMap::new(),
+            entities: Entities::empty(),
+            revocations: HashMap::new(),
+            strict_validation: false,
+            loaded_at: Utc::now(),
+        }
+    }
+
+    /// The compiled Cedar PolicySet
+    pub fn policy_set(&self) -> &PolicySet {
+        &self.policy_set
+    }
+
+    /// The parsed Cedar Schema, if the store has one
+    pub fn schema(&self) -> Option<&Schema> {
+        self.schema.as_ref()
+    }
+
+    /// Attribute types the schema declares for an entity type, used to
+    /// convert token claims mapped to attributes
+    pub fn attribute_types(&self, entity_type: &str) -> Option<&HashMap<String, AttributeType>> {
+        self.attribute_types.get(entity_type)
+    }
+
+    /// Stored entities of the store
+    ///
+    /// Parsed against the schema when the store has strict validation on.
+    /// Requests lay their own entities over these.
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    /// The unexpired revocation of tokens whose `claim` has `value`, if any
+    pub fn revocation(&self, claim: RevokedClaim, value: &str) -> Option<&TokenRevocation> {
+        self.revocations
+            .get(&(claim, value.to_string()))
+            .filter(|revocation| !revocation.is_expired_at(Utc::now()))
+    }
+
+    /// Whether requests must be validated against the schema
+    pub fn strict_validation(&self) -> bool {
+        self.strict_validation
+    }
+
+    /// When this snapshot was built
+    pub fn loaded_at(&self) -> DateTime<Utc> {
+        self.loaded_at
+    }
+
+    /// Number of policies in the set
+    pub fn policy_count(&self) -> usize {
+        self.policy_set.policies().count()
+    }
+
+    /// Returns true when the store has no policies
+    pub fn is_empty(&self) -> bool {
+        self.policy_set.is_empty()
+    }
+}
+
+/// In-memory cache for a single Policy Store
+///
+/// Holds the current [`CompiledPolicyStore`] behind an `Arc`. Every update
+/// builds a complete new snapshot and swaps the pointer, so readers always see
+/// either the previous or the next version of the store, never a mix.
 pub struct PolicyStoreCache {
-    /// ID del Policy Store
+    /// Policy Store ID
     pub policy_store_id: String,
-    
-    /// PolicySet de Cedar en memoria
-    /// RwLock permite muchas lecturas concurrentes
-    policy_set: Arc<RwLock<PolicySet>>,
-    
-    /// Schema de Cedar (opcional)
-    schema: Arc<RwLock<Option<Schema>>>,
-    
-    /// Timestamp de última actualización
-    last_updated: Arc<RwLock<DateTime<Utc>>>,
+
+    /// Current compiled snapshot
+    current: RwLock<Arc<CompiledPolicyStore>>,
 }
 
 impl PolicyStoreCache {
-    /// Crea un nuevo cache vacío
+    /// Creates a new empty cache
     pub fn new(policy_store_id: String) -> Self {
         Self {
             policy_store_id,
-            policy_set: Arc::new(RwLock::new(PolicySet::new())),
-            schema: Arc::new(RwLock::new(None)),
-            last_updated: Arc::new(RwLock::new(Utc::now())),
+            current: RwLock::new(Arc::new(CompiledPolicyStore::empty())),
         }
     }
-    
-    /// Carga el cache desde la base de datos
-    /// 
-    /// # Arguments
-    /// * `repo` - Repository para cargar datos
-    /// 
-    /// # Errors
-    /// Retorna error si:
-    /// - No se puede cargar el schema
-    /// - No se pueden cargar las políticas
-    /// - Las políticas tienen sintaxis inválida
+
+    /// Compiles the store from the repository and swaps it in
+    ///
+    /// If any stored policy or the schema fails to compile, the previous
+    /// snapshot is kept and the error is returned.
     pub async fn load_from_db(&self, repo: &dyn PolicyRepository) -> Result<()> {
         tracing::info!("Loading cache for policy store: {}", self.policy_store_id);
-        
-        // 1. Cargar schema (opcional)
-        match repo.get_schema(&self.policy_store_id).await {
-            Ok(schema_model) => {
-                match Schema::from_json_str(&schema_model.schema_json) {
-                    Ok(cedar_schema) => {
-                        *self.schema.write().await = Some(cedar_schema);
-                        tracing::debug!("Schema loaded for store {}", self.policy_store_id);
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to parse schema for store {}: {}", 
-                                     self.policy_store_id, e);
-                    }
-                }
-            }
-            Err(_) => {
-                tracing::debug!("No schema found for store {}", self.policy_store_id);
+
+        let compiled = Self::compile(&self.policy_store_id, repo).await?;
+        let policy_count = compiled.policy_count();
+        let has_schema = compiled.schema.is_some();
+
+        self.replace(compiled).await;
+
+        tracing::info!(
+            "Cache loaded for store {}: {} policies, schema: {}",
+            self.policy_store_id,
+            policy_count,
+            has_schema
+        );
+
+        Ok(())
+    }
+
+    /// Builds a compiled snapshot of a store without touching any cache
+    pub async fn compile(
+        policy_store_id: &str,
+        repo: &dyn PolicyRepository,
+    ) -> Result<CompiledPolicyStore> {
+        let store_id = PolicyStoreId::new(policy_store_id.to_string())?;
+        let store = repo.get_policy_store(&store_id).await?;
+
+        // 1. Schema (optional)
+        let (schema, attribute_types) = match repo.get_schema(&store_id).await? {
+            Some(schema_model) => (
+                Some(parse_schema(&schema_model.schema_json).map_err(|e| {
+                    AuthorizationError::InvalidArgument(format!(
+                        "Invalid schema for store {}: {}",
+                        policy_store_id, e
+                    ))
+                })?),
+                schema_types(&schema_model.schema_json),
+            ),
+            None => (None, HashMap::new()),
+        };
+
+        // 2. Static policies, keyed by their stored IDs
+        let mut policy_set = PolicySet::new();
+        let mut links = Vec::new();
+        for policy in repo.list_policies(&store_id).await? {
+            if let Some(link) = policy.template_link {
+                links.push((policy.policy_id, link));
+                continue;
             }
+            let policy_id = policy.policy_id.as_str();
+            let cedar_policy = parse_policy(policy_id, policy.statement.as_str())?;
+            policy_set.add(cedar_policy).map_err(|e| {
+                AuthorizationError::Internal(format!(
+                    "Failed to add policy {} to set: {}",
+                    policy_id, e
+                ))
+            })?;
         }
-        
-        // 2. Cargar todas las políticas
-        let policies = repo.list_policies(&self.policy_store_id).await?;
-        let mut policy_set = PolicySet::new();
-        let mut loaded_count = 0;
-        let mut error_count = 0;
-        
-        for policy_model in policies {
-            match Policy::from_str(&policy_model.statement) {
-                Ok(cedar_policy) => {
-                    if let Err(e) = policy_set.add(cedar_policy) {
-                        tracing::error!("Failed to add policy {} to set: {}", 
-                                      policy_model.policy_id, e);
-                        error_count += 1;
-                    } else {
-                        loaded_count += 1;
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Failed to parse policy {}: {}", 
-                                  policy_model.policy_id, e);
-                    error_count += 1;
+
+        // 3. Template-linked policies, instantiated through Cedar so they are
+        //    evaluated (and reported) under their own link IDs
+        if !links.is_empty() {
+            for template in repo.list_policy_templates(&store_id).await? {
+                if links
+                    .iter()
+                    .any(|(_, link)| link.policy_template_id == template.template_id)
+                {
+                    add_template(&mut policy_set, &template)?;
                 }
             }
+            for (policy_id, link) in &links {
+                link_policy(&mut policy_set, policy_id.as_str(), link)?;
+            }
         }
-        
-        // 3. Actualizar cache
-        *self.policy_set.write().await = policy_set;
-        *self.last_updated.write().await = Utc::now();
-        
-        tracing::info!(
-            "Cache loaded for store {}: {} policies loaded, {} errors",
-            self.policy_store_id, loaded_count, error_count
-        );
-        
-        Ok(())
+
+        // 4. Stored entities, parsed once and overlaid with each request's
+        //    entities at evaluation
+        let entities_json = repo
+            .list_entities(&store_id)
+            .await?
+            .iter()
+            .map(entity_json)
+            .collect();
+        let entity_schema = schema.as_ref().filter(|_| store.strict_validation);
+        let entities =
+            Entities::from_json_value(serde_json::Value::Array(entities_json), entity_schema)
+                .map_err(|e| {
+                    AuthorizationError::InvalidArgument(format!(
+                        "Invalid stored entities for store {}: {}",
+                        policy_store_id, e
+                    ))
+                })?;
+
+        // 5. Token denylist, checked on every token authorization
+        let revocations = repo
+            .list_token_revocations(&store_id)
+            .await?
+            .into_iter()
+            .map(|revocation| ((revocation.claim, revocation.value.clone()), revocation))
+            .collect();
+
+        Ok(CompiledPolicyStore {
+            policy_set,
+            schema,
+            attribute_types,
+            entities,
+            revocations,
+            strict_validation: store.strict_validation,
+            loaded_at: Utc::now(),
+        })
+    }
+
+    /// Returns the current snapshot
+    pub async fn snapshot(&self) -> Arc<CompiledPolicyStore> {
+        self.current.read().await.clone()
+    }
+
+    /// Atomically replaces the current snapshot
+    pub async fn replace(&self, compiled: CompiledPolicyStore) {
+        *self.current.write().await = Arc::new(compiled);
     }
-    
-    /// Obtiene una referencia al PolicySet (para lectura)
-    /// 
-    /// # Returns
-    /// Arc<RwLock<PolicySet>> que puede ser compartido entre threads
-    pub fn policy_set(&self) -> Arc<RwLock<PolicySet>> {
-        self.policy_set.clone()
-    }
-    
-    /// Obtiene una referencia al Schema (para lectura)
-    pub fn schema(&self) -> Arc<RwLock<Option<Schema>>> {
-        self.schema.clone()
-    }
-    
-    /// Obtiene el timestamp de última actualización
+
+    /// Gets the timestamp of the last update
     pub async fn last_updated(&self) -> DateTime<Utc> {
-        *self.last_updated.read().await
-    }
-    
-    /// Agrega una política al cache
-    /// 
-    /// # Arguments
-    /// * `policy_id` - ID de la política
-    /// * `statement` - Statement de Cedar
-    /// 
+        self.snapshot().await.loaded_at
+    }
+
+    /// Adds a policy to the cache
+    ///
     /// # Errors
-    /// Retorna error si la política tiene sintaxis inválida
+    /// Returns an error if the policy has invalid syntax or its ID is taken
     pub async fn add_policy(&self, policy_id: &str, statement: &str) -> Result<()> {
-        let policy = Policy::from_str(statement)?;
-        
-        let mut policy_set = self.policy_set.write().await;
-        policy_set.add(policy)?;
-        
-        *self.last_updated.write().await = Utc::now();
-        
-        tracing::debug!("Policy {} added to cache for store {}", 
-                       policy_id, self.policy_store_id);
-        
+        let policy = parse_policy(policy_id, statement)?;
+
+        let mut current = self.current.write().await;
+        let mut next = (**current).clone();
+        next.policy_set
+            .add(policy)
+            .map_err(|e| AuthorizationError::InvalidArgument(e.to_string()))?;
+        next.loaded_at = Utc::now();
+        *current = Arc::new(next);
+
+        tracing::debug!(
+            "Policy {} added to cache for store {}",
+            policy_id,
+            self.policy_store_id
+        );
+
         Ok(())
     }
-    
-    /// Elimina una política del cache
-    /// 
-    /// # Arguments
-    /// * `policy_id` - ID de la política a eliminar
+
+    /// Removes a policy (static or template-linked) from the cache
     pub async fn remove_policy(&self, policy_id: &str) -> Result<()> {
-        let mut policy_set = self.policy_set.write().await;
-        
-        // Cedar PolicySet no tiene método remove directo
-        // Necesitamos reconstruir el set sin la política eliminada
-        let policies: Vec<Policy> = policy_set
-            .policies()
-            .filter(|p| {
-                // Comparar el ID de la política con el que queremos eliminar
-                // p.id() devuelve &PolicyId, no Option
-                p.id().to_string() != policy_id
-            })
-            .cloned()
-            .collect();
-        
-        let mut new_set = PolicySet::new();
-        for policy in policies {
-            new_set.add(policy)?;
-        }
-        
-        *policy_set = new_set;
-        *self.last_updated.write().await = Utc::now();
-        
-        tracing::debug!("Policy {} removed from cache for store {}", 
-                       policy_id, self.policy_store_id);
-        
+        let mut current = self.current.write().await;
+        let mut next = (**current).clone();
+
+        let id = CedarPolicyId::new(policy_id);
+        let removed = match next.policy_set.policy(&id).map(|p| p.is_static()) {
+            Some(true) => next.policy_set.remove_static(id).map(|_| ()),
+            Some(false) => next.policy_set.unlink(id).map(|_| ()),
+            None => Ok(()),
+        };
+        removed.map_err(|e| AuthorizationError::Internal(e.to_string()))?;
+
+        next.loaded_at = Utc::now();
+        *current = Arc::new(next);
+
+        tracing::debug!(
+            "Policy {} removed from cache for store {}",
+            policy_id,
+            self.policy_store_id
+        );
+
         Ok(())
     }
-    
-    /// Actualiza el schema en el cache
-    /// 
-    /// # Arguments
-    /// * `schema_json` - Schema en formato JSON
-    /// 
+
+    /// Updates the schema in the cache
+    ///
     /// # Errors
-    /// Retorna error si el schema es inválido
-    pub async fn update_schema(&self, schema_json: &str) -> Result<()> {
-        let cedar_schema = Schema::from_json_str(schema_json)?;
-        
-        *self.schema.write().await = Some(cedar_schema);
-        *self.last_updated.write().await = Utc::now();
-        
+    /// Returns an error if the schema is invalid
+    pub async fn update_schema(&self, schema_src: &str) -> Result<()> {
+        let schema = parse_schema(schema_src)
+            .map_err(|e| AuthorizationError::InvalidArgument(format!("Invalid schema: {}", e)))?;
+
+        let mut current = self.current.write().await;
+        *current = Arc::new(CompiledPolicyStore {
+            policy_set: current.policy_set.clone(),
+            schema: Some(schema),
+            attribute_types: schema_types(schema_src),
+            entities: current.entities.clone(),
+            revocations: current.revocations.clone(),
+            strict_validation: current.strict_validation,
+            loaded_at: Utc::now(),
+        });
+
         tracing::debug!("Schema updated in cache for store {}", self.policy_store_id);
-        
+
         Ok(())
     }
-    
-    /// Elimina el schema del cache
+
+    /// Removes the schema from the cache
     pub async fn remove_schema(&self) {
-        *self.schema.write().await = None;
-        *self.last_updated.write().await = Utc::now();
-        
+        let mut current = self.current.write().await;
+        *current = Arc::new(CompiledPolicyStore {
+            policy_set: current.policy_set.clone(),
+            schema: None,
+            attribute_types: HashMap::new(),
+            entities: current.entities.clone(),
+            revocations: current.revocations.clone(),
+            strict_validation: current.strict_validation,
+            loaded_at: Utc::now(),
+        });
+
         tracing::debug!("Schema removed from cache for store {}", self.policy_store_id);
     }
-    
-    /// Obtiene estadísticas del cache
+
+    /// Gets cache statistics
     pub async fn stats(&self) -> CacheStats {
-        let policy_set = self.policy_set.read().await;
-        let schema = self.schema.read().await;
-        let last_updated = *self.last_updated.read().await;
-        
+        let snapshot = self.snapshot().await;
+
         CacheStats {
             policy_store_id: self.policy_store_id.clone(),
-            policy_count: policy_set.policies().count(),
-            has_schema: schema.is_some(),
-            last_updated,
+            policy_count: snapshot.policy_count(),
+            has_schema: snapshot.schema.is_some(),
+            entity_count: snapshot.entities.iter().count(),
+            revocation_count: snapshot.revocations.len(),
+            last_updated: snapshot.loaded_at,
         }
     }
 }
 
-/// Estadísticas del cache
+/// Cache statistics
 #[derive(Debug, Clone)]
 pub struct CacheStats {
     pub policy_store_id: String,
     pub policy_count: usize,
     pub has_schema: bool,
+    pub entity_count: usize,
+    pub revocation_count: usize,
     pub last_updated: DateTime<Utc>,
 }
 
+/// Parses a policy and assigns it the stored policy ID
+fn parse_policy(policy_id: &str, statement: &str) -> Result<Policy> {
+    Policy::parse(Some(CedarPolicyId::new(policy_id)), statement).map_err(|e| {
+        AuthorizationError::InvalidArgument(format!("Invalid policy {}: {}", policy_id, e))
+    })
+}
+
+/// Adds a stored policy template to a policy set under its template ID
+pub fn add_template(policy_set: &mut PolicySet, template: &PolicyTemplate) -> Result<()> {
+    let cedar_template = Template::parse(
+        Some(CedarPolicyId::new(&template.template_id)),
+        &template.statement,
+    )
+    .map_err(|e| {
+        AuthorizationError::InvalidArgument(format!(
+            "Invalid policy template {}: {}",
+            template.template_id, e
+        ))
+    })?;
+
+    policy_set.add_template(cedar_template).map_err(|e| {
+        AuthorizationError::Internal(format!(
+            "Failed to add template {} to set: {}",
+            template.template_id, e
+        ))
+    })
+}
+
+/// Links a template already in `policy_set` under the given policy ID
+///
+/// Fails if a slot used by the template is not bound by the link, or the link
+/// binds a slot the template does not have.
+pub fn link_policy(policy_set: &mut PolicySet, policy_id: &str, link: &TemplateLink) -> Result<()> {
+    let mut values = HashMap::new();
+    if let Some(principal) = &link.principal {
+        values.insert(SlotId::principal(), parse_entity_uid(principal.as_str())?);
+    }
+    if let Some(resource) = &link.resource {
+        values.insert(SlotId::resource(), parse_entity_uid(resource.as_str())?);
+    }
+
+    policy_set
+        .link(
+            CedarPolicyId::new(&link.policy_template_id),
+            CedarPolicyId::new(policy_id),
+            values,
+        )
+        .map_err(|e| {
+            AuthorizationError::InvalidArgument(format!(
+                "Failed to link policy {} to template {}: {}",
+                policy_id, link.policy_template_id, e
+            ))
+        })
+}
+
+/// Converts a stored entity to Cedar's entity JSON format
+pub fn entity_json(entity: &StoredEntity) -> serde_json::Value {
+    serde_json::json!({
+        "uid": entity_uid_json(&entity.key),
+        "attrs": entity.attributes,
+        "parents": entity.parents.iter().map(entity_uid_json).collect::<Vec<_>>(),
+    })
+}
+
+/// Converts an entity key to Cedar's `{"type", "id"}` UID JSON
+pub fn entity_uid_json(key: &EntityKey) -> serde_json::Value {
+    serde_json::json!({ "type": key.entity_type, "id": key.entity_id })
+}
+
+fn parse_entity_uid(uid: &str) -> Result<EntityUid> {
+    EntityUid::from_str(uid).map_err(|e| {
+        AuthorizationError::InvalidArgument(format!("Invalid entity UID {}: {}", uid, e))
+    })
+}
+
+/// Parses a schema in either JSON or Cedar schema syntax
+fn parse_schema(schema_src: &str) -> std::result::Result<Schema, String> {
+    if schema_src.trim_start().starts_with('{') {
+        Schema::from_json_str(schema_src).map_err(|e| e.to_string())
+    } else {
+        Schema::from_str(schema_src).map_err(|e| e.to_string())
+    }
+}
+
+/// Attribute types of a schema in either syntax, by entity type
+fn schema_types(schema_src: &str) -> HashMap<String, HashMap<String, AttributeType>> {
+    let schema_json = if schema_src.trim_start().starts_with('{') {
+        serde_json::from_str(schema_src).ok()
+    } else {
+        SchemaFragment::from_cedarschema_str(schema_src)
+            .ok()
+            .and_then(|(fragment, _)| fragment.to_json_value().ok())
+    };
+    schema_json
+        .map(|schema_json| schema_attribute_types(&schema_json))
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[tokio::test]
     async fn test_cache_creation() {
         let cache = PolicyStoreCache::new("test-store".to_string());
         assert_eq!(cache.policy_store_id, "test-store");
-        
+
         let stats = cache.stats().await;
         assert_eq!(stats.policy_count, 0);
         assert!(!stats.has_schema);
     }
-    
+
     #[tokio::test]
     async fn test_add_policy() {
         let cache = PolicyStoreCache::new("test-store".to_string());
-        
+
         let policy = r#"permit(principal, action, resource);"#;
         cache.add_policy("policy-1", policy).await.unwrap();
-        
+
         let stats = cache.stats().await;
         assert_eq!(stats.policy_count, 1);
     }
-    
+
     #[tokio::test]
-    #[ignore] // TODO: Cedar from_str no asigna ID automáticamente
     async fn test_remove_policy() {
         let cache = PolicyStoreCache::new("test-store".to_string());
-        
+
         let policy = r#"permit(principal, action, resource);"#;
         cache.add_policy("policy-1", policy).await.unwrap();
         cache.remove_policy("policy-1").await.unwrap();
-        
+
         let stats = cache.stats().await;
         assert_eq!(stats.policy_count, 0);
     }
-    
+
     #[tokio::test]
     async fn test_update_schema() {
         let cache = PolicyStoreCache::new("test-store".to_string());
-        
+
         let schema = r#"{"": {"entityTypes": {}, "actions": {}}}"#;
         cache.update_schema(schema).await.unwrap();
-        
+
         let stats = cache.stats().await;
         assert!(stats.has_schema);
     }
+
+    #[tokio::test]
+    async fn test_schema_attribute_types() {
+        let cache = PolicyStoreCache::new("test-store".to_string());
+
+        let schema = r#"
+            namespace MyApp {
+                entity User { level: Long, groups: Set<String>, ip: ipaddr };
+                action view appliesTo { principal: User, resource: User };
+            }
+        "#;
+        cache.update_schema(schema).await.unwrap();
+
+        let snapshot = cache.snapshot().await;
+        let types = snapshot.attribute_types("MyApp::User").unwrap();
+        assert_eq!(types["level"], AttributeType::Long);
+        assert_eq!(types["groups"].to_string(), "Set<String>");
+        assert_eq!(types["ip"], AttributeType::Ipaddr);
+
+        cache.remove_schema().await;
+        let snapshot = cache.snapshot().await;
+        assert!(snapshot.attribute_types("MyApp::User").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_policies_keep_stored_ids() {
+        let cache = PolicyStoreCache::new("test-store".to_string());
+        cache
+            .add_policy("allow-all", "permit(principal, action, resource);")
+            .await
+            .unwrap();
+
+        let snapshot = cache.snapshot().await;
+        let ids: Vec<String> = snapshot
+            .policy_set()
+            .policies()
+            .map(|p| p.id().to_string())
+            .collect();
+        assert_eq!(ids, vec!["allow-all".to_string()]);
+    }
+
+    #[test]
+    fn test_link_policy_uses_link_id() {
+        let template = PolicyTemplate::new(
+            "share".to_string(),
+            PolicyStoreId::new("test-store".to_string()).unwrap(),
+            r#"permit(principal == ?principal, action, resource == ?resource);"#.to_string(),
+            None,
+        );
+        let link = TemplateLink {
+            policy_template_id: "share".to_string(),
+            principal: Some(hodei_domain::Principal::new(r#"User::"alice""#.to_string()).unwrap()),
+            resource: Some(hodei_domain::Resource::new(r#"Doc::"d1""#.to_string()).unwrap()),
+        };
+
+        let mut policy_set = PolicySet::new();
+        add_template(&mut policy_set, &template).unwrap();
+        link_policy(&mut policy_set, "alice-d1", &link).unwrap();
+
+        let linked = policy_set.policy(&CedarPolicyId::new("alice-d1")).unwrap();
+        assert!(!linked.is_static());
+        assert_eq!(linked.template_id(), Some(&CedarPolicyId::new("share")));
+    }
+
+    #[test]
+    fn test_link_policy_requires_all_slots() {
+        let template = PolicyTemplate::new(
+            "share".to_string(),
+            PolicyStoreId::new("test-store".to_string()).unwrap(),
+            r#"permit(principal == ?principal, action, resource == ?resource);"#.to_string(),
+            None,
+        );
+        let link = TemplateLink {
+            policy_template_id: "share".to_string(),
+            principal: Some(hodei_domain::Principal::new(r#"User::"alice""#.to_string()).unwrap()),
+            resource: None,
+        };
+
+        let mut policy_set = PolicySet::new();
+        add_template(&mut policy_set, &template).unwrap();
+        assert!(link_policy(&mut policy_set, "alice", &link).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_is_unaffected_by_later_updates() {
+        let cache = PolicyStoreCache::new("test-store".to_string());
+        let before = cache.snapshot().await;
+
+        cache
+            .add_policy("p1", "permit(principal, action, resource);")
+            .await
+            .unwrap();
+
+        assert!(before.is_empty());
+        assert_eq!(cache.snapshot().await.policy_count(), 1);
+    }
 }
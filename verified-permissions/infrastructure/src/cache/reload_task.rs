@@ -91,19 +91,30 @@ impl ReloadTask {
 impl CacheManager {
     /// Reload all caches from the database
     ///
-    /// Returns the number of policy stores reloaded
+    /// Picks up stores created by other processes and drops caches of stores
+    /// that no longer exist. Returns the number of policy stores reloaded.
     pub async fn reload_all(&self) -> crate::error::Result<usize> {
         info!("Reloading all policy store caches...");
-        
-        // Simply reinitialize all caches
-        self.initialize().await?;
-        
-        // Count the number of stores
+
         let stores = self.repository.list_policy_stores().await?;
-        let count = stores.len();
-        
+        let mut count = 0;
+
+        for store in &stores {
+            match self.reload(&store.id).await {
+                Ok(_) => count += 1,
+                Err(e) => warn!("Failed to reload cache for store {}: {}", store.id, e),
+            }
+        }
+
+        for stat in self.stats().await {
+            if !stores.iter().any(|s| s.id.as_str() == stat.policy_store_id) {
+                let stale = hodei_domain::PolicyStoreId::new(stat.policy_store_id)?;
+                self.invalidate(&stale).await;
+            }
+        }
+
         info!("Reloaded {} policy stores", count);
-        
+
         Ok(count)
     }
 }
@@ -111,7 +122,8 @@ impl CacheManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::Repository;
+    use crate::repository::RepositoryAdapter;
+    use hodei_domain::PolicyRepository;
     use std::sync::Arc;
 
     #[tokio::test]
@@ -123,7 +135,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_reload_task_creation() {
-        let repo = Repository::new(":memory:").await.unwrap();
+        let repo = RepositoryAdapter::new(":memory:").await.unwrap();
         let cache_manager = Arc::new(CacheManager::new(Arc::new(repo)));
         
         let config = ReloadConfig {
@@ -137,11 +149,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_reload_all() {
-        let repo = Arc::new(Repository::new(":memory:").await.unwrap());
+        let repo = Arc::new(RepositoryAdapter::new(":memory:").await.unwrap());
         
         // Create some policy stores
-        repo.create_policy_store(Some("Store 1".to_string())).await.unwrap();
-        repo.create_policy_store(Some("Store 2".to_string())).await.unwrap();
+        repo.create_policy_store("Store 1".to_string(), None, vec![], "test".to_string())
+            .await
+            .unwrap();
+        repo.create_policy_store("Store 2".to_string(), None, vec![], "test".to_string())
+            .await
+            .unwrap();
         
         let cache_manager = CacheManager::new(repo);
         cache_manager.initialize().await.unwrap();
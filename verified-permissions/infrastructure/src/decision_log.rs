@@ -0,0 +1,273 @@
+//! Decision log writer
+//!
+//! Records data-plane authorization decisions in the `authorization_logs`
+//! table without putting the database on the request path: decisions are
+//! queued on a bounded channel and written in batches by a background task.
+//! When the queue is full new decisions are dropped and counted.
+//!
+//! Every `IsAuthorized` style request against an existing policy store is
+//! recorded, including requests that fail evaluation and requests whose
+//! token is rejected; both are logged as denies carrying the error, the
+//! latter without a principal. Requests naming an unknown policy store,
+//! `IsAuthorizedPartial` residuals and `ListAllowedActions` listings are not
+//! decisions and are not recorded.
+
+use hodei_domain::{DecisionLog, PolicyRepository};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
+use tracing::{error, info, warn};
+
+/// Configuration for the decision log writer
+#[derive(Debug, Clone)]
+pub struct DecisionLogConfig {
+    /// Maximum number of decisions waiting to be written
+    pub capacity: usize,
+
+    /// Maximum number of decisions written in one batch
+    pub batch_size: usize,
+
+    /// How long a partial batch may wait before it is written
+    pub flush_interval: Duration,
+}
+
+impl Default for DecisionLogConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            batch_size: 256,
+            flush_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Handle used by the data plane to record decisions
+///
+/// Cloning is cheap; the background task stops once every handle is dropped
+/// and the queued decisions have been written.
+#[derive(Clone)]
+pub struct DecisionLogWriter {
+    sender: mpsc::Sender<DecisionLog>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl DecisionLogWriter {
+    /// Starts the background writer
+    ///
+    /// Returns the handle and the writer task, which finishes after the last
+    /// handle is dropped and the queue is flushed.
+    pub fn spawn(
+        repository: Arc<dyn PolicyRepository>,
+        config: DecisionLogConfig,
+    ) -> (Self, tokio::task::JoinHandle<()>) {
+        let (sender, receiver) = mpsc::channel(config.capacity.max(1));
+        let handle = tokio::spawn(Self::run(repository, config, receiver));
+
+        (
+            Self {
+                sender,
+                dropped: Arc::new(AtomicU64::new(0)),
+            },
+            handle,
+        )
+    }
+
+    /// Queues a decision, dropping it if the queue is full
+    pub fn record(&self, log: DecisionLog) {
+        if self.sender.try_send(log).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(
+                    "Decision log queue is full, {} decisions dropped so far",
+                    dropped
+                );
+            }
+        }
+    }
+
+    /// Number of decisions dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    async fn run(
+        repository: Arc<dyn PolicyRepository>,
+        config: DecisionLogConfig,
+        mut receiver: mpsc::Receiver<DecisionLog>,
+    ) {
+        info!(
+            "Starting decision log writer (batch size: {}, flush interval: {:?})",
+            config.batch_size, config.flush_interval
+        );
+
+        let batch_size = config.batch_size.max(1);
+        let mut batch = Vec::with_capacity(batch_size);
+
+        // Wait for a first decision, then fill the batch until it is full or
+        // the flush interval has passed
+        while let Some(log) = receiver.recv().await {
+            batch.push(log);
+            let deadline = Instant::now() + config.flush_interval;
+            let mut closed = false;
+
+            while batch.len() < batch_size {
+                match time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(log)) => batch.push(log),
+                    Ok(None) => {
+                        closed = true;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            if let Err(e) = repository.record_decisions(&batch).await {
+                error!("Failed to write {} decision logs: {}", batch.len(), e);
+            }
+            batch.clear();
+
+            if closed {
+                break;
+            }
+        }
+
+        info!("Decision log writer stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::RepositoryAdapter;
+    use chrono::{Duration as ChronoDuration, Utc};
+    use hodei_domain::{DecisionLogFilter, PolicyStoreId};
+    use std::collections::BTreeMap;
+
+    async fn create_store(repo: &RepositoryAdapter) -> PolicyStoreId {
+        repo.create_policy_store("Store".to_string(), None, vec![], "test".to_string())
+            .await
+            .unwrap()
+            .id
+    }
+
+    fn decision(store: &PolicyStoreId, principal: &str, decision: &str) -> DecisionLog {
+        DecisionLog {
+            id: None,
+            policy_store_id: store.clone(),
+            principal: format!("User::\"{}\"", principal),
+            action: "Action::\"view\"".to_string(),
+            resource: "Document::\"doc1\"".to_string(),
+            decision: decision.to_string(),
+            determining_policies: vec!["policy1".to_string()],
+            errors: vec![],
+            latency_micros: 42,
+            caller: BTreeMap::from([("user-agent".to_string(), "test".to_string())]),
+            correlation_id: Some(format!("{}-1", principal)),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decisions_are_written_in_batches_and_queryable() {
+        let repo = Arc::new(RepositoryAdapter::new(":memory:").await.unwrap());
+        let store = create_store(&repo).await;
+
+        let config = DecisionLogConfig {
+            capacity: 16,
+            batch_size: 2,
+            flush_interval: Duration::from_millis(10),
+        };
+        let (writer, handle) = DecisionLogWriter::spawn(repo.clone(), config);
+        writer.record(decision(&store, "alice", "ALLOW"));
+        writer.record(decision(&store, "bob", "DENY"));
+        writer.record(decision(&store, "carol", "ALLOW"));
+
+        // Dropping the last handle flushes the queue and stops the task
+        drop(writer);
+        handle.await.unwrap();
+
+        let all = repo
+            .query_decision_logs(
+                &store,
+                &DecisionLogFilter {
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].principal, "User::\"carol\"");
+        assert_eq!(all[0].determining_policies, vec!["policy1".to_string()]);
+        assert_eq!(all[0].latency_micros, 42);
+        assert_eq!(
+            all[0].caller.get("user-agent").map(String::as_str),
+            Some("test")
+        );
+
+        let denied = repo
+            .query_decision_logs(
+                &store,
+                &DecisionLogFilter {
+                    decision: Some("DENY".to_string()),
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].correlation_id.as_deref(), Some("bob-1"));
+
+        let page = repo
+            .query_decision_logs(
+                &store,
+                &DecisionLogFilter {
+                    before_id: all[0].id,
+                    limit: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].principal, "User::\"bob\"");
+
+        let future = repo
+            .query_decision_logs(
+                &store,
+                &DecisionLogFilter {
+                    start_time: Some(Utc::now() + ChronoDuration::hours(1)),
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_decisions() {
+        let repo = Arc::new(RepositoryAdapter::new(":memory:").await.unwrap());
+        let store = create_store(&repo).await;
+
+        let config = DecisionLogConfig {
+            capacity: 1,
+            batch_size: 1,
+            flush_interval: Duration::from_millis(10),
+        };
+        let (writer, handle) = DecisionLogWriter::spawn(repo, config);
+
+        // The writer task has not run yet on this single-threaded runtime
+        for _ in 0..5 {
+            writer.record(decision(&store, "alice", "ALLOW"));
+        }
+        assert_eq!(writer.dropped(), 4);
+
+        drop(writer);
+        handle.await.unwrap();
+    }
+}
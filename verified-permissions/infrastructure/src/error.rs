@@ -29,6 +29,9 @@ pub enum AuthorizationError {
     
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+    
+    #[error("Domain error: {0}")]
+    Domain(#[from] hodei_domain::DomainError),
 }
 
 // Note: Conversion to tonic::Status is done in the API layer
@@ -0,0 +1,141 @@
+//! Signature algorithms accepted per identity source
+//!
+//! A token names its own algorithm in the `alg` header. Checking it against
+//! the identity source's allowlist before picking a key keeps a token from
+//! downgrading to a weaker algorithm, or from being verified with a public
+//! key used as an HMAC secret.
+
+use crate::error::{AuthorizationError, Result};
+use jsonwebtoken::Algorithm;
+use std::str::FromStr;
+
+/// Algorithms of keys published in a JWKS
+pub const ASYMMETRIC_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Algorithms of tokens signed with a shared secret
+pub const SYMMETRIC_ALGORITHMS: &[Algorithm] =
+    &[Algorithm::HS256, Algorithm::HS384, Algorithm::HS512];
+
+/// Signature algorithms an identity source accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgorithmAllowlist {
+    algorithms: Vec<Algorithm>,
+}
+
+impl AlgorithmAllowlist {
+    /// Allowlist for tokens verified with JWKS keys
+    ///
+    /// Empty `names` accept every asymmetric algorithm; HMAC algorithms are
+    /// rejected.
+    pub fn asymmetric(names: &[String]) -> Result<Self> {
+        Self::parse(names, ASYMMETRIC_ALGORITHMS, ASYMMETRIC_ALGORITHMS)
+    }
+
+    /// Allowlist for tokens signed with a shared secret
+    ///
+    /// Empty `names` accept HS256 only; only HMAC algorithms are allowed.
+    pub fn symmetric(names: &[String]) -> Result<Self> {
+        Self::parse(names, SYMMETRIC_ALGORITHMS, &[Algorithm::HS256])
+    }
+
+    fn parse(names: &[String], supported: &[Algorithm], default: &[Algorithm]) -> Result<Self> {
+        if names.is_empty() {
+            return Ok(Self {
+                algorithms: default.to_vec(),
+            });
+        }
+
+        let mut algorithms = Vec::with_capacity(names.len());
+        for name in names {
+            let algorithm = Algorithm::from_str(name.trim())
+                .ok()
+                .filter(|algorithm| supported.contains(algorithm))
+                .ok_or_else(|| {
+                    AuthorizationError::InvalidArgument(format!(
+                        "Unsupported signature algorithm '{}' (expected one of {})",
+                        name,
+                        Self::names_of(supported).join(", ")
+                    ))
+                })?;
+            if !algorithms.contains(&algorithm) {
+                algorithms.push(algorithm);
+            }
+        }
+        Ok(Self { algorithms })
+    }
+
+    /// Whether tokens signed with `algorithm` are accepted
+    pub fn allows(&self, algorithm: Algorithm) -> bool {
+        self.algorithms.contains(&algorithm)
+    }
+
+    /// Rejects tokens whose `alg` header is not on the allowlist
+    pub fn check(&self, algorithm: Algorithm) -> Result<()> {
+        if self.allows(algorithm) {
+            Ok(())
+        } else {
+            Err(AuthorizationError::Unauthenticated(format!(
+                "Token algorithm {:?} is not allowed (allowed: {})",
+                algorithm,
+                self.names().join(", ")
+            )))
+        }
+    }
+
+    /// Names of the accepted algorithms, e.g. `["RS256", "ES256"]`
+    pub fn names(&self) -> Vec<String> {
+        Self::names_of(&self.algorithms)
+    }
+
+    fn names_of(algorithms: &[Algorithm]) -> Vec<String> {
+        algorithms
+            .iter()
+            .map(|algorithm| format!("{:?}", algorithm))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let asymmetric = AlgorithmAllowlist::asymmetric(&[]).unwrap();
+        assert!(asymmetric.allows(Algorithm::ES384));
+        assert!(asymmetric.allows(Algorithm::EdDSA));
+        assert!(!asymmetric.allows(Algorithm::HS256));
+
+        let symmetric = AlgorithmAllowlist::symmetric(&[]).unwrap();
+        assert_eq!(symmetric.names(), names(&["HS256"]));
+    }
+
+    #[test]
+    fn test_allowlist_rejects_other_algorithms() {
+        let allowlist = AlgorithmAllowlist::asymmetric(&names(&["PS256", "ES256"])).unwrap();
+        assert!(allowlist.check(Algorithm::PS256).is_ok());
+        assert!(allowlist.check(Algorithm::RS256).is_err());
+        assert!(allowlist.check(Algorithm::HS256).is_err());
+    }
+
+    #[test]
+    fn test_family_mismatch_is_invalid() {
+        assert!(AlgorithmAllowlist::asymmetric(&names(&["HS256"])).is_err());
+        assert!(AlgorithmAllowlist::symmetric(&names(&["RS256"])).is_err());
+        assert!(AlgorithmAllowlist::asymmetric(&names(&["none"])).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+//! Cedar types of attributes mapped from token claims
+//!
+//! Without a type an attribute keeps the claim's JSON value. A declared type
+//! (from the claims mapping or inferred from the store schema) converts the
+//! claim to that Cedar type, so numeric, boolean, set and extension claims
+//! can be compared in policies. Values are produced in Cedar's entity JSON
+//! format, with extension values written as `{"__extn": {...}}`.
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Nesting allowed when resolving schema common types
+const MAX_COMMON_TYPE_DEPTH: usize = 8;
+
+/// Cedar type of a mapped attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AttributeType {
+    String,
+    Long,
+    Bool,
+    /// `ipaddr` extension: an IPv4 or IPv6 address or CIDR range
+    Ipaddr,
+    /// `decimal` extension: up to four fractional digits
+    Decimal,
+    /// `datetime` extension: RFC 3339 timestamps, dates or Unix seconds
+    Datetime,
+    /// `Set<T>`; a single value becomes a one-element set
+    Set(Box<AttributeType>),
+}
+
+impl AttributeType {
+    /// Converts a claim value to Cedar entity JSON of this type
+    pub fn convert(&self, value: &Value) -> Result<Value, String> {
+        match self {
+            AttributeType::String => match value {
+                Value::String(_) => Ok(value.clone()),
+                Value::Number(_) | Value::Bool(_) => Ok(Value::String(value.to_string())),
+                _ => Err(format!("expected a string, got {}", kind(value))),
+            },
+
+            AttributeType::Long => match value {
+                Value::Number(n) => n
+                    .as_i64()
+                    .map(Value::from)
+                    .ok_or_else(|| format!("{} is not a 64-bit integer", n)),
+                Value::String(s) => s
+                    .trim()
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| format!("'{}' is not a 64-bit integer", s)),
+                _ => Err(format!("expected an integer, got {}", kind(value))),
+            },
+
+            AttributeType::Bool => match value {
+                Value::Bool(_) => Ok(value.clone()),
+                Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+                Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+                _ => Err(format!("expected true or false, got {}", describe(value))),
+            },
+
+            AttributeType::Ipaddr => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| format!("expected an IP address, got {}", kind(value)))?
+                    .trim();
+                let valid = match s.split_once('/') {
+                    Some((ip, prefix)) => match (ip.parse::<IpAddr>(), prefix.parse::<u8>()) {
+                        (Ok(IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+                        (Ok(IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+                        _ => false,
+                    },
+                    None => s.parse::<IpAddr>().is_ok(),
+                };
+                if !valid {
+                    return Err(format!("'{}' is not an IP address or CIDR range", s));
+                }
+                Ok(extension("ip", s.to_string()))
+            }
+
+            AttributeType::Decimal => {
+                let s = match value {
+                    Value::Number(n) => n.to_string(),
+                    Value::String(s) => s.trim().to_string(),
+                    _ => return Err(format!("expected a decimal, got {}", kind(value))),
+                };
+                decimal(&s)
+                    .map(|decimal| extension("decimal", decimal))
+                    .ok_or_else(|| {
+                        format!(
+                            "'{}' is not a decimal with at most four fractional digits",
+                            s
+                        )
+                    })
+            }
+
+            AttributeType::Datetime => {
+                let datetime = match value {
+                    // Unix seconds, as in iat, exp or auth_time
+                    Value::Number(n) => n
+                        .as_i64()
+                        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+                        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+                        .ok_or_else(|| format!("{} is not a Unix timestamp", n))?,
+                    Value::String(s) => {
+                        let s = s.trim();
+                        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                            dt.with_timezone(&Utc)
+                                .to_rfc3339_opts(SecondsFormat::Millis, true)
+                        } else if NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() {
+                            s.to_string()
+                        } else {
+                            return Err(format!("'{}' is not an RFC 3339 timestamp or date", s));
+                        }
+                    }
+                    _ => return Err(format!("expected a timestamp, got {}", kind(value))),
+                };
+                Ok(extension("datetime", datetime))
+            }
+
+            AttributeType::Set(element) => match value {
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| element.convert(item))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array),
+                Value::Null => Err("expected a set, got null".to_string()),
+                value => Ok(Value::Array(vec![element.convert(value)?])),
+            },
+        }
+    }
+
+    /// Type of a schema attribute in Cedar's JSON schema format
+    ///
+    /// Returns `None` for types that claims cannot hold, such as entity
+    /// references and records.
+    fn from_schema(ty: &Value, common_types: &Map<String, Value>, depth: usize) -> Option<Self> {
+        let type_name = ty.get("type")?.as_str()?;
+        let name = match type_name {
+            "Set" => {
+                let element = Self::from_schema(ty.get("element")?, common_types, depth)?;
+                return Some(AttributeType::Set(Box::new(element)));
+            }
+            "Extension" | "EntityOrCommon" => ty.get("name")?.as_str()?,
+            name => name,
+        };
+        let name = name.strip_prefix("__cedar::").unwrap_or(name);
+
+        match name {
+            "String" => Some(AttributeType::String),
+            "Long" => Some(AttributeType::Long),
+            "Boolean" | "Bool" => Some(AttributeType::Bool),
+            "ipaddr" => Some(AttributeType::Ipaddr),
+            "decimal" => Some(AttributeType::Decimal),
+            "datetime" => Some(AttributeType::Datetime),
+            _ if depth < MAX_COMMON_TYPE_DEPTH => {
+                let local = name.rsplit("::").next().unwrap_or(name);
+                Self::from_schema(common_types.get(local)?, common_types, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Attribute types of every entity type in a schema in Cedar's JSON format
+///
+/// Keyed by the qualified entity type name (e.g. `MyApp::User`), then by
+/// attribute name. Attributes whose type claims cannot hold are left out.
+pub fn schema_attribute_types(schema: &Value) -> HashMap<String, HashMap<String, AttributeType>> {
+    let mut types = HashMap::new();
+    let Some(namespaces) = schema.as_object() else {
+        return types;
+    };
+
+    let empty = Map::new();
+    for (namespace, definition) in namespaces {
+        let common_types = definition
+            .get("commonTypes")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        let Some(entity_types) = definition.get("entityTypes").and_then(Value::as_object) else {
+            continue;
+        };
+
+        for (name, entity_type) in entity_types {
+            let mut shape = entity_type.get("shape");
+            // The shape may name a common record type
+            if let Some(alias) = shape
+                .and_then(|shape| shape.get("type"))
+                .and_then(Value::as_str)
+                .filter(|alias| *alias != "Record")
+            {
+                shape = common_types.get(alias.rsplit("::").next().unwrap_or(alias));
+            }
+            let Some(attributes) = shape
+                .and_then(|shape| shape.get("attributes"))
+                .and_then(Value::as_object)
+            else {
+                continue;
+            };
+
+            let attribute_types = attributes
+                .iter()
+                .filter_map(|(attribute, ty)| {
+                    Some((
+                        attribute.clone(),
+                        AttributeType::from_schema(ty, common_types, 0)?,
+                    ))
+                })
+                .collect();
+            let qualified = if namespace.is_empty() {
+                name.clone()
+            } else {
+                format!("{}::{}", namespace, name)
+            };
+            types.insert(qualified, attribute_types);
+        }
+    }
+    types
+}
+
+fn extension(function: &str, arg: String) -> Value {
+    serde_json::json!({ "__extn": { "fn": function, "arg": arg } })
+}
+
+/// Normalizes a decimal to Cedar's form: digits, a point and one to four
+/// fractional digits, within the range of a 64-bit integer of ten-thousandths
+fn decimal(s: &str) -> Option<String> {
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, "0"));
+    let digits = integer.strip_prefix('-').unwrap_or(integer);
+    let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(digits) || !is_digits(fraction) || fraction.len() > 4 {
+        return None;
+    }
+    let scaled = format!("{}{:0<4}", integer, fraction);
+    scaled.parse::<i64>().ok()?;
+    Some(format!("{}.{}", integer, fraction))
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s),
+        value => kind(value).to_string(),
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeType::String => write!(f, "String"),
+            AttributeType::Long => write!(f, "Long"),
+            AttributeType::Bool => write!(f, "Bool"),
+            AttributeType::Ipaddr => write!(f, "ipaddr"),
+            AttributeType::Decimal => write!(f, "decimal"),
+            AttributeType::Datetime => write!(f, "datetime"),
+            AttributeType::Set(element) => write!(f, "Set<{}>", element),
+        }
+    }
+}
+
+impl FromStr for AttributeType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(element) = s
+            .strip_prefix("Set<")
+            .or_else(|| s.strip_prefix("set<"))
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return Ok(AttributeType::Set(Box::new(element.parse()?)));
+        }
+
+        match s.to_ascii_lowercase().as_str() {
+            "string" => Ok(AttributeType::String),
+            "long" => Ok(AttributeType::Long),
+            "bool" | "boolean" => Ok(AttributeType::Bool),
+            "ipaddr" => Ok(AttributeType::Ipaddr),
+            "decimal" => Ok(AttributeType::Decimal),
+            "datetime" => Ok(AttributeType::Datetime),
+            _ => Err(format!(
+                "Unknown attribute type '{}' (expected String, Long, Bool, ipaddr, decimal, datetime or Set<...>)",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for AttributeType {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<AttributeType> for String {
+    fn from(ty: AttributeType) -> Self {
+        ty.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(s: &str) -> AttributeType {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(parse("Long"), AttributeType::Long);
+        assert_eq!(parse("boolean"), AttributeType::Bool);
+        assert_eq!(
+            parse("Set<String>"),
+            AttributeType::Set(Box::new(AttributeType::String))
+        );
+        assert_eq!(parse("Set<ipaddr>").to_string(), "Set<ipaddr>");
+        assert!("Record".parse::<AttributeType>().is_err());
+        assert!("Set<Foo>".parse::<AttributeType>().is_err());
+    }
+
+    #[test]
+    fn test_convert_primitives() {
+        assert_eq!(parse("Long").convert(&json!("42")).unwrap(), json!(42));
+        assert_eq!(parse("Long").convert(&json!(7)).unwrap(), json!(7));
+        assert!(parse("Long").convert(&json!(1.5)).is_err());
+        assert!(parse("Long").convert(&json!("five")).is_err());
+
+        assert_eq!(parse("Bool").convert(&json!("TRUE")).unwrap(), json!(true));
+        assert!(parse("Bool").convert(&json!("yes")).is_err());
+
+        assert_eq!(parse("String").convert(&json!(5)).unwrap(), json!("5"));
+        assert!(parse("String").convert(&json!(["a"])).is_err());
+
+        assert_eq!(
+            parse("Set<String>").convert(&json!(["a", "b"])).unwrap(),
+            json!(["a", "b"])
+        );
+        assert_eq!(parse("Set<Long>").convert(&json!("3")).unwrap(), json!([3]));
+    }
+
+    #[test]
+    fn test_convert_extensions() {
+        assert_eq!(
+            parse("ipaddr").convert(&json!("10.0.0.0/8")).unwrap(),
+            json!({"__extn": {"fn": "ip", "arg": "10.0.0.0/8"}})
+        );
+        assert!(parse("ipaddr").convert(&json!("10.0.0.0/33")).is_err());
+        assert!(parse("ipaddr").convert(&json!("not-an-ip")).is_err());
+
+        assert_eq!(
+            parse("decimal").convert(&json!(2)).unwrap(),
+            json!({"__extn": {"fn": "decimal", "arg": "2.0"}})
+        );
+        assert_eq!(
+            parse("decimal").convert(&json!("-0.1234")).unwrap(),
+            json!({"__extn": {"fn": "decimal", "arg": "-0.1234"}})
+        );
+        assert!(parse("decimal").convert(&json!("1.23456")).is_err());
+        assert!(parse("decimal").convert(&json!("1e5")).is_err());
+
+        assert_eq!(
+            parse("datetime").convert(&json!(1700000000)).unwrap(),
+            json!({"__extn": {"fn": "datetime", "arg": "2023-11-14T22:13:20.000Z"}})
+        );
+        assert_eq!(
+            parse("datetime")
+                .convert(&json!("2024-01-02T03:04:05+01:00"))
+                .unwrap(),
+            json!({"__extn": {"fn": "datetime", "arg": "2024-01-02T02:04:05.000Z"}})
+        );
+        assert_eq!(
+            parse("datetime").convert(&json!("2024-01-02")).unwrap(),
+            json!({"__extn": {"fn": "datetime", "arg": "2024-01-02"}})
+        );
+        let error = parse("datetime").convert(&json!("yesterday")).unwrap_err();
+        assert!(error.contains("yesterday"));
+    }
+
+    #[test]
+    fn test_schema_attribute_types() {
+        let schema = json!({
+            "MyApp": {
+                "commonTypes": {
+                    "Level": {"type": "Long"},
+                    "Profile": {
+                        "type": "Record",
+                        "attributes": {"email": {"type": "String"}}
+                    }
+                },
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "level": {"type": "EntityOrCommon", "name": "Level"},
+                                "mfa": {"type": "Boolean"},
+                                "groups": {"type": "Set", "element": {"type": "String"}},
+                                "ip": {"type": "Extension", "name": "ipaddr"},
+                                "age": {"type": "EntityOrCommon", "name": "__cedar::Long"},
+                                "manager": {"type": "Entity", "name": "User"}
+                            }
+                        }
+                    },
+                    "Service": {"shape": {"type": "Profile"}},
+                    "Team": {}
+                }
+            }
+        });
+
+        let types = schema_attribute_types(&schema);
+        let user = &types["MyApp::User"];
+        assert_eq!(user["level"], AttributeType::Long);
+        assert_eq!(user["mfa"], AttributeType::Bool);
+        assert_eq!(user["groups"], parse("Set<String>"));
+        assert_eq!(user["ip"], AttributeType::Ipaddr);
+        assert_eq!(user["age"], AttributeType::Long);
+        assert!(!user.contains_key("manager"));
+        assert_eq!(types["MyApp::Service"]["email"], AttributeType::String);
+        assert!(!types.contains_key("MyApp::Team"));
+    }
+}
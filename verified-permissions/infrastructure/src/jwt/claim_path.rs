@@ -0,0 +1,419 @@
+//! Paths into structured token claims
+//!
+//! Identity providers nest authorization data in different shapes: Keycloak
+//! puts client roles under `resource_access.<client>.roles`, Zitadel encodes
+//! project roles as the keys of an object, and OAuth `scope` is a single
+//! space-separated string. A claim path selects such values with a small
+//! JSONPath-like syntax:
+//!
+//! | Path                                       | Selects                                  |
+//! |--------------------------------------------|------------------------------------------|
+//! | `realm_access.roles`                       | Nested object member                     |
+//! | `resource_access["my.client"].roles`       | Member whose name contains dots          |
+//! | `groups[0]`, `groups[-1]`                  | Array element (negative counts from end) |
+//! | `resource_access.*.roles`, `orgs[*]`       | Every array element or member value      |
+//! | `urn:zitadel:iam:org:project:roles.keys()` | Object keys                              |
+//! | `scope.split()`, `perms.split(";")`        | Parts of a string, split on whitespace and commas or on a separator |
+//!
+//! A leading `$` or `$.` is accepted. `keys()` and `split()` must be the last
+//! step. Paths with `*`, `keys()` or `split()` select a set of values and
+//! yield an array; object members are visited in key order, whatever order
+//! the token lists them in.
+
+use crate::error::{AuthorizationError, Result};
+use serde_json::Value;
+
+/// Parsed claim path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimPath {
+    claim: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    /// Object member, or array element when the name is a number
+    Member(String),
+    /// Array element; negative counts from the end
+    Index(i64),
+    /// Every array element or object member value
+    Wildcard,
+    /// Object keys as string values
+    Keys,
+    /// String split into parts; `None` splits on whitespace and commas
+    Split(Option<String>),
+}
+
+impl ClaimPath {
+    /// Parses a claim path
+    pub fn parse(path: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            AuthorizationError::InvalidArgument(format!(
+                "Invalid claim path '{}': {}",
+                path, reason
+            ))
+        };
+
+        let rest = path.strip_prefix('$').unwrap_or(path);
+        let rest = if rest.len() < path.len() {
+            rest.strip_prefix('.').unwrap_or(rest)
+        } else {
+            rest
+        };
+
+        let mut parser = Parser {
+            chars: rest.char_indices().peekable(),
+            input: rest,
+        };
+        let mut steps = Vec::new();
+        let mut expect_segment = true;
+
+        while let Some(c) = parser.peek() {
+            let step = match c {
+                '[' => {
+                    parser.advance();
+                    parser.bracket().map_err(invalid)?
+                }
+                '.' if !expect_segment => {
+                    parser.advance();
+                    expect_segment = true;
+                    continue;
+                }
+                _ if expect_segment => parser.segment().map_err(invalid)?,
+                _ => return Err(invalid("expected '.' or '[' between steps")),
+            };
+            if matches!(steps.last(), Some(Step::Keys | Step::Split(_))) {
+                return Err(invalid("keys() and split() must be the last step"));
+            }
+            steps.push(step);
+            expect_segment = false;
+        }
+        if expect_segment {
+            return Err(invalid("empty step"));
+        }
+
+        let claim = match steps.remove(0) {
+            Step::Member(claim) => claim,
+            _ => return Err(invalid("must start with a claim name")),
+        };
+        Ok(Self { claim, steps })
+    }
+
+    /// Looks up `path` among the claims returned by `get`
+    ///
+    /// The full path is tried as a claim name first, so names such as
+    /// `cognito:groups` or `https://example.com/roles` work as-is. Paths that
+    /// do not parse select nothing.
+    pub fn lookup<'a>(get: impl Fn(&str) -> Option<&'a Value>, path: &str) -> Option<Value> {
+        if let Some(value) = get(path) {
+            return Some(value.clone());
+        }
+        Self::parse(path).ok()?.select(get)
+    }
+
+    /// Selects the value of the path among the claims returned by `get`
+    pub fn select<'a>(&self, get: impl Fn(&str) -> Option<&'a Value>) -> Option<Value> {
+        let mut values = vec![get(&self.claim)?];
+        let mut many = false;
+
+        for step in &self.steps {
+            values = match step {
+                Step::Member(name) => values
+                    .into_iter()
+                    .filter_map(|value| match value {
+                        Value::Array(items) => items.get(name.parse::<usize>().ok()?),
+                        value => value.get(name),
+                    })
+                    .collect(),
+                Step::Index(index) => values
+                    .into_iter()
+                    .filter_map(|value| {
+                        let items = value.as_array()?;
+                        let index = if *index < 0 {
+                            items.len().checked_sub(index.unsigned_abs() as usize)?
+                        } else {
+                            *index as usize
+                        };
+                        items.get(index)
+                    })
+                    .collect(),
+                Step::Wildcard => {
+                    many = true;
+                    values
+                        .into_iter()
+                        .flat_map(|value| match value {
+                            Value::Array(items) => items.iter().collect(),
+                            Value::Object(members) => sorted_members(members)
+                                .into_iter()
+                                .map(|(_, value)| value)
+                                .collect(),
+                            _ => Vec::new(),
+                        })
+                        .collect()
+                }
+                Step::Keys => {
+                    let keys = values
+                        .into_iter()
+                        .filter_map(Value::as_object)
+                        .flat_map(|members| {
+                            sorted_members(members)
+                                .into_iter()
+                                .map(|(key, _)| Value::String(key.clone()))
+                        })
+                        .collect();
+                    return Self::set(keys);
+                }
+                Step::Split(separator) => {
+                    let parts = values
+                        .into_iter()
+                        .flat_map(|value| match value {
+                            Value::Array(items) => items.iter().collect(),
+                            value => vec![value],
+                        })
+                        .filter_map(Value::as_str)
+                        .flat_map(|s| split(s, separator.as_deref()))
+                        .map(Value::String)
+                        .collect();
+                    return Self::set(parts);
+                }
+            };
+        }
+
+        if many {
+            Self::set(values.into_iter().cloned().collect())
+        } else {
+            values.into_iter().next().cloned()
+        }
+    }
+
+    fn set(values: Vec<Value>) -> Option<Value> {
+        (!values.is_empty()).then_some(Value::Array(values))
+    }
+}
+
+/// Object members in key order; serde_json keeps insertion order when its
+/// `preserve_order` feature is enabled by another crate
+fn sorted_members(members: &serde_json::Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut members: Vec<(&String, &Value)> = members.iter().collect();
+    members.sort_by_key(|(key, _)| *key);
+    members
+}
+
+fn split(value: &str, separator: Option<&str>) -> Vec<String> {
+    let parts: Vec<&str> = match separator {
+        Some(separator) => value.split(separator).collect(),
+        None => value
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .collect(),
+    };
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    input: &'a str,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|(i, _)| *i)
+            .unwrap_or(self.input.len())
+    }
+
+    /// Dotted segment: a member name, `*`, `keys()` or `split(...)`
+    fn segment(&mut self) -> std::result::Result<Step, &'static str> {
+        let start = self.position();
+        while let Some(c) = self.peek() {
+            match c {
+                '.' | '[' => break,
+                '(' => {
+                    self.arguments()?;
+                    break;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+        let input = self.input;
+        let end = self.position();
+        let segment = &input[start..end];
+
+        match segment {
+            "" => Err("empty step"),
+            "*" => Ok(Step::Wildcard),
+            "keys()" => Ok(Step::Keys),
+            "split()" => Ok(Step::Split(None)),
+            _ => match segment
+                .strip_prefix("split(")
+                .and_then(|arguments| arguments.strip_suffix(')'))
+            {
+                Some(argument) => Ok(Step::Split(Some(
+                    unquote(argument).ok_or("split() expects a quoted separator")?,
+                ))),
+                None if segment.contains('(') => Err("unknown function"),
+                None => Ok(Step::Member(segment.to_string())),
+            },
+        }
+    }
+
+    /// Consumes a parenthesized argument list, which may contain quoted dots
+    fn arguments(&mut self) -> std::result::Result<(), &'static str> {
+        let mut quote = None;
+        while let Some(c) = self.advance() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), _) if c == q => quote = None,
+                (None, ')') => return Ok(()),
+                _ => {}
+            }
+        }
+        Err("unclosed '('")
+    }
+
+    /// Bracketed step after `[`: `*`, an index or a quoted member name
+    fn bracket(&mut self) -> std::result::Result<Step, &'static str> {
+        let start = self.position();
+        let mut quote = None;
+        loop {
+            let c = self.peek().ok_or("unclosed '['")?;
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), _) if c == q => quote = None,
+                (None, ']') => break,
+                _ => {}
+            }
+            self.advance();
+        }
+        let input = self.input;
+        let end = self.position();
+        let inner = input[start..end].trim();
+        self.advance();
+
+        if inner == "*" {
+            Ok(Step::Wildcard)
+        } else if let Ok(index) = inner.parse::<i64>() {
+            Ok(Step::Index(index))
+        } else {
+            unquote(inner)
+                .map(Step::Member)
+                .ok_or("brackets expect *, an index or a quoted name")
+        }
+    }
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let s = s.trim();
+    ['"', '\'']
+        .into_iter()
+        .find_map(|q| s.strip_prefix(q)?.strip_suffix(q))
+        .filter(|inner| !inner.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lookup(claims: &Value, path: &str) -> Option<Value> {
+        ClaimPath::lookup(|name| claims.get(name), path)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(ClaimPath::parse("realm_access.roles").is_ok());
+        assert!(ClaimPath::parse("$.resource_access['my.client'].roles[0]").is_ok());
+        assert!(ClaimPath::parse("perms.split(\".\")").is_ok());
+
+        assert!(ClaimPath::parse("").is_err());
+        assert!(ClaimPath::parse("roles.").is_err());
+        assert!(ClaimPath::parse("a..b").is_err());
+        assert!(ClaimPath::parse("[0]").is_err());
+        assert!(ClaimPath::parse("roles[0").is_err());
+        assert!(ClaimPath::parse("roles[name]").is_err());
+        assert!(ClaimPath::parse("roles.keys().x").is_err());
+        assert!(ClaimPath::parse("roles.values()").is_err());
+    }
+
+    #[test]
+    fn test_nested_members_and_indexes() {
+        let claims = json!({
+            "resource_access": {
+                "web.app": {"roles": ["admin", "viewer"]},
+                "api": {"roles": ["reader"]}
+            },
+            "cognito:groups": ["admins"],
+            "https://example.com/roles": ["editor"]
+        });
+
+        assert_eq!(lookup(&claims, "cognito:groups"), Some(json!(["admins"])));
+        assert_eq!(
+            lookup(&claims, "https://example.com/roles"),
+            Some(json!(["editor"]))
+        );
+        assert_eq!(
+            lookup(&claims, "resource_access.api.roles"),
+            Some(json!(["reader"]))
+        );
+        assert_eq!(
+            lookup(&claims, "resource_access[\"web.app\"].roles[-1]"),
+            Some(json!("viewer"))
+        );
+        assert_eq!(
+            lookup(&claims, "$.resource_access.api.roles.0"),
+            Some(json!("reader"))
+        );
+        assert_eq!(lookup(&claims, "resource_access.api.roles[5]"), None);
+        assert_eq!(lookup(&claims, "resource_access.missing.roles"), None);
+    }
+
+    #[test]
+    fn test_sets() {
+        let claims = json!({
+            "resource_access": {
+                "web": {"roles": ["admin"]},
+                "api": {"roles": ["reader", "writer"]}
+            },
+            "urn:zitadel:iam:org:project:roles": {
+                "editor": {"org1": "acme.example.com"},
+                "viewer": {"org1": "acme.example.com"}
+            },
+            "scope": "openid docs:read,docs:write",
+            "perms": ["a;b", "c"]
+        });
+
+        assert_eq!(
+            lookup(&claims, "resource_access.*.roles[*]"),
+            Some(json!(["reader", "writer", "admin"]))
+        );
+        assert_eq!(
+            lookup(&claims, "urn:zitadel:iam:org:project:roles.keys()"),
+            Some(json!(["editor", "viewer"]))
+        );
+        assert_eq!(
+            lookup(&claims, "scope.split()"),
+            Some(json!(["openid", "docs:read", "docs:write"]))
+        );
+        assert_eq!(
+            lookup(&claims, "perms.split(';')"),
+            Some(json!(["a", "b", "c"]))
+        );
+        assert_eq!(lookup(&claims, "resource_access.*.groups"), None);
+    }
+}
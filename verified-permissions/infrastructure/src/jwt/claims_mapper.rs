@@ -1,8 +1,11 @@
 //! Claims mapping to Cedar entities
 
 use crate::error::{AuthorizationError, Result};
-use crate::jwt::{ValidatedClaims, ValueTransform};
+use crate::jwt::{AttributeType, ClaimPath, ValidatedClaims, ValueTransform};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use tracing::warn;
 
 /// Simplified entity identifier (avoids proto dependency)
 #[derive(Debug, Clone)]
@@ -20,45 +23,130 @@ pub struct EntityData {
 }
 
 /// Mapping of a JWT claim to a parent entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParentMapping {
-    /// Path to the claim in the JWT (e.g., "groups", "cognito:groups")
+    /// Claim name or path in the JWT (e.g., "groups", "cognito:groups",
+    /// "resource_access.*.roles"; see [`ClaimPath`])
     pub claim_path: String,
     
     /// Entity type for the parent (e.g., "Group", "Role")
     pub entity_type: String,
     
-    /// Optional transformation to apply to the claim value
+    /// Optional transformation to apply to each claim value; it may turn a
+    /// value into several parents or drop it
+    #[serde(default)]
     pub transform: ValueTransform,
 }
 
 /// Configuration for mapping JWT claims to Cedar entities
-#[derive(Debug, Clone)]
+///
+/// This is what gets stored as the identity source's `claims_mapping_json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ClaimsMappingConfig {
     /// Claim to use as principal entity ID (default: "sub")
     pub principal_id_claim: String,
     
+    /// Entity type of the principal (default: "User")
+    pub principal_entity_type: String,
+    
     /// Claim containing group membership (deprecated, use parent_mappings instead)
     pub group_claim: Option<String>,
     
-    /// Map of Cedar attribute names to JWT claim names
+    /// Entity type of the groups found in `group_claim` (default: "Role")
+    pub group_entity_type: String,
+    
+    /// Map of Cedar attribute names to JWT claim names or paths (default:
+    /// `email` from the `email` claim)
     pub attribute_mappings: HashMap<String, String>,
+
+    /// Cedar types of mapped attributes; untyped attributes keep the claim's
+    /// JSON value
+    pub attribute_types: HashMap<String, AttributeType>,
     
     /// Mappings for parent entities (groups, roles, etc.)
     pub parent_mappings: Vec<ParentMapping>,
+    
+    /// Token claims copied into the request context
+    pub context_claims: Option<ContextClaimsConfig>,
+}
+
+/// Copies validated token claims into the Cedar request context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextClaimsConfig {
+    /// Context attribute holding the claims (default: "token")
+    pub context_key: String,
+    
+    /// Claims to copy (names or paths); all claims when empty
+    pub claims: Vec<String>,
+}
+
+impl Default for ContextClaimsConfig {
+    fn default() -> Self {
+        Self {
+            context_key: "token".to_string(),
+            claims: Vec::new(),
+        }
+    }
 }
 
 impl Default for ClaimsMappingConfig {
     fn default() -> Self {
         Self {
             principal_id_claim: "sub".to_string(),
+            principal_entity_type: "User".to_string(),
             group_claim: Some("groups".to_string()),
-            attribute_mappings: HashMap::new(),
+            group_entity_type: "Role".to_string(),
+            attribute_mappings: HashMap::from([("email".to_string(), "email".to_string())]),
+            attribute_types: HashMap::new(),
             parent_mappings: Vec::new(),
+            context_claims: None,
         }
     }
 }
 
+impl ClaimsMappingConfig {
+    /// Parse a stored claims mapping
+    ///
+    /// Empty strings (written for unset proto fields) fall back to the
+    /// defaults, except `group_claim` where an empty value disables it.
+    /// Mappings stored before `principal_entity_type` existed were evaluated
+    /// with `email` always mapped, so they keep that attribute.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let legacy = value.get("principal_entity_type").is_none();
+        let mut config: Self = serde_json::from_value(value)?;
+        let defaults = Self::default();
+
+        if legacy {
+            config
+                .attribute_mappings
+                .entry("email".to_string())
+                .or_insert_with(|| "email".to_string());
+        }
+        if config.principal_id_claim.is_empty() {
+            config.principal_id_claim = defaults.principal_id_claim;
+        }
+        if config.principal_entity_type.is_empty() {
+            config.principal_entity_type = defaults.principal_entity_type;
+        }
+        if config.group_entity_type.is_empty() {
+            config.group_entity_type = defaults.group_entity_type;
+        }
+        if config.group_claim.as_deref() == Some("") {
+            config.group_claim = None;
+        }
+        if let Some(context_claims) = &mut config.context_claims
+            && context_claims.context_key.is_empty()
+        {
+            context_claims.context_key = ContextClaimsConfig::default().context_key;
+        }
+
+        Ok(config)
+    }
+}
+
 /// Claims mapper for converting JWT claims to Cedar entities
 pub struct ClaimsMapper;
 
@@ -73,17 +161,14 @@ impl ClaimsMapper {
         let principal_id = if config.principal_id_claim == "sub" {
             claims.sub.clone()
         } else {
-            claims
-                .additional_claims
-                .get(&config.principal_id_claim)
-                .and_then(|v| v.as_str())
+            Self::find_claim(claims, &config.principal_id_claim)
+                .and_then(|v| v.as_str().map(str::to_string))
                 .ok_or_else(|| {
                     AuthorizationError::Internal(format!(
                         "Principal ID claim '{}' not found in token",
                         config.principal_id_claim
                     ))
                 })?
-                .to_string()
         };
 
         let principal = EntityId {
@@ -94,29 +179,56 @@ impl ClaimsMapper {
         // Build principal entity with attributes
         let mut attributes = HashMap::new();
         
-        // Map configured attributes
+        // Map configured attributes, converted to their declared Cedar type
         for (cedar_attr, claim_name) in &config.attribute_mappings {
-            if let Some(value) = claims.additional_claims.get(claim_name) {
+            if let Some(mut value) = Self::find_claim(claims, claim_name) {
+                if let Some(attr_type) = config.attribute_types.get(cedar_attr) {
+                    value = attr_type.convert(&value).map_err(|e| {
+                        AuthorizationError::InvalidArgument(format!(
+                            "Claim '{}' cannot be mapped to attribute '{}' of type {}: {}",
+                            claim_name, cedar_attr, attr_type, e
+                        ))
+                    })?;
+                }
                 // Convert to JSON string for Cedar
-                let attr_value = serde_json::to_string(value)
-                    .unwrap_or_else(|_| format!("\"{}\"", value));
-                attributes.insert(cedar_attr.clone(), attr_value);
+                attributes.insert(cedar_attr.clone(), value.to_string());
             }
         }
 
         // Extract groups as parent entities
         let mut parents = Vec::new();
-        if let Some(group_claim) = &config.group_claim {
-            if let Some(groups_value) = claims.additional_claims.get(group_claim) {
-                if let Some(groups_array) = groups_value.as_array() {
-                    for group in groups_array {
-                        if let Some(group_str) = group.as_str() {
-                            parents.push(EntityId {
-                                entity_type: "Role".to_string(), // or "Group"
-                                entity_id: group_str.to_string(),
-                            });
-                        }
-                    }
+        if let Some(group_claim) = &config.group_claim
+            && let Some(groups_value) = Self::find_claim(claims, group_claim)
+        {
+            for group in Self::claim_strings(&groups_value) {
+                parents.push(EntityId {
+                    entity_type: config.group_entity_type.clone(),
+                    entity_id: group.to_string(),
+                });
+            }
+        }
+
+        // Apply parent mappings (roles, groups, ...) with their transforms
+        for mapping in &config.parent_mappings {
+            let Some(value) = Self::find_claim(claims, &mapping.claim_path) else {
+                continue;
+            };
+
+            for raw in Self::claim_strings(&value) {
+                match mapping.transform.apply_all(raw) {
+                    Ok(entity_ids) => parents.extend(
+                        entity_ids
+                            .into_iter()
+                            .filter(|entity_id| !entity_id.is_empty())
+                            .map(|entity_id| EntityId {
+                                entity_type: mapping.entity_type.clone(),
+                                entity_id,
+                            }),
+                    ),
+                    Err(e) => warn!(
+                        "Skipping value of claim '{}' for parent type {}: {}",
+                        mapping.claim_path, mapping.entity_type, e
+                    ),
                 }
             }
         }
@@ -140,6 +252,93 @@ impl ClaimsMapper {
         let (_, entities) = Self::map_to_principal(claims, config, principal_type)?;
         Ok(entities)
     }
+
+    /// Build the context record of the configured token claims
+    ///
+    /// Values are converted to their Cedar JSON form: arrays become sets,
+    /// integers longs and objects records. Nulls and non-integer numbers have
+    /// no Cedar equivalent and are left out. The OAuth `scope` claim, a
+    /// space-separated string, becomes a set of scopes.
+    pub fn context_claims(claims: &ValidatedClaims, config: &ContextClaimsConfig) -> Value {
+        let all = match serde_json::to_value(claims) {
+            Ok(Value::Object(all)) => all,
+            _ => serde_json::Map::new(),
+        };
+
+        let selected: Vec<(&str, Value)> = if config.claims.is_empty() {
+            all.iter()
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect()
+        } else {
+            config
+                .claims
+                .iter()
+                .filter_map(|path| {
+                    ClaimPath::lookup(|name| all.get(name), path)
+                        .map(|value| (path.as_str(), value))
+                })
+                .collect()
+        };
+
+        let record = selected
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = match value {
+                    Value::String(scope) if name == "scope" => Value::Array(
+                        scope
+                            .split_whitespace()
+                            .map(|s| Value::String(s.to_string()))
+                            .collect(),
+                    ),
+                    value => Self::cedar_value(&value)?,
+                };
+                Some((name.to_string(), value))
+            })
+            .collect();
+
+        Value::Object(record)
+    }
+
+    /// Cedar JSON form of a claim value, if it has one
+    fn cedar_value(value: &Value) -> Option<Value> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) | Value::String(_) => Some(value.clone()),
+            Value::Number(n) => n.as_i64().map(Value::from),
+            Value::Array(items) => Some(Value::Array(
+                items.iter().filter_map(Self::cedar_value).collect(),
+            )),
+            Value::Object(fields) => Some(Value::Object(
+                fields
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), Self::cedar_value(v)?)))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Look up a claim by name or by [`ClaimPath`]
+    fn find_claim(claims: &ValidatedClaims, path: &str) -> Option<Value> {
+        ClaimPath::lookup(|name| claims.additional_claims.get(name), path)
+    }
+
+    /// String values of a claim used for parent entities
+    ///
+    /// Arrays yield their string elements, nested arrays included (as
+    /// selected by `resource_access.*.roles`), and objects their keys
+    /// (Zitadel encodes project roles as `{"role": {...}}`).
+    fn claim_strings(value: &Value) -> Vec<&str> {
+        match value {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(items) => items
+                .iter()
+                .filter(|item| !item.is_object())
+                .flat_map(Self::claim_strings)
+                .collect(),
+            Value::Object(map) => map.keys().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -168,7 +367,21 @@ mod tests {
         let config = ClaimsMappingConfig::default();
         assert_eq!(config.principal_id_claim, "sub");
         assert_eq!(config.group_claim, Some("groups".to_string()));
-        assert!(config.attribute_mappings.is_empty());
+        assert_eq!(
+            config.attribute_mappings,
+            HashMap::from([("email".to_string(), "email".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_default_config_maps_email() {
+        let claims = create_test_claims();
+        let config = ClaimsMappingConfig::default();
+
+        let (_, entities) = ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap();
+
+        assert_eq!(entities[0].attributes.len(), 1);
+        assert_eq!(entities[0].attributes["email"], "\"user@example.com\"");
     }
 
     #[test]
@@ -284,4 +497,332 @@ mod tests {
         
         assert_eq!(entities[0].parents.len(), 0);
     }
+
+    #[test]
+    fn test_parent_mappings_with_nested_paths_and_transforms() {
+        let mut additional = HashMap::new();
+        additional.insert(
+            "realm_access".to_string(),
+            json!({"roles": ["admin", "viewer"]}),
+        );
+        additional.insert("groups".to_string(), json!(["/acme/engineering"]));
+        additional.insert(
+            "urn:zitadel:iam:org:project:roles".to_string(),
+            json!({"editor": {"org1": "acme.example.com"}}),
+        );
+
+        let claims = ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec!["client-id".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            additional_claims: additional,
+        };
+
+        let config = ClaimsMappingConfig {
+            group_claim: None,
+            parent_mappings: vec![
+                ParentMapping {
+                    claim_path: "realm_access.roles".to_string(),
+                    entity_type: "RealmRole".to_string(),
+                    transform: ValueTransform::None,
+                },
+                ParentMapping {
+                    claim_path: "groups".to_string(),
+                    entity_type: "Group".to_string(),
+                    transform: ValueTransform::SplitLast("/".to_string()),
+                },
+                ParentMapping {
+                    claim_path: "urn:zitadel:iam:org:project:roles".to_string(),
+                    entity_type: "ProjectRole".to_string(),
+                    transform: ValueTransform::None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let (_, entities) = ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap();
+
+        let parents: Vec<(&str, &str)> = entities[0]
+            .parents
+            .iter()
+            .map(|p| (p.entity_type.as_str(), p.entity_id.as_str()))
+            .collect();
+        assert_eq!(
+            parents,
+            vec![
+                ("RealmRole", "admin"),
+                ("RealmRole", "viewer"),
+                ("Group", "engineering"),
+                ("ProjectRole", "editor"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_claim_paths_for_nested_and_structured_claims() {
+        let mut additional = HashMap::new();
+        additional.insert(
+            "resource_access".to_string(),
+            json!({
+                "web.app": {"roles": ["editor"]},
+                "api": {"roles": ["reader", "writer"]}
+            }),
+        );
+        additional.insert(
+            "urn:zitadel:iam:org:project:roles".to_string(),
+            json!({"admin": {"org1": "acme.example.com"}}),
+        );
+        additional.insert("scope".to_string(), json!("docs:read docs:write"));
+        additional.insert(
+            "emails".to_string(),
+            json!(["a@example.com", "b@example.com"]),
+        );
+
+        let claims = ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec!["client-id".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            additional_claims: additional,
+        };
+
+        let mut config = ClaimsMappingConfig {
+            principal_id_claim: "emails[0]".to_string(),
+            group_claim: None,
+            parent_mappings: vec![
+                ParentMapping {
+                    claim_path: "resource_access[\"web.app\"].roles".to_string(),
+                    entity_type: "WebRole".to_string(),
+                    transform: ValueTransform::None,
+                },
+                ParentMapping {
+                    claim_path: "resource_access.*.roles".to_string(),
+                    entity_type: "ClientRole".to_string(),
+                    transform: ValueTransform::None,
+                },
+                ParentMapping {
+                    claim_path: "urn:zitadel:iam:org:project:roles.keys()".to_string(),
+                    entity_type: "ProjectRole".to_string(),
+                    transform: ValueTransform::None,
+                },
+            ],
+            ..Default::default()
+        };
+        config
+            .attribute_mappings
+            .insert("scopes".to_string(), "scope.split()".to_string());
+
+        let (principal, entities) =
+            ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap();
+
+        assert_eq!(principal.entity_id, "a@example.com");
+        assert_eq!(
+            entities[0].attributes.get("scopes"),
+            Some(&r#"["docs:read","docs:write"]"#.to_string())
+        );
+        let parents: Vec<(&str, &str)> = entities[0]
+            .parents
+            .iter()
+            .map(|p| (p.entity_type.as_str(), p.entity_id.as_str()))
+            .collect();
+        assert_eq!(
+            parents,
+            vec![
+                ("WebRole", "editor"),
+                ("ClientRole", "reader"),
+                ("ClientRole", "writer"),
+                ("ClientRole", "editor"),
+                ("ProjectRole", "admin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typed_attributes() {
+        let mut additional = HashMap::new();
+        additional.insert("custom:level".to_string(), json!("5"));
+        additional.insert("email_verified".to_string(), json!("true"));
+        additional.insert("auth_time".to_string(), json!(1700000000));
+        additional.insert("ip".to_string(), json!("192.168.1.10"));
+
+        let claims = ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec!["client-id".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            additional_claims: additional,
+        };
+
+        let mut config = ClaimsMappingConfig::default();
+        for (attr, claim, attr_type) in [
+            ("level", "custom:level", "Long"),
+            ("verified", "email_verified", "Bool"),
+            ("auth_time", "auth_time", "datetime"),
+            ("ip", "ip", "ipaddr"),
+        ] {
+            config
+                .attribute_mappings
+                .insert(attr.to_string(), claim.to_string());
+            config
+                .attribute_types
+                .insert(attr.to_string(), attr_type.parse().unwrap());
+        }
+        config
+            .attribute_mappings
+            .insert("raw_level".to_string(), "custom:level".to_string());
+
+        let (_, entities) = ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap();
+        let attribute =
+            |name: &str| serde_json::from_str::<Value>(&entities[0].attributes[name]).unwrap();
+        assert_eq!(attribute("level"), json!(5));
+        assert_eq!(attribute("raw_level"), json!("5"));
+        assert_eq!(attribute("verified"), json!(true));
+        assert_eq!(
+            attribute("auth_time"),
+            json!({"__extn": {"fn": "datetime", "arg": "2023-11-14T22:13:20.000Z"}})
+        );
+        assert_eq!(
+            attribute("ip"),
+            json!({"__extn": {"fn": "ip", "arg": "192.168.1.10"}})
+        );
+
+        config
+            .attribute_types
+            .insert("raw_level".to_string(), AttributeType::Bool);
+        let error = ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap_err();
+        assert!(error.to_string().contains("'raw_level' of type Bool"));
+    }
+
+    #[test]
+    fn test_group_entity_type() {
+        let claims = create_test_claims();
+        let config = ClaimsMappingConfig {
+            principal_entity_type: "MyApp::Employee".to_string(),
+            group_entity_type: "MyApp::Team".to_string(),
+            ..Default::default()
+        };
+
+        let (principal, entities) =
+            ClaimsMapper::map_to_principal(&claims, &config, &config.principal_entity_type)
+                .unwrap();
+
+        assert_eq!(principal.entity_type, "MyApp::Employee");
+        assert!(
+            entities[0]
+                .parents
+                .iter()
+                .all(|p| p.entity_type == "MyApp::Team")
+        );
+        assert_eq!(entities[0].parents.len(), 2);
+    }
+
+    #[test]
+    fn test_context_claims_all_and_typed() {
+        let mut additional = HashMap::new();
+        additional.insert("scope".to_string(), json!("docs:read docs:write"));
+        additional.insert("amr".to_string(), json!(["pwd", "mfa"]));
+        additional.insert("mfa".to_string(), json!(true));
+        additional.insert("ratio".to_string(), json!(0.5));
+        additional.insert("nickname".to_string(), json!(null));
+
+        let claims = ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec!["client-id".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            additional_claims: additional,
+        };
+
+        let context = ClaimsMapper::context_claims(&claims, &ContextClaimsConfig::default());
+
+        assert_eq!(context["sub"], json!("user123"));
+        assert_eq!(context["aud"], json!(["client-id"]));
+        assert_eq!(context["exp"], json!(9999999999i64));
+        assert_eq!(context["scope"], json!(["docs:read", "docs:write"]));
+        assert_eq!(context["amr"], json!(["pwd", "mfa"]));
+        assert_eq!(context["mfa"], json!(true));
+        assert!(context.get("ratio").is_none());
+        assert!(context.get("nickname").is_none());
+    }
+
+    #[test]
+    fn test_context_claims_selected() {
+        let mut additional = HashMap::new();
+        additional.insert("realm_access".to_string(), json!({"roles": ["admin"]}));
+        additional.insert("email".to_string(), json!("user@example.com"));
+
+        let claims = ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec![],
+            exp: 9999999999,
+            iat: 1234567890,
+            additional_claims: additional,
+        };
+
+        let config = ContextClaimsConfig {
+            context_key: "token".to_string(),
+            claims: vec![
+                "iss".to_string(),
+                "realm_access.roles".to_string(),
+                "missing".to_string(),
+            ],
+        };
+        let context = ClaimsMapper::context_claims(&claims, &config);
+
+        assert_eq!(
+            context,
+            json!({
+                "iss": "https://issuer.example.com",
+                "realm_access.roles": ["admin"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_from_json() {
+        let json = r#"{
+            "principal_id_claim": "preferred_username",
+            "principal_entity_type": "Employee",
+            "attribute_mappings": {"mail": "email"},
+            "parent_mappings": [
+                {"claim_path": "groups", "entity_type": "Group", "transform": {"type": "SplitLast", "value": "/"}}
+            ]
+        }"#;
+
+        let config = ClaimsMappingConfig::from_json(json).unwrap();
+        assert_eq!(config.principal_id_claim, "preferred_username");
+        assert_eq!(config.principal_entity_type, "Employee");
+        assert_eq!(config.group_claim, Some("groups".to_string()));
+        assert_eq!(
+            config.attribute_mappings.get("mail"),
+            Some(&"email".to_string())
+        );
+        assert_eq!(config.parent_mappings.len(), 1);
+        assert_eq!(
+            config.parent_mappings[0].transform.apply("/a/b").unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn test_config_from_legacy_json_with_empty_fields() {
+        let json = r#"{"principal_id_claim":"","group_claim":"","attribute_mappings":{}}"#;
+
+        let config = ClaimsMappingConfig::from_json(json).unwrap();
+        assert_eq!(config.principal_id_claim, "sub");
+        assert_eq!(config.principal_entity_type, "User");
+        assert_eq!(config.group_claim, None);
+        assert_eq!(config.group_entity_type, "Role");
+        assert!(config.parent_mappings.is_empty());
+        assert_eq!(
+            config.attribute_mappings.get("email"),
+            Some(&"email".to_string())
+        );
+    }
 }
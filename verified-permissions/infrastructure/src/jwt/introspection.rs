@@ -0,0 +1,457 @@
+//! OAuth 2.0 token introspection (RFC 7662)
+//!
+//! Opaque access tokens cannot be verified locally, so the authorization
+//! server's introspection endpoint is asked about them. Responses are cached
+//! per endpoint and token: active ones for the configured TTL, but never
+//! past the token's `exp`; inactive ones for `negative_ttl`. The response
+//! members become the token claims, so introspected tokens go through the
+//! same validation policy and claims mapping as JWTs.
+
+use crate::error::{AuthorizationError, Result};
+use crate::jwt::validation_policy::TokenValidationPolicy;
+use crate::jwt::ValidatedClaims;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// Default time an active introspection response is cached, in seconds
+pub const DEFAULT_INTROSPECTION_CACHE_TTL_SECONDS: u64 = 300;
+
+/// Introspection endpoint of an identity source and its client credentials
+#[derive(Debug, Clone)]
+pub struct IntrospectionEndpoint {
+    /// URL of the introspection endpoint
+    pub url: String,
+    /// Client ID used to authenticate to the endpoint (HTTP Basic)
+    pub client_id: String,
+    /// Client secret used to authenticate to the endpoint (HTTP Basic)
+    pub client_secret: String,
+    /// Expected `iss`; also the issuer of responses without one
+    pub issuer: String,
+    /// Longest time an active response is cached
+    pub cache_ttl: Duration,
+}
+
+/// Configuration for the introspection client
+#[derive(Debug, Clone)]
+pub struct TokenIntrospectorConfig {
+    /// HTTP request timeout (default: 10 seconds)
+    pub request_timeout: Duration,
+    /// How long an inactive response is cached (default: 30 seconds)
+    pub negative_ttl: Duration,
+    /// Maximum number of cached responses (default: 10000)
+    pub max_entries: usize,
+}
+
+impl Default for TokenIntrospectorConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(10),
+            negative_ttl: Duration::from_secs(30),
+            max_entries: 10_000,
+        }
+    }
+}
+
+/// Cached introspection response
+struct CachedResponse {
+    response: Value,
+    expires_at: Instant,
+}
+
+/// Introspects opaque tokens and caches the responses
+pub struct TokenIntrospector {
+    /// HTTP client for the introspection requests
+    client: reqwest::Client,
+    /// Responses by (endpoint URL, token)
+    cache: RwLock<HashMap<(String, String), CachedResponse>>,
+    /// Configuration
+    config: TokenIntrospectorConfig,
+}
+
+impl TokenIntrospector {
+    /// Create a new introspection client with default configuration
+    pub fn new() -> Self {
+        Self::with_config(TokenIntrospectorConfig::default())
+    }
+
+    /// Create a new introspection client with custom configuration
+    pub fn with_config(config: TokenIntrospectorConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            cache: RwLock::new(HashMap::new()),
+            config,
+        }
+    }
+
+    /// Validate an opaque token through the endpoint's introspection
+    ///
+    /// The token must be active, and the response must name the expected
+    /// issuer when it has an `iss`. `policy` then checks the response like
+    /// the claims of a JWT, with `client_ids` matched against `aud` or
+    /// `client_id` as the policy says.
+    pub async fn introspect(
+        &self,
+        token: &str,
+        endpoint: &IntrospectionEndpoint,
+        client_ids: &[String],
+        policy: &TokenValidationPolicy,
+    ) -> Result<ValidatedClaims> {
+        let response = match self.cached(&endpoint.url, token).await {
+            Some(response) => {
+                debug!("Introspection cache hit for endpoint: {}", endpoint.url);
+                response
+            }
+            None => {
+                let response = self.request(token, endpoint).await?;
+                self.store(token, endpoint, &response).await;
+                response
+            }
+        };
+
+        let claims = Self::claims_from_response(
+            response,
+            &endpoint.issuer,
+            policy,
+            jsonwebtoken::get_current_timestamp() as i64,
+        )?;
+        policy.check_claims(&claims, client_ids)?;
+        Ok(claims)
+    }
+
+    /// Number of cached responses
+    pub async fn cached_tokens_count(&self) -> usize {
+        self.cache.read().await.len()
+    }
+
+    /// Clear all cached responses
+    pub async fn clear(&self) {
+        self.cache.write().await.clear();
+    }
+
+    async fn cached(&self, url: &str, token: &str) -> Option<Value> {
+        let cache = self.cache.read().await;
+        cache
+            .get(&(url.to_string(), token.to_string()))
+            .filter(|cached| cached.expires_at > Instant::now())
+            .map(|cached| cached.response.clone())
+    }
+
+    /// Caches a response until the endpoint's TTL or the token's `exp`,
+    /// whichever comes first
+    async fn store(&self, token: &str, endpoint: &IntrospectionEndpoint, response: &Value) {
+        let ttl = if response["active"].as_bool() == Some(true) {
+            match response["exp"].as_i64() {
+                Some(exp) => {
+                    let remaining = exp - jsonwebtoken::get_current_timestamp() as i64;
+                    endpoint
+                        .cache_ttl
+                        .min(Duration::from_secs(remaining.max(0) as u64))
+                }
+                None => endpoint.cache_ttl,
+            }
+        } else {
+            self.config.negative_ttl
+        };
+        if ttl.is_zero() {
+            return;
+        }
+
+        let mut cache = self.cache.write().await;
+        if cache.len() >= self.config.max_entries {
+            let now = Instant::now();
+            cache.retain(|_, cached| cached.expires_at > now);
+            if cache.len() >= self.config.max_entries {
+                warn!(
+                    "Introspection cache full ({} entries), clearing it",
+                    cache.len()
+                );
+                cache.clear();
+            }
+        }
+        cache.insert(
+            (endpoint.url.clone(), token.to_string()),
+            CachedResponse {
+                response: response.clone(),
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+
+    /// Posts the token to the introspection endpoint
+    async fn request(&self, token: &str, endpoint: &IntrospectionEndpoint) -> Result<Value> {
+        debug!("Introspecting token at: {}", endpoint.url);
+
+        let response = self
+            .client
+            .post(&endpoint.url)
+            .basic_auth(&endpoint.client_id, Some(&endpoint.client_secret))
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await
+            .map_err(|e| {
+                AuthorizationError::Internal(format!("Token introspection failed: {}", e))
+            })?;
+
+        if !response.status().is_success() {
+            return Err(AuthorizationError::Internal(format!(
+                "Token introspection failed: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let body: Value = response.json().await.map_err(|e| {
+            AuthorizationError::Internal(format!("Invalid introspection response: {}", e))
+        })?;
+        if !body.is_object() {
+            return Err(AuthorizationError::Internal(
+                "Invalid introspection response: not a JSON object".to_string(),
+            ));
+        }
+        Ok(body)
+    }
+
+    /// Turns an introspection response into token claims
+    ///
+    /// `sub` falls back to `username`, and `iss` to the expected issuer.
+    /// Without `iat` the token counts as issued now; without `exp` its `exp`
+    /// is 0.
+    fn claims_from_response(
+        response: Value,
+        issuer: &str,
+        policy: &TokenValidationPolicy,
+        now: i64,
+    ) -> Result<ValidatedClaims> {
+        let Value::Object(mut members) = response else {
+            return Err(rejected(
+                "introspection response is not an object".to_string(),
+            ));
+        };
+        if members.remove("active").and_then(|v| v.as_bool()) != Some(true) {
+            return Err(rejected("token is not active".to_string()));
+        }
+
+        match members.get("iss").and_then(Value::as_str) {
+            Some(iss) if iss.trim_end_matches('/') != issuer.trim_end_matches('/') => {
+                return Err(rejected(format!("unexpected issuer '{}'", iss)));
+            }
+            Some(_) => {}
+            None => {
+                members.insert("iss".to_string(), Value::from(issuer));
+            }
+        }
+        if !members.get("sub").is_some_and(Value::is_string) {
+            let username = members
+                .get("username")
+                .and_then(Value::as_str)
+                .ok_or_else(|| rejected("response has no 'sub' or 'username'".to_string()))?;
+            members.insert("sub".to_string(), Value::from(username));
+        }
+
+        // The endpoint vouches for the token, but its time claims are
+        // still held to the policy's leeway
+        let skew = policy.clock_skew_seconds as i64;
+        let time = |name: &str| members.get(name).and_then(Value::as_i64);
+        if time("exp").is_some_and(|exp| exp + skew < now) {
+            return Err(rejected("token has expired (exp)".to_string()));
+        }
+        if policy.validate_nbf && time("nbf").is_some_and(|nbf| nbf > now + skew) {
+            return Err(rejected("token is not valid yet (nbf)".to_string()));
+        }
+        members.entry("exp").or_insert(Value::from(0));
+        members.entry("iat").or_insert(Value::from(now));
+
+        serde_json::from_value(Value::Object(members))
+            .map_err(|e| rejected(format!("invalid introspection response: {}", e)))
+    }
+}
+
+impl Default for TokenIntrospector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn rejected(reason: String) -> AuthorizationError {
+    AuthorizationError::Unauthenticated(format!("Token rejected: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::test_support::TestServer;
+    use crate::jwt::AudienceCheck;
+    use serde_json::json;
+
+    fn endpoint(server: &TestServer) -> IntrospectionEndpoint {
+        IntrospectionEndpoint {
+            url: format!("{}/introspect", server.url),
+            client_id: "resource-server".to_string(),
+            client_secret: "resource-secret".to_string(),
+            issuer: "https://auth.example.com".to_string(),
+            cache_ttl: Duration::from_secs(300),
+        }
+    }
+
+    fn now() -> i64 {
+        jsonwebtoken::get_current_timestamp() as i64
+    }
+
+    #[tokio::test]
+    async fn test_active_response_becomes_claims() {
+        let server = TestServer::start().await;
+        server.route(
+            "/introspect",
+            json!({
+                "active": true,
+                "username": "alice",
+                "client_id": "my-app",
+                "scope": "read write",
+                "exp": now() + 600,
+                "groups": ["admins"]
+            }),
+        );
+
+        let introspector = TokenIntrospector::new();
+        let policy = TokenValidationPolicy {
+            audience_check: Some(AudienceCheck::AudOrClientId),
+            ..Default::default()
+        };
+        let claims = introspector
+            .introspect(
+                "opaque-token",
+                &endpoint(&server),
+                &["my-app".to_string()],
+                &policy,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(claims.sub, "alice");
+        assert_eq!(claims.iss, "https://auth.example.com");
+        assert_eq!(claims.additional_claims["scope"], json!("read write"));
+        assert_eq!(claims.additional_claims["groups"], json!(["admins"]));
+        assert!(!claims.additional_claims.contains_key("active"));
+
+        // The endpoint is called with the client credentials (HTTP Basic)
+        let request = server.last_request().unwrap();
+        assert!(request.starts_with("POST /introspect"));
+        assert!(request.contains("cmVzb3VyY2Utc2VydmVyOnJlc291cmNlLXNlY3JldA=="));
+
+        // Client IDs are checked like for JWTs
+        let rejected = introspector
+            .introspect(
+                "opaque-token",
+                &endpoint(&server),
+                &["other-app".to_string()],
+                &policy,
+            )
+            .await;
+        assert!(rejected.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inactive_or_foreign_tokens_are_rejected() {
+        let server = TestServer::start().await;
+        let introspector = TokenIntrospector::new();
+        let policy = TokenValidationPolicy::default();
+
+        server.route("/introspect", json!({"active": false}));
+        let err = introspector
+            .introspect("revoked", &endpoint(&server), &[], &policy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthorizationError::Unauthenticated(_)));
+
+        server.route(
+            "/introspect",
+            json!({"active": true, "sub": "alice", "iss": "https://other.example.com"}),
+        );
+        assert!(introspector
+            .introspect("foreign", &endpoint(&server), &[], &policy)
+            .await
+            .is_err());
+
+        server.route(
+            "/introspect",
+            json!({"active": true, "sub": "alice", "exp": now() - 3600}),
+        );
+        assert!(introspector
+            .introspect("expired", &endpoint(&server), &[], &policy)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_cached_until_exp() {
+        let server = TestServer::start().await;
+        let introspector = TokenIntrospector::new();
+        let policy = TokenValidationPolicy::default();
+
+        server.route(
+            "/introspect",
+            json!({"active": true, "sub": "alice", "exp": now() + 600}),
+        );
+        for _ in 0..3 {
+            introspector
+                .introspect("long-lived", &endpoint(&server), &[], &policy)
+                .await
+                .unwrap();
+        }
+        assert_eq!(server.requests(), 1);
+
+        // A token expiring now is never cached
+        server.route(
+            "/introspect",
+            json!({"active": true, "sub": "alice", "exp": now()}),
+        );
+        for _ in 0..2 {
+            introspector
+                .introspect("expiring", &endpoint(&server), &[], &policy)
+                .await
+                .unwrap();
+        }
+        assert_eq!(server.requests(), 3);
+
+        // Inactive responses are cached too
+        server.route("/introspect", json!({"active": false}));
+        for _ in 0..2 {
+            assert!(introspector
+                .introspect("revoked", &endpoint(&server), &[], &policy)
+                .await
+                .is_err());
+        }
+        assert_eq!(server.requests(), 4);
+        assert_eq!(introspector.cached_tokens_count().await, 2);
+    }
+
+    #[test]
+    fn test_claims_from_response_defaults() {
+        let policy = TokenValidationPolicy::default();
+        let claims = TokenIntrospector::claims_from_response(
+            json!({"active": true, "sub": "alice", "aud": "api"}),
+            "https://auth.example.com",
+            &policy,
+            1_700_000_000,
+        )
+        .unwrap();
+        assert_eq!(claims.aud, vec!["api".to_string()]);
+        assert_eq!(claims.iat, 1_700_000_000);
+        assert_eq!(claims.exp, 0);
+
+        assert!(TokenIntrospector::claims_from_response(
+            json!({"active": true}),
+            "https://auth.example.com",
+            &policy,
+            1_700_000_000,
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,10 @@
 //! JWKS Cache with auto-refresh and TTL
+//!
+//! Keys are cached per issuer. A token signed with an unknown key ID triggers
+//! a refresh (to pick up rotated keys), at most once per
+//! `min_refresh_interval` for each issuer. Key IDs still unknown after a
+//! refresh are remembered for `negative_ttl`, so a flood of tokens with
+//! made-up key IDs cannot turn into a flood of JWKS requests.
 
 use crate::error::{AuthorizationError, Result};
 use jsonwebtoken::DecodingKey;
@@ -6,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info, warn};
 
 /// OIDC Discovery document
@@ -30,12 +36,55 @@ pub struct Jwks {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Jwk {
     pub kty: String,
-    pub kid: String,
+    /// Key ID; keys without one cannot be selected and are skipped
+    #[serde(default)]
+    pub kid: Option<String>,
     #[serde(rename = "use")]
     pub key_use: Option<String>,
     pub alg: Option<String>,
+    /// RSA modulus
     pub n: Option<String>,
+    /// RSA exponent
     pub e: Option<String>,
+    /// Curve of EC (`P-256`, `P-384`) and OKP (`Ed25519`) keys
+    #[serde(default)]
+    pub crv: Option<String>,
+    /// EC x coordinate, or the OKP public key
+    #[serde(default)]
+    pub x: Option<String>,
+    /// EC y coordinate
+    #[serde(default)]
+    pub y: Option<String>,
+}
+
+impl Jwk {
+    /// Signature verification key of this JWK
+    ///
+    /// Supports RSA, EC (P-256, P-384) and OKP (Ed25519) keys. Returns
+    /// `Ok(None)` for keys that cannot verify token signatures: encryption
+    /// keys, symmetric (`oct`) keys and unsupported key types or curves.
+    pub fn decoding_key(&self) -> jsonwebtoken::errors::Result<Option<DecodingKey>> {
+        if self.key_use.as_deref() == Some("enc") {
+            return Ok(None);
+        }
+
+        let key = match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => match (&self.n, &self.e) {
+                (Some(n), Some(e)) => DecodingKey::from_rsa_components(n, e)?,
+                _ => return Ok(None),
+            },
+            ("EC", Some("P-256" | "P-384")) => match (&self.x, &self.y) {
+                (Some(x), Some(y)) => DecodingKey::from_ec_components(x, y)?,
+                _ => return Ok(None),
+            },
+            ("OKP", Some("Ed25519")) => match &self.x {
+                Some(x) => DecodingKey::from_ed_components(x)?,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(key))
+    }
 }
 
 /// Cached JWKS entry with metadata
@@ -65,6 +114,13 @@ pub struct JwksCacheConfig {
     pub refresh_interval: Duration,
     /// HTTP request timeout (default: 10 seconds)
     pub request_timeout: Duration,
+    /// Minimum time between refreshes of an issuer triggered by unknown key
+    /// IDs (default: 30 seconds)
+    pub min_refresh_interval: Duration,
+    /// How long an unknown key ID is remembered (default: 1 minute)
+    pub negative_ttl: Duration,
+    /// Maximum number of remembered unknown key IDs (default: 10000)
+    pub max_negative_entries: usize,
 }
 
 impl Default for JwksCacheConfig {
@@ -73,6 +129,9 @@ impl Default for JwksCacheConfig {
             ttl: Duration::from_secs(3600),           // 1 hour
             refresh_interval: Duration::from_secs(1800), // 30 minutes
             request_timeout: Duration::from_secs(10),
+            min_refresh_interval: Duration::from_secs(30),
+            negative_ttl: Duration::from_secs(60),
+            max_negative_entries: 10_000,
         }
     }
 }
@@ -83,6 +142,10 @@ pub struct JwksCache {
     client: reqwest::Client,
     /// Cache of JWKS by issuer
     cache: Arc<RwLock<HashMap<String, CachedJwks>>>,
+    /// Unknown key IDs by (issuer, kid), with the time they were seen
+    unknown_kids: RwLock<HashMap<(String, String), Instant>>,
+    /// One refresh at a time per issuer
+    refresh_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
     /// Configuration
     config: JwksCacheConfig,
     /// Metrics
@@ -96,6 +159,10 @@ pub struct CacheMetrics {
     pub misses: u64,
     pub refreshes: u64,
     pub errors: u64,
+    /// Lookups rejected because the key ID is remembered as unknown
+    pub negative_hits: u64,
+    /// Refreshes skipped because the issuer was refreshed too recently
+    pub rate_limited: u64,
 }
 
 impl CacheMetrics {
@@ -125,55 +192,139 @@ impl JwksCache {
         Self {
             client,
             cache: Arc::new(RwLock::new(HashMap::new())),
+            unknown_kids: RwLock::new(HashMap::new()),
+            refresh_locks: Mutex::new(HashMap::new()),
             config,
             metrics: Arc::new(RwLock::new(CacheMetrics::default())),
         }
     }
 
     /// Get a decoding key for a specific key ID and issuer
+    ///
+    /// The JWKS URI is discovered from the issuer.
     pub async fn get_key(&self, kid: &str, issuer: &str) -> Result<DecodingKey> {
+        self.get_key_from(kid, issuer, None).await
+    }
+
+    /// Get a decoding key for a specific key ID and issuer
+    ///
+    /// Keys are fetched from `jwks_uri` when given, else from the JWKS URI
+    /// in the issuer's OIDC discovery document.
+    pub async fn get_key_from(
+        &self,
+        kid: &str,
+        issuer: &str,
+        jwks_uri: Option<&str>,
+    ) -> Result<DecodingKey> {
         // Check cache first
-        {
+        let expired = {
             let cache = self.cache.read().await;
-            if let Some(cached) = cache.get(issuer) {
-                // Check if cache is still valid
-                if cached.fetched_at.elapsed() < self.config.ttl {
+            match cache.get(issuer) {
+                Some(cached) if cached.fetched_at.elapsed() < self.config.ttl => {
                     if let Some(key) = cached.keys.get(kid) {
                         debug!("JWKS cache hit for issuer: {}, kid: {}", issuer, kid);
-                        let mut metrics = self.metrics.write().await;
-                        metrics.hits += 1;
+                        self.metrics.write().await.hits += 1;
                         return Ok(key.clone());
                     }
+                    false
                 }
+                _ => true,
             }
+        };
+
+        // Key IDs that were missing from a recent refresh fail fast
+        if !expired && self.is_unknown_kid(issuer, kid).await {
+            debug!("Unknown kid {} for issuer {} (negative cache)", kid, issuer);
+            self.metrics.write().await.negative_hits += 1;
+            return Err(Self::unknown_kid_error(kid, issuer));
         }
 
         debug!("JWKS cache miss for issuer: {}, kid: {}", issuer, kid);
-        let mut metrics = self.metrics.write().await;
-        metrics.misses += 1;
-        drop(metrics);
+        self.metrics.write().await.misses += 1;
 
         // Cache miss or expired - fetch and update
-        self.refresh_keys(issuer).await?;
+        self.refresh_if_due(issuer, jwks_uri).await?;
 
         // Try again from cache
-        let cache = self.cache.read().await;
-        cache
-            .get(issuer)
-            .and_then(|cached| cached.keys.get(kid))
-            .cloned()
-            .ok_or_else(|| {
-                AuthorizationError::Internal(format!(
-                    "Key with kid '{}' not found for issuer '{}'",
-                    kid, issuer
-                ))
+        let key = {
+            let cache = self.cache.read().await;
+            cache
+                .get(issuer)
+                .and_then(|cached| cached.keys.get(kid))
+                .cloned()
+        };
+        match key {
+            Some(key) => Ok(key),
+            None => {
+                self.remember_unknown_kid(issuer, kid).await;
+                Err(Self::unknown_kid_error(kid, issuer))
+            }
+        }
+    }
+
+    fn unknown_kid_error(kid: &str, issuer: &str) -> AuthorizationError {
+        AuthorizationError::Unauthenticated(format!(
+            "Key with kid '{}' not found for issuer '{}'",
+            kid, issuer
+        ))
+    }
+
+    async fn is_unknown_kid(&self, issuer: &str, kid: &str) -> bool {
+        let unknown_kids = self.unknown_kids.read().await;
+        unknown_kids
+            .get(&(issuer.to_string(), kid.to_string()))
+            .is_some_and(|seen| seen.elapsed() < self.config.negative_ttl)
+    }
+
+    async fn remember_unknown_kid(&self, issuer: &str, kid: &str) {
+        let mut unknown_kids = self.unknown_kids.write().await;
+        if unknown_kids.len() >= self.config.max_negative_entries {
+            let negative_ttl = self.config.negative_ttl;
+            unknown_kids.retain(|_, seen| seen.elapsed() < negative_ttl);
+            if unknown_kids.len() >= self.config.max_negative_entries {
+                warn!(
+                    "Unknown kid cache full ({} entries), clearing it",
+                    unknown_kids.len()
+                );
+                unknown_kids.clear();
+            }
+        }
+        unknown_kids.insert((issuer.to_string(), kid.to_string()), Instant::now());
+    }
+
+    /// Refreshes the keys of an issuer unless they were fetched less than
+    /// `min_refresh_interval` ago
+    ///
+    /// Concurrent callers for the same issuer wait for a single refresh.
+    async fn refresh_if_due(&self, issuer: &str, jwks_uri: Option<&str>) -> Result<()> {
+        let lock = {
+            let mut locks = self.refresh_locks.lock().await;
+            locks.entry(issuer.to_string()).or_default().clone()
+        };
+        let _guard = lock.lock().await;
+
+        let recently_fetched = {
+            let cache = self.cache.read().await;
+            cache.get(issuer).is_some_and(|cached| {
+                cached.fetched_at.elapsed() < self.config.min_refresh_interval
             })
+        };
+        if recently_fetched {
+            debug!("Skipping JWKS refresh for issuer {} (rate limited)", issuer);
+            self.metrics.write().await.rate_limited += 1;
+            return Ok(());
+        }
+
+        match jwks_uri {
+            Some(jwks_uri) => self.refresh_keys_from(issuer, jwks_uri).await,
+            None => self.refresh_keys(issuer).await,
+        }
     }
 
     /// Discover JWKS URI from OIDC issuer
     pub async fn discover_jwks_uri(&self, issuer: &str) -> Result<String> {
         let discovery_url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
-        
+
         debug!("Discovering OIDC configuration from: {}", discovery_url);
 
         let response = self
@@ -199,52 +350,100 @@ impl JwksCache {
             AuthorizationError::Internal(format!("Failed to parse OIDC discovery document: {}", e))
         })?;
 
+        // The document must describe the issuer it was fetched for
+        if discovery.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(AuthorizationError::Internal(format!(
+                "OIDC discovery document issuer '{}' does not match '{}'",
+                discovery.issuer, issuer
+            )));
+        }
+
         info!("Discovered JWKS URI: {} for issuer: {}", discovery.jwks_uri, issuer);
         Ok(discovery.jwks_uri)
     }
 
     /// Refresh keys for a specific issuer
+    ///
+    /// Keys are fetched again from the JWKS URI the issuer was cached with,
+    /// or from the one found through OIDC discovery.
     pub async fn refresh_keys(&self, issuer: &str) -> Result<()> {
-        info!("Refreshing JWKS for issuer: {}", issuer);
+        let cached_uri = {
+            let cache = self.cache.read().await;
+            cache.get(issuer).map(|cached| cached.jwks_uri.clone())
+        };
+
+        let jwks_uri = match cached_uri {
+            Some(jwks_uri) => jwks_uri,
+            None => match self.discover_jwks_uri(issuer).await {
+                Ok(jwks_uri) => jwks_uri,
+                Err(e) => {
+                    self.metrics.write().await.errors += 1;
+                    return Err(e);
+                }
+            },
+        };
+
+        self.refresh_keys_from(issuer, &jwks_uri).await
+    }
 
-        // Discover JWKS URI
-        let jwks_uri = self.discover_jwks_uri(issuer).await?;
+    /// Refresh keys for a specific issuer from a known JWKS URI
+    pub async fn refresh_keys_from(&self, issuer: &str, jwks_uri: &str) -> Result<()> {
+        info!("Refreshing JWKS for issuer: {}", issuer);
 
         // Fetch JWKS
-        let jwks = self.fetch_jwks(&jwks_uri).await?;
+        let jwks = match self.fetch_jwks(jwks_uri).await {
+            Ok(jwks) => jwks,
+            Err(e) => {
+                self.metrics.write().await.errors += 1;
+                return Err(e);
+            }
+        };
 
         // Convert to decoding keys
         let mut keys = HashMap::new();
         for jwk in jwks.keys {
-            if let (Some(n), Some(e)) = (jwk.n, jwk.e) {
-                match DecodingKey::from_rsa_components(&n, &e) {
-                    Ok(key) => {
-                        keys.insert(jwk.kid.clone(), key);
-                        debug!("Cached key: {} for issuer: {}", jwk.kid, issuer);
-                    }
-                    Err(e) => {
-                        warn!("Failed to create decoding key for kid {}: {}", jwk.kid, e);
-                        let mut metrics = self.metrics.write().await;
-                        metrics.errors += 1;
-                    }
+            let Some(kid) = jwk.kid.clone() else {
+                debug!("Skipping JWK without kid for issuer: {}", issuer);
+                continue;
+            };
+            match jwk.decoding_key() {
+                Ok(Some(key)) => {
+                    debug!("Cached {} key: {} for issuer: {}", jwk.kty, kid, issuer);
+                    keys.insert(kid, key);
+                }
+                Ok(None) => {
+                    debug!(
+                        "Skipping unsupported {} key: {} for issuer: {}",
+                        jwk.kty, kid, issuer
+                    );
+                }
+                Err(e) => {
+                    warn!("Failed to create decoding key for kid {}: {}", kid, e);
+                    let mut metrics = self.metrics.write().await;
+                    metrics.errors += 1;
                 }
             }
         }
 
         // Update cache
+        let count = keys.len();
         let cached = CachedJwks {
             keys,
             fetched_at: Instant::now(),
-            jwks_uri,
+            jwks_uri: jwks_uri.to_string(),
         };
 
         let mut cache = self.cache.write().await;
         cache.insert(issuer.to_string(), cached);
+        drop(cache);
 
         let mut metrics = self.metrics.write().await;
         metrics.refreshes += 1;
 
-        info!("Successfully refreshed {} keys for issuer: {}", cache.get(issuer).map(|c| c.keys.len()).unwrap_or(0), issuer);
+        info!(
+            "Successfully refreshed {} keys for issuer: {}",
+            count, issuer
+        );
         Ok(())
     }
 
@@ -278,13 +477,13 @@ impl JwksCache {
     /// Start background refresh task for all cached issuers
     pub fn start_background_refresh(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
         let interval = self.config.refresh_interval;
-        
+
         tokio::spawn(async move {
             let mut ticker = tokio::time::interval(interval);
-            
+
             loop {
                 ticker.tick().await;
-                
+
                 let issuers: Vec<String> = {
                     let cache = self.cache.read().await;
                     cache.keys().cloned().collect()
@@ -293,10 +492,15 @@ impl JwksCache {
                 for issuer in issuers {
                     if let Err(e) = self.refresh_keys(&issuer).await {
                         warn!("Background refresh failed for issuer {}: {}", issuer, e);
-                        let mut metrics = self.metrics.write().await;
-                        metrics.errors += 1;
                     }
                 }
+
+                // Forget unknown key IDs that have expired
+                let negative_ttl = self.config.negative_ttl;
+                self.unknown_kids
+                    .write()
+                    .await
+                    .retain(|_, seen| seen.elapsed() < negative_ttl);
             }
         })
     }
@@ -310,6 +514,7 @@ impl JwksCache {
     pub async fn clear(&self) {
         let mut cache = self.cache.write().await;
         cache.clear();
+        self.unknown_kids.write().await.clear();
         info!("JWKS cache cleared");
     }
 
@@ -329,6 +534,7 @@ impl Default for JwksCache {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jwt::test_support::{TEST_KID, TestServer, test_jwks};
 
     #[test]
     fn test_cache_config_defaults() {
@@ -336,6 +542,8 @@ mod tests {
         assert_eq!(config.ttl, Duration::from_secs(3600));
         assert_eq!(config.refresh_interval, Duration::from_secs(1800));
         assert_eq!(config.request_timeout, Duration::from_secs(10));
+        assert_eq!(config.min_refresh_interval, Duration::from_secs(30));
+        assert_eq!(config.negative_ttl, Duration::from_secs(60));
     }
 
     #[test]
@@ -368,4 +576,90 @@ mod tests {
         assert_eq!(metrics.hits, 0);
         assert_eq!(metrics.misses, 0);
     }
+
+    #[tokio::test]
+    async fn test_get_key_from_jwks_uri() {
+        let server = TestServer::start().await;
+        server.route("/jwks", test_jwks());
+        let jwks_uri = format!("{}/jwks", server.url);
+
+        let cache = JwksCache::new();
+        assert!(cache.get_key_from(TEST_KID, &server.url, Some(&jwks_uri)).await.is_ok());
+        assert!(cache.get_key_from(TEST_KID, &server.url, Some(&jwks_uri)).await.is_ok());
+
+        let metrics = cache.metrics().await;
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.refreshes, 1);
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_kid_is_negatively_cached_and_rate_limited() {
+        let server = TestServer::start().await;
+        server.route("/jwks", test_jwks());
+        let jwks_uri = format!("{}/jwks", server.url);
+
+        let cache = JwksCache::with_config(JwksCacheConfig {
+            min_refresh_interval: Duration::ZERO,
+            ..Default::default()
+        });
+        cache
+            .get_key_from(TEST_KID, &server.url, Some(&jwks_uri))
+            .await
+            .unwrap();
+
+        // An unknown kid refreshes the keys once, then fails from the negative cache
+        assert!(cache.get_key_from("rotated", &server.url, Some(&jwks_uri)).await.is_err());
+        assert_eq!(server.requests(), 2);
+        assert!(cache.get_key_from("rotated", &server.url, Some(&jwks_uri)).await.is_err());
+        assert_eq!(server.requests(), 2);
+        assert_eq!(cache.metrics().await.negative_hits, 1);
+
+        // Other unknown kids within the minimum refresh interval do not refetch
+        let cache = JwksCache::new();
+        cache
+            .get_key_from(TEST_KID, &server.url, Some(&jwks_uri))
+            .await
+            .unwrap();
+        let before = server.requests();
+        for kid in ["forged-1", "forged-2", "forged-3"] {
+            assert!(cache.get_key_from(kid, &server.url, Some(&jwks_uri)).await.is_err());
+        }
+        assert_eq!(server.requests(), before);
+        assert_eq!(cache.metrics().await.rate_limited, 3);
+    }
+
+    #[tokio::test]
+    async fn test_jwks_uri_from_discovery() {
+        let server = TestServer::start().await;
+        server.route(
+            "/.well-known/openid-configuration",
+            serde_json::json!({
+                "issuer": server.url,
+                "jwks_uri": format!("{}/jwks", server.url),
+            }),
+        );
+        server.route("/jwks", test_jwks());
+
+        let cache = JwksCache::new();
+        assert!(cache.get_key(TEST_KID, &server.url).await.is_ok());
+        assert_eq!(cache.cached_issuers_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_discovery_rejects_other_issuer() {
+        let server = TestServer::start().await;
+        server.route(
+            "/.well-known/openid-configuration",
+            serde_json::json!({
+                "issuer": "https://attacker.example.com",
+                "jwks_uri": format!("{}/jwks", server.url),
+            }),
+        );
+
+        let cache = JwksCache::new();
+        let error = cache.discover_jwks_uri(&server.url).await.unwrap_err();
+        assert!(error.to_string().contains("does not match"));
+    }
 }
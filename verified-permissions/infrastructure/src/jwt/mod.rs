@@ -4,17 +4,29 @@
 //! for use in authorization decisions.
 
 pub mod validator;
+pub mod algorithms;
+pub mod validation_policy;
 pub mod transforms;
+pub mod claim_path;
 pub mod jwks_cache;
+pub mod introspection;
+pub mod sender_constraint;
 pub mod claims_mapper;
+pub mod attribute_types;
 pub mod providers;
 pub mod issuer_detection;
 
 pub use validator::JwtValidator;
+pub use algorithms::AlgorithmAllowlist;
+pub use validation_policy::{AudienceCheck, RequiredClaim, TokenValidationPolicy};
 pub use transforms::ValueTransform;
+pub use claim_path::ClaimPath;
 pub use jwks_cache::JwksCache;
+pub use introspection::{IntrospectionEndpoint, TokenIntrospector};
+pub use sender_constraint::{PossessionProof, SenderConstraint, SenderConstraintVerifier};
 pub use issuer_detection::{extract_issuer_from_token, extract_subject_from_token, extract_claims_from_token};
-pub use claims_mapper::{ClaimsMappingConfig, ClaimsMapper, ParentMapping};
+pub use claims_mapper::{ClaimsMappingConfig, ClaimsMapper, ContextClaimsConfig, ParentMapping};
+pub use attribute_types::{AttributeType, schema_attribute_types};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -28,7 +40,9 @@ pub struct ValidatedClaims {
     /// Issuer
     pub iss: String,
     
-    /// Audience
+    /// Audience (a single string or an array in the token; absent in
+    /// Cognito access tokens)
+    #[serde(default, deserialize_with = "deserialize_audience")]
     pub aud: Vec<String>,
     
     /// Expiration time (Unix timestamp)
@@ -42,6 +56,27 @@ pub struct ValidatedClaims {
     pub additional_claims: HashMap<String, serde_json::Value>,
 }
 
+/// Accepts `aud` as either a single string or an array of strings
+fn deserialize_audience<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Audience {
+        Single(String),
+        Multiple(Vec<String>),
+    }
+
+    Ok(match Audience::deserialize(deserializer)? {
+        Audience::Single(aud) => vec![aud],
+        Audience::Multiple(aud) => aud,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,4 +99,28 @@ mod tests {
         assert!(json.contains("user123"));
         assert!(json.contains("user@example.com"));
     }
+
+    #[test]
+    fn test_audience_as_string_or_missing() {
+        let single: ValidatedClaims = serde_json::from_value(serde_json::json!({
+            "sub": "user123",
+            "iss": "https://issuer.example.com",
+            "aud": "client-id",
+            "exp": 1234567890,
+            "iat": 1234567800
+        }))
+        .unwrap();
+        assert_eq!(single.aud, vec!["client-id".to_string()]);
+
+        let missing: ValidatedClaims = serde_json::from_value(serde_json::json!({
+            "sub": "user123",
+            "iss": "https://issuer.example.com",
+            "exp": 1234567890,
+            "iat": 1234567800,
+            "client_id": "client-id"
+        }))
+        .unwrap();
+        assert!(missing.aud.is_empty());
+        assert!(missing.additional_claims.contains_key("client_id"));
+    }
 }
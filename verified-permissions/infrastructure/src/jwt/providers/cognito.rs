@@ -21,6 +21,10 @@ pub struct CognitoConfig {
     
     /// Whether to include Cognito groups
     pub include_groups: bool,
+    
+    /// Base URL of the Cognito IdP endpoint, for local emulators
+    /// (default: https://cognito-idp.{region}.amazonaws.com)
+    pub endpoint: Option<String>,
 }
 
 impl Default for CognitoConfig {
@@ -30,6 +34,7 @@ impl Default for CognitoConfig {
             user_pool_id: None,
             client_ids: Vec::new(),
             include_groups: true,
+            endpoint: None,
         }
     }
 }
@@ -49,6 +54,12 @@ impl Default for CognitoProvider {
 }
 
 impl CognitoProvider {
+    /// Claim carrying the user pool groups of the user
+    pub const GROUPS_CLAIM: &'static str = "cognito:groups";
+
+    /// Algorithm user pools sign their tokens with
+    pub const SIGNING_ALGORITHM: &'static str = "RS256";
+    
     /// Create a new Cognito provider with custom configuration
     pub fn new(config: CognitoConfig) -> Self {
         Self { config }
@@ -82,6 +93,7 @@ impl CognitoProvider {
                 user_pool_id: Some(user_pool_id),
                 client_ids: Vec::new(),
                 include_groups: true,
+                endpoint: None,
             },
         })
     }
@@ -94,6 +106,7 @@ impl CognitoProvider {
                 user_pool_id: Some(user_pool_id.into()),
                 client_ids: Vec::new(),
                 include_groups: true,
+                endpoint: None,
             },
         }
     }
@@ -104,6 +117,37 @@ impl CognitoProvider {
         self
     }
     
+    /// Use another Cognito IdP endpoint (e.g. a local emulator)
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.config.endpoint = Some(endpoint.into());
+        self
+    }
+    
+    /// Issuer of the user pool's tokens
+    /// Format: https://cognito-idp.{region}.amazonaws.com/{user-pool-id}
+    pub fn issuer(&self) -> Result<String> {
+        let user_pool_id = self.config.user_pool_id.as_deref().ok_or_else(|| {
+            AuthorizationError::Internal("Cognito provider has no User Pool ID".to_string())
+        })?;
+        
+        let endpoint = match &self.config.endpoint {
+            Some(endpoint) => endpoint.trim_end_matches('/').to_string(),
+            None => {
+                let region = self.config.region.as_deref().ok_or_else(|| {
+                    AuthorizationError::Internal("Cognito provider has no region".to_string())
+                })?;
+                format!("https://cognito-idp.{}.amazonaws.com", region)
+            }
+        };
+        
+        Ok(format!("{}/{}", endpoint, user_pool_id))
+    }
+    
+    /// JWKS URL of the user pool: {issuer}/.well-known/jwks.json
+    pub fn jwks_uri(&self) -> Result<String> {
+        Ok(format!("{}/.well-known/jwks.json", self.issuer()?))
+    }
+    
     /// Extract region and pool ID from issuer URL
     /// Format: https://cognito-idp.{region}.amazonaws.com/{user-pool-id}
     pub fn parse_issuer(issuer: &str) -> Option<(String, String)> {
@@ -148,7 +192,7 @@ impl IdentityProvider for CognitoProvider {
         // Cognito groups
         if self.config.include_groups {
             parent_mappings.push(ParentMapping {
-                claim_path: "cognito:groups".to_string(),
+                claim_path: Self::GROUPS_CLAIM.to_string(),
                 entity_type: "Group".to_string(),
                 transform: ValueTransform::None,
             });
@@ -169,6 +213,19 @@ impl IdentityProvider for CognitoProvider {
     }
     
     fn validate_claims(&self, claims: &ValidatedClaims) -> Result<()> {
+        // Only ID and access tokens may be used for authorization
+        if let Some(token_use) = claims.additional_claims.get("token_use") {
+            match token_use.as_str() {
+                Some("id") | Some("access") => {}
+                _ => {
+                    return Err(AuthorizationError::Unauthenticated(format!(
+                        "Unsupported Cognito token_use: {}",
+                        token_use
+                    )));
+                }
+            }
+        }
+        
         // Validate client_id if configured
         if !self.config.client_ids.is_empty() {
             let token_client_id = claims.additional_claims
@@ -328,4 +385,101 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not in allowed list"));
     }
+
+    #[test]
+    fn test_issuer_and_jwks_uri_from_arn() {
+        let arn = "arn:aws:cognito-idp:eu-west-1:123456789:userpool/eu-west-1_XYZ789";
+        let provider = CognitoProvider::from_arn(arn).unwrap();
+
+        assert_eq!(
+            provider.issuer().unwrap(),
+            "https://cognito-idp.eu-west-1.amazonaws.com/eu-west-1_XYZ789"
+        );
+        assert_eq!(
+            provider.jwks_uri().unwrap(),
+            "https://cognito-idp.eu-west-1.amazonaws.com/eu-west-1_XYZ789/.well-known/jwks.json"
+        );
+        assert!(CognitoProvider::default().issuer().is_err());
+    }
+
+    #[test]
+    fn test_validate_claims_token_use() {
+        let provider = CognitoProvider::default();
+        let claims = |token_use: &str| {
+            let mut additional = HashMap::new();
+            additional.insert("token_use".to_string(), serde_json::json!(token_use));
+            ValidatedClaims {
+                sub: "user123".to_string(),
+                iss: "https://cognito-idp.us-east-1.amazonaws.com/us-east-1_ABC123".to_string(),
+                aud: vec![],
+                exp: 9999999999,
+                iat: 1234567890,
+                additional_claims: additional,
+            }
+        };
+
+        assert!(provider.validate_claims(&claims("id")).is_ok());
+        assert!(provider.validate_claims(&claims("access")).is_ok());
+        assert!(provider.validate_claims(&claims("refresh")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_access_token_validated_against_served_jwks() {
+        use crate::jwt::test_support::{serve_json, sign_token, test_jwks};
+        use crate::jwt::{AlgorithmAllowlist, ClaimsMapper, JwtValidator, TokenValidationPolicy};
+
+        let endpoint = serve_json(test_jwks()).await;
+        let provider = CognitoProvider::from_arn(
+            "arn:aws:cognito-idp:us-east-1:123456789:userpool/us-east-1_ABC123",
+        )
+        .unwrap()
+        .with_endpoint(endpoint.clone())
+        .with_client_id("app-client");
+        let issuer = provider.issuer().unwrap();
+        assert_eq!(issuer, format!("{}/us-east-1_ABC123", endpoint));
+
+        // Cognito access tokens carry client_id instead of aud
+        let token = sign_token(&serde_json::json!({
+            "sub": "user123",
+            "iss": issuer,
+            "client_id": "app-client",
+            "token_use": "access",
+            "cognito:groups": ["admins"],
+            "exp": 9999999999i64,
+            "iat": 1234567890
+        }));
+
+        let algorithms =
+            AlgorithmAllowlist::asymmetric(&[CognitoProvider::SIGNING_ALGORITHM.to_string()])
+                .unwrap();
+        let claims = JwtValidator::new()
+            .validate_token(
+                &token,
+                &issuer,
+                &[],
+                Some(&provider.jwks_uri().unwrap()),
+                &algorithms,
+                &TokenValidationPolicy::default(),
+            )
+            .await
+            .unwrap();
+        provider.validate_claims(&claims).unwrap();
+
+        let config = ClaimsMappingConfig {
+            group_claim: Some(CognitoProvider::GROUPS_CLAIM.to_string()),
+            ..Default::default()
+        };
+        let (principal, entities) =
+            ClaimsMapper::map_to_principal(&claims, &config, "User").unwrap();
+        assert_eq!(principal.entity_id, "user123");
+        assert_eq!(entities[0].parents[0].entity_id, "admins");
+
+        let mut config = provider.config.clone();
+        config.client_ids = vec!["another-client".to_string()];
+        assert!(
+            CognitoProvider::new(config)
+                .validate_claims(&claims)
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,670 @@
+//! Sender-constrained access tokens (DPoP and mTLS certificate binding)
+//!
+//! A bound token names, in its `cnf` claim, the key its client must prove
+//! possession of: `jkt` is the JWK thumbprint of a DPoP key (RFC 9449) and
+//! `x5t#S256` the thumbprint of a client certificate (RFC 8705). The caller
+//! of the authorization API forwards the DPoP proof with the HTTP method and
+//! URL of the request that carried it, or the thumbprint of the client
+//! certificate of the TLS connection it terminated.
+//!
+//! DPoP proof IDs (`jti`) are remembered until the proof is too old to be
+//! accepted anyway, so a captured proof cannot be replayed against this
+//! server. Only proofs whose key matches the token's `cnf.jkt` are
+//! remembered, and each key may only have a limited number in flight, so a
+//! single client cannot fill the replay store for everyone else.
+
+use crate::error::{AuthorizationError, Result};
+use crate::jwt::algorithms::ASYMMETRIC_ALGORITHMS;
+use crate::jwt::jwks_cache::Jwk;
+use crate::jwt::ValidatedClaims;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::{Algorithm, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+use tokio::sync::Mutex;
+
+/// How long after its `iat` a DPoP proof is accepted, in seconds
+pub const DPOP_PROOF_MAX_AGE_SECONDS: u64 = 300;
+
+/// When an identity source requires proof of possession of the token key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderConstraint {
+    /// Tokens are bearer tokens; `cnf` is ignored
+    #[default]
+    Disabled,
+    /// Tokens with a `cnf` claim must come with proof of possession
+    IfBound,
+    /// Every token must be bound and come with proof of possession
+    Required,
+}
+
+/// Proof of possession presented with an access token
+#[derive(Debug, Clone, Default)]
+pub struct PossessionProof {
+    /// DPoP proof JWT (the `DPoP` header of the request)
+    pub dpop_proof: Option<String>,
+    /// HTTP method of the request that carried the DPoP proof
+    pub http_method: Option<String>,
+    /// HTTP URL of the request that carried the DPoP proof
+    pub http_url: Option<String>,
+    /// Base64url SHA-256 thumbprint of the client certificate (DER)
+    pub client_certificate_thumbprint: Option<String>,
+}
+
+/// Header members of a DPoP proof
+#[derive(Debug, Deserialize)]
+struct DpopHeader {
+    typ: Option<String>,
+    alg: String,
+    jwk: Option<Value>,
+}
+
+/// Claims of a DPoP proof
+#[derive(Debug, Deserialize)]
+struct DpopClaims {
+    jti: String,
+    htm: String,
+    htu: String,
+    iat: i64,
+    #[serde(default)]
+    ath: Option<String>,
+}
+
+/// Configuration for the sender constraint verifier
+#[derive(Debug, Clone)]
+pub struct SenderConstraintVerifierConfig {
+    /// How long after its `iat` a DPoP proof is accepted (default: 300 seconds)
+    pub proof_max_age_seconds: u64,
+    /// Maximum number of remembered DPoP proof IDs; when full, the proofs
+    /// closest to expiring are forgotten first (default: 100000)
+    pub max_replay_entries: usize,
+    /// Maximum number of remembered DPoP proof IDs per key; further proofs
+    /// of that key are rejected until older ones expire (default: 10000)
+    pub max_replay_entries_per_key: usize,
+}
+
+impl Default for SenderConstraintVerifierConfig {
+    fn default() -> Self {
+        Self {
+            proof_max_age_seconds: DPOP_PROOF_MAX_AGE_SECONDS,
+            max_replay_entries: 100_000,
+            max_replay_entries_per_key: 10_000,
+        }
+    }
+}
+
+/// DPoP proofs that have been used and are still accepted
+#[derive(Default)]
+struct UsedProofs {
+    /// Unix time each proof stops being accepted, by key thumbprint and `jti`
+    by_key: HashMap<String, HashMap<String, i64>>,
+    /// The same proofs as (expiry, key thumbprint, `jti`), soonest first
+    by_expiry: BTreeSet<(i64, String, String)>,
+}
+
+impl UsedProofs {
+    fn len(&self) -> usize {
+        self.by_expiry.len()
+    }
+
+    /// Forgets the proofs that stopped being accepted before `now`
+    fn forget_expired(&mut self, now: i64) {
+        while let Some((until, _, _)) = self.by_expiry.first() {
+            if *until >= now {
+                break;
+            }
+            self.forget_oldest();
+        }
+    }
+
+    /// Forgets the proof closest to expiring
+    fn forget_oldest(&mut self) {
+        let Some((_, thumbprint, jti)) = self.by_expiry.pop_first() else {
+            return;
+        };
+        if let Some(proofs) = self.by_key.get_mut(&thumbprint) {
+            proofs.remove(&jti);
+            if proofs.is_empty() {
+                self.by_key.remove(&thumbprint);
+            }
+        }
+    }
+}
+
+/// Verifies that the presenter of a bound token holds the token's key
+pub struct SenderConstraintVerifier {
+    /// Used DPoP proofs, forgotten once they stop being accepted
+    used_proofs: Mutex<UsedProofs>,
+    /// Configuration
+    config: SenderConstraintVerifierConfig,
+}
+
+impl SenderConstraintVerifier {
+    /// Create a new verifier with default configuration
+    pub fn new() -> Self {
+        Self::with_config(SenderConstraintVerifierConfig::default())
+    }
+
+    /// Create a new verifier with custom configuration
+    pub fn with_config(config: SenderConstraintVerifierConfig) -> Self {
+        Self {
+            used_proofs: Mutex::new(UsedProofs::default()),
+            config,
+        }
+    }
+
+    /// Checks the `cnf` binding of validated token claims against the
+    /// presented proof of possession
+    ///
+    /// `clock_skew_seconds` is the identity source's leeway, also applied
+    /// to the `iat` of DPoP proofs.
+    pub async fn verify(
+        &self,
+        access_token: &str,
+        claims: &ValidatedClaims,
+        proof: &PossessionProof,
+        constraint: SenderConstraint,
+        clock_skew_seconds: u64,
+    ) -> Result<()> {
+        self.verify_at(
+            access_token,
+            claims,
+            proof,
+            constraint,
+            clock_skew_seconds,
+            jsonwebtoken::get_current_timestamp() as i64,
+        )
+        .await
+    }
+
+    async fn verify_at(
+        &self,
+        access_token: &str,
+        claims: &ValidatedClaims,
+        proof: &PossessionProof,
+        constraint: SenderConstraint,
+        clock_skew_seconds: u64,
+        now: i64,
+    ) -> Result<()> {
+        if constraint == SenderConstraint::Disabled {
+            return Ok(());
+        }
+
+        let cnf = claims.additional_claims.get("cnf");
+        let jkt = cnf.and_then(|cnf| cnf.get("jkt")).and_then(Value::as_str);
+        let x5t = cnf
+            .and_then(|cnf| cnf.get("x5t#S256"))
+            .and_then(Value::as_str);
+
+        if jkt.is_none() && x5t.is_none() {
+            if constraint == SenderConstraint::Required {
+                return Err(rejected(
+                    "token is not sender-constrained (no cnf claim)".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        if let Some(jkt) = jkt {
+            let dpop_proof = non_empty(&proof.dpop_proof).ok_or_else(|| {
+                rejected("DPoP-bound token presented without a DPoP proof".to_string())
+            })?;
+            self.verify_dpop_proof(
+                dpop_proof,
+                jkt,
+                access_token,
+                proof,
+                clock_skew_seconds,
+                now,
+            )
+            .await?;
+        }
+
+        if let Some(x5t) = x5t {
+            let thumbprint = non_empty(&proof.client_certificate_thumbprint).ok_or_else(|| {
+                rejected(
+                    "certificate-bound token presented without a client certificate".to_string(),
+                )
+            })?;
+            if thumbprint.trim_end_matches('=') != x5t.trim_end_matches('=') {
+                return Err(rejected(
+                    "client certificate does not match the token's cnf.x5t#S256".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies a DPoP proof made with the key `jkt` for the request and
+    /// access token
+    async fn verify_dpop_proof(
+        &self,
+        dpop_proof: &str,
+        jkt: &str,
+        access_token: &str,
+        proof: &PossessionProof,
+        clock_skew_seconds: u64,
+        now: i64,
+    ) -> Result<()> {
+        let header: DpopHeader = dpop_proof
+            .split('.')
+            .next()
+            .and_then(|segment| URL_SAFE_NO_PAD.decode(segment).ok())
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| invalid_proof("malformed header".to_string()))?;
+
+        if header.typ.as_deref() != Some("dpop+jwt") {
+            return Err(invalid_proof("typ must be dpop+jwt".to_string()));
+        }
+        let algorithm = Algorithm::from_str(&header.alg)
+            .ok()
+            .filter(|algorithm| ASYMMETRIC_ALGORITHMS.contains(algorithm))
+            .ok_or_else(|| invalid_proof(format!("unsupported algorithm '{}'", header.alg)))?;
+
+        // The proof is signed with the key in its own header, which must be
+        // a public key
+        let jwk = header
+            .jwk
+            .ok_or_else(|| invalid_proof("missing jwk header".to_string()))?;
+        if jwk.get("d").is_some() {
+            return Err(invalid_proof("jwk contains a private key".to_string()));
+        }
+        let jwk: Jwk = serde_json::from_value(jwk)
+            .map_err(|e| invalid_proof(format!("invalid jwk: {}", e)))?;
+        let key = jwk
+            .decoding_key()
+            .ok()
+            .flatten()
+            .ok_or_else(|| invalid_proof("unsupported jwk".to_string()))?;
+        let thumbprint = jwk_thumbprint(&jwk)?;
+        if thumbprint != jkt {
+            return Err(rejected(
+                "DPoP proof key does not match the token's cnf.jkt".to_string(),
+            ));
+        }
+
+        let mut validation = Validation::new(algorithm);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        validation.validate_aud = false;
+        let claims = jsonwebtoken::decode::<DpopClaims>(dpop_proof, &key, &validation)
+            .map_err(|e| invalid_proof(e.to_string()))?
+            .claims;
+
+        let method = non_empty(&proof.http_method)
+            .ok_or_else(|| rejected("DPoP proof presented without the HTTP method".to_string()))?;
+        if claims.htm != method {
+            return Err(invalid_proof(format!(
+                "htm '{}' does not match the request method",
+                claims.htm
+            )));
+        }
+
+        let url = non_empty(&proof.http_url)
+            .and_then(normalized_htu)
+            .ok_or_else(|| rejected("DPoP proof presented without a valid HTTP URL".to_string()))?;
+        if normalized_htu(&claims.htu).as_deref() != Some(url.as_str()) {
+            return Err(invalid_proof(format!(
+                "htu '{}' does not match the request URL",
+                claims.htu
+            )));
+        }
+
+        let skew = clock_skew_seconds as i64;
+        if claims.iat > now + skew {
+            return Err(invalid_proof("issued in the future (iat)".to_string()));
+        }
+        let accepted_until = claims.iat + self.config.proof_max_age_seconds as i64 + skew;
+        if now > accepted_until {
+            return Err(invalid_proof("proof is too old (iat)".to_string()));
+        }
+
+        let ath = URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()));
+        if claims.ath.as_deref() != Some(ath.as_str()) {
+            return Err(invalid_proof(
+                "ath does not match the access token".to_string(),
+            ));
+        }
+
+        self.remember_proof(thumbprint, claims.jti, accepted_until, now)
+            .await
+    }
+
+    /// Records a DPoP proof of the key `thumbprint` as used, rejecting a
+    /// replayed one
+    async fn remember_proof(
+        &self,
+        thumbprint: String,
+        jti: String,
+        accepted_until: i64,
+        now: i64,
+    ) -> Result<()> {
+        let mut used_proofs = self.used_proofs.lock().await;
+        used_proofs.forget_expired(now);
+
+        let in_flight = used_proofs.by_key.get(&thumbprint);
+        if in_flight.is_some_and(|proofs| proofs.contains_key(&jti)) {
+            return Err(invalid_proof(format!(
+                "jti '{}' has already been used",
+                jti
+            )));
+        }
+        // Forgetting unexpired proofs of this key would let them be replayed
+        if in_flight.map_or(0, HashMap::len) >= self.config.max_replay_entries_per_key.max(1) {
+            return Err(rejected(
+                "too many DPoP proofs in flight for this key, try again later".to_string(),
+            ));
+        }
+
+        while used_proofs.len() >= self.config.max_replay_entries.max(1) {
+            used_proofs.forget_oldest();
+        }
+        used_proofs
+            .by_expiry
+            .insert((accepted_until, thumbprint.clone(), jti.clone()));
+        used_proofs
+            .by_key
+            .entry(thumbprint)
+            .or_default()
+            .insert(jti, accepted_until);
+        Ok(())
+    }
+
+    /// Number of remembered DPoP proof IDs
+    pub async fn used_proofs_count(&self) -> usize {
+        self.used_proofs.lock().await.len()
+    }
+}
+
+impl Default for SenderConstraintVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// JWK SHA-256 thumbprint (RFC 7638) of a public key, base64url-encoded
+pub fn jwk_thumbprint(jwk: &Jwk) -> Result<String> {
+    let member = |name: &str, value: &Option<String>| {
+        value
+            .as_ref()
+            .map(|value| format!("\"{}\":{}", name, Value::from(value.as_str())))
+            .ok_or_else(|| invalid_proof(format!("jwk is missing '{}'", name)))
+    };
+
+    // Required members only, in lexicographic order and without whitespace
+    let members = match jwk.kty.as_str() {
+        "RSA" => vec![
+            member("e", &jwk.e)?,
+            "\"kty\":\"RSA\"".to_string(),
+            member("n", &jwk.n)?,
+        ],
+        "EC" => vec![
+            member("crv", &jwk.crv)?,
+            "\"kty\":\"EC\"".to_string(),
+            member("x", &jwk.x)?,
+            member("y", &jwk.y)?,
+        ],
+        "OKP" => vec![
+            member("crv", &jwk.crv)?,
+            "\"kty\":\"OKP\"".to_string(),
+            member("x", &jwk.x)?,
+        ],
+        kty => return Err(invalid_proof(format!("unsupported key type '{}'", kty))),
+    };
+    let canonical = format!("{{{}}}", members.join(","));
+    Ok(URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes())))
+}
+
+/// `htu` compared without query and fragment, after URL normalization
+/// (lowercase scheme and host, no default port)
+fn normalized_htu(url: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(url).ok()?;
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(url.to_string())
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|value| !value.is_empty())
+}
+
+fn rejected(reason: String) -> AuthorizationError {
+    AuthorizationError::Unauthenticated(format!("Token rejected: {}", reason))
+}
+
+fn invalid_proof(reason: String) -> AuthorizationError {
+    rejected(format!("invalid DPoP proof: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::test_support::{sign_dpop_proof, test_dpop_jwk};
+    use serde_json::json;
+
+    const NOW: i64 = 1_700_000_000;
+    const ACCESS_TOKEN: &str = "access-token";
+    const URL: &str = "https://api.example.com/documents";
+
+    fn claims(cnf: Value) -> ValidatedClaims {
+        ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: Vec::new(),
+            exp: NOW + 3600,
+            iat: NOW,
+            additional_claims: HashMap::from([("cnf".to_string(), cnf)]),
+        }
+    }
+
+    fn proof_claims(jti: &str) -> Value {
+        json!({
+            "jti": jti,
+            "htm": "GET",
+            "htu": URL,
+            "iat": NOW,
+            "ath": URL_SAFE_NO_PAD.encode(Sha256::digest(ACCESS_TOKEN.as_bytes())),
+        })
+    }
+
+    /// DPoP proof sent with a GET of the test URL
+    fn dpop(claims: &Value) -> PossessionProof {
+        PossessionProof {
+            dpop_proof: Some(sign_dpop_proof(claims)),
+            http_method: Some("GET".to_string()),
+            http_url: Some(format!("{}?page=2", URL)),
+            client_certificate_thumbprint: None,
+        }
+    }
+
+    async fn verify(
+        verifier: &SenderConstraintVerifier,
+        token: &ValidatedClaims,
+        proof: &PossessionProof,
+        constraint: SenderConstraint,
+    ) -> Result<()> {
+        verifier
+            .verify_at(ACCESS_TOKEN, token, proof, constraint, 60, NOW)
+            .await
+    }
+
+    #[test]
+    fn test_jwk_thumbprint() {
+        // RFC 7638, section 3.1
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29"
+        }))
+        .unwrap();
+        assert_eq!(
+            jwk_thumbprint(&jwk).unwrap(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dpop_bound_token() {
+        let verifier = SenderConstraintVerifier::new();
+        let jkt = jwk_thumbprint(&serde_json::from_value(test_dpop_jwk()).unwrap()).unwrap();
+        let token = claims(json!({ "jkt": jkt }));
+        let if_bound = SenderConstraint::IfBound;
+
+        // The query is not part of htu
+        let proof = dpop(&proof_claims("proof-1"));
+        assert!(verify(&verifier, &token, &proof, if_bound).await.is_ok());
+
+        // The same proof cannot be used twice
+        let error = verify(&verifier, &token, &proof, if_bound)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("already been used"));
+
+        // Bearer use of the bound token
+        let bearer = PossessionProof::default();
+        assert!(verify(&verifier, &token, &bearer, if_bound).await.is_err());
+        let disabled = SenderConstraint::Disabled;
+        assert!(verify(&verifier, &token, &bearer, disabled).await.is_ok());
+
+        let method = PossessionProof {
+            http_method: Some("POST".to_string()),
+            ..dpop(&proof_claims("proof-2"))
+        };
+        assert!(verify(&verifier, &token, &method, if_bound).await.is_err());
+
+        let url = PossessionProof {
+            http_url: Some("https://api.example.com/admin".to_string()),
+            ..dpop(&proof_claims("proof-3"))
+        };
+        assert!(verify(&verifier, &token, &url, if_bound).await.is_err());
+
+        let mut ath = proof_claims("proof-4");
+        ath["ath"] = json!("bm90IHRoZSBhY2Nlc3MgdG9rZW4");
+        let ath = dpop(&ath);
+        assert!(verify(&verifier, &token, &ath, if_bound).await.is_err());
+
+        let mut old = proof_claims("proof-5");
+        old["iat"] = json!(NOW - 3600);
+        let old = dpop(&old);
+        assert!(verify(&verifier, &token, &old, if_bound).await.is_err());
+
+        // A valid proof, but for another key
+        let other_key = claims(json!({ "jkt": "0ZcOCORZNYy-DWpqq30jZyJGHTN0d2HglBV3uiguA4I" }));
+        let proof = dpop(&proof_claims("proof-6"));
+        let error = verify(&verifier, &other_key, &proof, if_bound)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cnf.jkt"));
+    }
+
+    #[tokio::test]
+    async fn test_certificate_bound_token() {
+        let verifier = SenderConstraintVerifier::new();
+        let thumbprint = "bwcK0esc3ACC3DB2Y5_lESsXE8o9ltc05O89jdN-dg2";
+        let token = claims(json!({ "x5t#S256": thumbprint }));
+        let certificate = |thumbprint: &str| PossessionProof {
+            client_certificate_thumbprint: Some(thumbprint.to_string()),
+            ..Default::default()
+        };
+        let required = SenderConstraint::Required;
+
+        let proof = certificate(thumbprint);
+        assert!(verify(&verifier, &token, &proof, required).await.is_ok());
+        let proof = certificate("other");
+        assert!(verify(&verifier, &token, &proof, required).await.is_err());
+        let proof = PossessionProof::default();
+        assert!(verify(&verifier, &token, &proof, required).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unbound_token() {
+        let verifier = SenderConstraintVerifier::new();
+        let token = ValidatedClaims {
+            additional_claims: HashMap::new(),
+            ..claims(Value::Null)
+        };
+        let proof = PossessionProof::default();
+
+        for (constraint, accepted) in [
+            (SenderConstraint::Disabled, true),
+            (SenderConstraint::IfBound, true),
+            (SenderConstraint::Required, false),
+        ] {
+            let result = verify(&verifier, &token, &proof, constraint).await;
+            assert_eq!(result.is_ok(), accepted, "{:?}", constraint);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proof_for_another_key_is_not_remembered() {
+        let verifier = SenderConstraintVerifier::new();
+        let jkt = jwk_thumbprint(&serde_json::from_value(test_dpop_jwk()).unwrap()).unwrap();
+        let token = claims(json!({ "jkt": jkt }));
+        let other_key = claims(json!({ "jkt": "0ZcOCORZNYy-DWpqq30jZyJGHTN0d2HglBV3uiguA4I" }));
+        let if_bound = SenderConstraint::IfBound;
+
+        let proof = dpop(&proof_claims("proof-1"));
+        let error = verify(&verifier, &other_key, &proof, if_bound)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cnf.jkt"));
+        assert_eq!(verifier.used_proofs_count().await, 0);
+
+        // The rejected use did not consume the proof
+        assert!(verify(&verifier, &token, &proof, if_bound).await.is_ok());
+        assert_eq!(verifier.used_proofs_count().await, 1);
+    }
+
+    /// Verifies a proof `jti` of the test key issued at `now`
+    async fn verify_at(verifier: &SenderConstraintVerifier, jti: &str, now: i64) -> Result<()> {
+        let jkt = jwk_thumbprint(&serde_json::from_value(test_dpop_jwk()).unwrap()).unwrap();
+        let mut proof = proof_claims(jti);
+        proof["iat"] = json!(now);
+        verifier
+            .verify_at(
+                ACCESS_TOKEN,
+                &claims(json!({ "jkt": jkt })),
+                &dpop(&proof),
+                SenderConstraint::IfBound,
+                60,
+                now,
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_replay_store_limits() {
+        // A key with too many proofs in flight is refused until they expire
+        let verifier = SenderConstraintVerifier::with_config(SenderConstraintVerifierConfig {
+            max_replay_entries_per_key: 2,
+            ..Default::default()
+        });
+        assert!(verify_at(&verifier, "proof-1", NOW).await.is_ok());
+        assert!(verify_at(&verifier, "proof-2", NOW).await.is_ok());
+        let error = verify_at(&verifier, "proof-3", NOW).await.unwrap_err();
+        assert!(error.to_string().contains("for this key"));
+        let later = NOW + DPOP_PROOF_MAX_AGE_SECONDS as i64 + 61;
+        assert!(verify_at(&verifier, "proof-3", later).await.is_ok());
+        assert_eq!(verifier.used_proofs_count().await, 1);
+
+        // A full store forgets the oldest proofs instead of refusing new ones
+        let verifier = SenderConstraintVerifier::with_config(SenderConstraintVerifierConfig {
+            max_replay_entries: 2,
+            ..Default::default()
+        });
+        for (jti, now) in [("proof-1", NOW), ("proof-2", NOW + 1), ("proof-3", NOW + 2)] {
+            assert!(verify_at(&verifier, jti, now).await.is_ok(), "{}", jti);
+        }
+        assert_eq!(verifier.used_proofs_count().await, 2);
+        let error = verify_at(&verifier, "proof-3", NOW + 2).await.unwrap_err();
+        assert!(error.to_string().contains("already been used"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Helpers for tests that validate real signed tokens
+//!
+//! Tokens are signed with fixed RSA, EC (P-256) and Ed25519 test keys and
+//! the matching JWKS is served from a local socket, so no identity provider
+//! is needed.
+
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Key ID of the test key in the served JWKS
+pub const TEST_KID: &str = "test-key";
+
+/// Key ID of the P-256 test key in the served JWKS
+pub const TEST_EC_KID: &str = "test-ec-key";
+
+/// Key ID of the Ed25519 test key in the served JWKS
+pub const TEST_ED_KID: &str = "test-ed-key";
+
+const TEST_KEY_PEM: &str = include_str!("testdata/rsa_test_key.pem");
+const TEST_EC_KEY_PEM: &str = include_str!("testdata/ec_test_key.pem");
+const TEST_ED_KEY_PEM: &str = include_str!("testdata/ed25519_test_key.pem");
+
+/// Base64url modulus of the test key
+const TEST_KEY_N: &str = "5DM6QdqJ2kw2lp0i89OVyepvNggZAVG7pIIJnQFXXA3Xew1aUIKOsEop4WS1FIW9LaQQJcpwUEBAUJXF_bVG9cKT4n-w4Cuadqz7jWBn_jUbjYtL614jxqmhonnYkLDdKanO1VCxOcr5XOEDnyMLBTg3f9DWNBID8puYvbS6GV-XMWMZnO0SFDu5SFmu50E_Bo5dz70bUmx1hcfIugunrKypF-iin8VW9_15LwaabGMVIorZzdNTbPTPxKwkxehkiIZslBQiSJZOFuARa5ifCJfr8tnHQ0sWahTp0KJGkKxOd7hzEnPQXkgw0rU41Un8_QASYA7MomqYXqw852W_jw";
+
+/// Base64url coordinates of the P-256 test key
+const TEST_EC_KEY_X: &str = "zM9gOtegDKJIo9c4oIzN67ZYO13FYRB1tBqmSazvcm0";
+const TEST_EC_KEY_Y: &str = "nQBmjqVcZ_iixnkWqsyRty84qCg48XgiDit7VcGs_mA";
+
+/// JWKS document containing the public part of the test key
+pub fn test_jwks() -> serde_json::Value {
+    serde_json::json!({
+        "keys": [{
+            "kty": "RSA",
+            "kid": TEST_KID,
+            "use": "sig",
+            "alg": "RS256",
+            "n": TEST_KEY_N,
+            "e": "AQAB"
+        }, {
+            "kty": "EC",
+            "kid": TEST_EC_KID,
+            "use": "sig",
+            "crv": "P-256",
+            "x": TEST_EC_KEY_X,
+            "y": TEST_EC_KEY_Y
+        }, {
+            "kty": "OKP",
+            "kid": TEST_ED_KID,
+            "use": "sig",
+            "crv": "Ed25519",
+            "x": "Ocf6zTgkXIvo-JpAbz2uYoJLmcU4pdoUZKWOikqXNdw"
+        }]
+    })
+}
+
+/// Signs the claims with the test key (RS256, `kid` = [`TEST_KID`])
+pub fn sign_token<T: Serialize>(claims: &T) -> String {
+    sign_token_with(Algorithm::RS256, claims)
+}
+
+/// Signs the claims with the test key matching `algorithm`
+///
+/// RS* and PS* use the RSA key, ES256 the P-256 key and EdDSA the Ed25519
+/// key, each with its `kid` from the served JWKS.
+pub fn sign_token_with<T: Serialize>(algorithm: Algorithm, claims: &T) -> String {
+    let (kid, key) = match algorithm {
+        Algorithm::ES256 => (
+            TEST_EC_KID,
+            EncodingKey::from_ec_pem(TEST_EC_KEY_PEM.as_bytes()).unwrap(),
+        ),
+        Algorithm::EdDSA => (
+            TEST_ED_KID,
+            EncodingKey::from_ed_pem(TEST_ED_KEY_PEM.as_bytes()).unwrap(),
+        ),
+        _ => (
+            TEST_KID,
+            EncodingKey::from_rsa_pem(TEST_KEY_PEM.as_bytes()).unwrap(),
+        ),
+    };
+    let mut header = Header::new(algorithm);
+    header.kid = Some(kid.to_string());
+    encode(&header, claims, &key).unwrap()
+}
+
+/// Public JWK of the P-256 test key, as carried by DPoP proofs
+pub fn test_dpop_jwk() -> serde_json::Value {
+    serde_json::json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": TEST_EC_KEY_X,
+        "y": TEST_EC_KEY_Y
+    })
+}
+
+/// Signs DPoP proof claims with the P-256 test key, whose public JWK goes
+/// in the header
+pub fn sign_dpop_proof<T: Serialize>(claims: &T) -> String {
+    let mut header = Header::new(Algorithm::ES256);
+    header.typ = Some("dpop+jwt".to_string());
+    header.jwk = Some(serde_json::from_value(test_dpop_jwk()).unwrap());
+    let key = EncodingKey::from_ec_pem(TEST_EC_KEY_PEM.as_bytes()).unwrap();
+    encode(&header, claims, &key).unwrap()
+}
+
+/// Serves `body` as JSON to every request and returns the base URL
+pub async fn serve_json(body: serde_json::Value) -> String {
+    let server = TestServer::start().await;
+    server.route("*", body);
+    server.url.clone()
+}
+
+/// Local HTTP server answering requests with JSON bodies by path
+///
+/// Routes can be added after start, so documents may embed the server URL.
+/// The `*` route answers any path without a route of its own; other paths
+/// get a 404.
+pub struct TestServer {
+    /// Base URL, e.g. `http://127.0.0.1:41234`
+    pub url: String,
+    routes: Arc<Mutex<HashMap<String, String>>>,
+    requests: Arc<AtomicUsize>,
+    last_request: Arc<Mutex<Option<String>>>,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes: Arc<Mutex<HashMap<String, String>>> = Arc::default();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let last_request: Arc<Mutex<Option<String>>> = Arc::default();
+
+        let server_routes = routes.clone();
+        let server_requests = requests.clone();
+        let server_last_request = last_request.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let routes = server_routes.clone();
+                let requests = server_requests.clone();
+                let last_request = server_last_request.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    requests.fetch_add(1, Ordering::SeqCst);
+
+                    // Request line: "GET /path HTTP/1.1"
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    *last_request.lock().unwrap() = Some(request.to_string());
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let body = {
+                        let routes = routes.lock().unwrap();
+                        routes.get(path).or_else(|| routes.get("*")).cloned()
+                    };
+
+                    let response = match body {
+                        Some(body) => format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        ),
+                        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string(),
+                    };
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        Self {
+            url: format!("http://{}", addr),
+            routes,
+            requests,
+            last_request,
+        }
+    }
+
+    /// Serves `body` as JSON at `path`
+    pub fn route(&self, path: &str, body: serde_json::Value) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), body.to_string());
+    }
+
+    /// Number of requests received so far
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Request line and headers of the last request received
+    pub fn last_request(&self) -> Option<String> {
+        self.last_request.lock().unwrap().clone()
+    }
+}
@@ -3,17 +3,25 @@
 //! This module provides various transformations that can be applied to claim values
 //! during JWT validation and claims mapping.
 
+use crate::jwt::ClaimPath;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 /// Value transformation operations
 ///
 /// These transformations can be chained together to extract and manipulate
-/// claim values in flexible ways.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// claim values in flexible ways. Some of them turn one value into several
+/// (`Split`, `JsonParse`) or drop it (`Filter`, `Drop`, `Lookup` without a
+/// default); use [`ValueTransform::apply_all`] to get every resulting value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 pub enum ValueTransform {
     /// No transformation
+    #[default]
     None,
     /// Split by separator and take the last part
     SplitLast(String),
@@ -37,6 +45,37 @@ pub enum ValueTransform {
     Uppercase,
     /// Trim whitespace
     Trim,
+    /// Map values through a table (e.g. IdP group names to Cedar role ids)
+    ///
+    /// Values missing from the table become `default`, or are dropped when
+    /// there is none.
+    Lookup {
+        table: HashMap<String, String>,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Apply `then` only to values matching the regex; others are kept as-is
+    When {
+        pattern: String,
+        then: Box<ValueTransform>,
+    },
+    /// Parse the value as JSON and take the strings at `path` (a
+    /// [`ClaimPath`]), or the whole document when unset
+    ///
+    /// Strings, numbers and booleans become values, arrays yield their
+    /// elements and objects their keys.
+    JsonParse {
+        #[serde(default)]
+        path: Option<String>,
+    },
+    /// Decode base64 (standard or URL-safe, padding optional) into UTF-8
+    Base64Decode,
+    /// Split into several values by separator, trimming and dropping empty parts
+    Split(String),
+    /// Keep only values matching the regex
+    Filter(String),
+    /// Drop values matching the regex
+    Drop(String),
     /// Chain multiple transformations
     Chain(Vec<ValueTransform>),
 }
@@ -50,7 +89,9 @@ impl ValueTransform {
     ///
     /// # Returns
     ///
-    /// The transformed value or an error if transformation fails
+    /// The transformed value or an error if transformation fails. Of
+    /// transforms that yield several values only the first is returned; a
+    /// dropped value is an error.
     pub fn apply(&self, value: &str) -> Result<String, String> {
         match self {
             ValueTransform::None => Ok(value.to_string()),
@@ -101,13 +142,117 @@ impl ValueTransform {
                 Ok(value.trim().to_string())
             }
 
+            ValueTransform::Base64Decode => {
+                let normalized: String = value
+                    .trim()
+                    .trim_end_matches('=')
+                    .chars()
+                    .map(|c| match c {
+                        '-' => '+',
+                        '_' => '/',
+                        c => c,
+                    })
+                    .collect();
+                let bytes = STANDARD_NO_PAD
+                    .decode(normalized)
+                    .map_err(|e| format!("Invalid base64 value: {}", e))?;
+                String::from_utf8(bytes)
+                    .map_err(|e| format!("Decoded base64 value is not UTF-8: {}", e))
+            }
+
+            _ => self
+                .apply_all(value)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("Value '{}' was dropped by the transform", value)),
+        }
+    }
+
+    /// Apply the transformation to a value, returning every resulting value
+    ///
+    /// The result may be empty when the value is dropped. In a `Chain` each
+    /// step is applied to every value produced by the previous one.
+    pub fn apply_all(&self, value: &str) -> Result<Vec<String>, String> {
+        match self {
+            ValueTransform::Lookup { table, default } => Ok(table
+                .get(value)
+                .or(default.as_ref())
+                .cloned()
+                .into_iter()
+                .collect()),
+
+            ValueTransform::When { pattern, then } => {
+                if regex(pattern)?.is_match(value) {
+                    then.apply_all(value)
+                } else {
+                    Ok(vec![value.to_string()])
+                }
+            }
+
+            ValueTransform::JsonParse { path } => {
+                let document: Value = serde_json::from_str(value)
+                    .map_err(|e| format!("Value is not valid JSON: {}", e))?;
+                let selected = match path {
+                    Some(path) => ClaimPath::lookup(|name| document.get(name), path),
+                    None => Some(document),
+                };
+                Ok(selected.as_ref().map(json_strings).unwrap_or_default())
+            }
+
+            ValueTransform::Split(separator) => Ok(value
+                .split(separator.as_str())
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .map(str::to_string)
+                .collect()),
+
+            ValueTransform::Filter(pattern) => Ok(if regex(pattern)?.is_match(value) {
+                vec![value.to_string()]
+            } else {
+                Vec::new()
+            }),
+
+            ValueTransform::Drop(pattern) => Ok(if regex(pattern)?.is_match(value) {
+                Vec::new()
+            } else {
+                vec![value.to_string()]
+            }),
+
             ValueTransform::Chain(transforms) => {
-                let mut result = value.to_string();
+                let mut values = vec![value.to_string()];
                 for transform in transforms {
-                    result = transform.apply(&result)?;
+                    let mut next = Vec::with_capacity(values.len());
+                    for value in &values {
+                        next.extend(transform.apply_all(value)?);
+                    }
+                    values = next;
                 }
-                Ok(result)
+                Ok(values)
+            }
+
+            _ => self.apply(value).map(|value| vec![value]),
+        }
+    }
+
+    /// Check the transformation's regex patterns and paths
+    ///
+    /// Run when a claims mapping is stored so configuration errors surface
+    /// then rather than on every token.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ValueTransform::RegexCapture { pattern, .. }
+            | ValueTransform::RegexReplace { pattern, .. }
+            | ValueTransform::Filter(pattern)
+            | ValueTransform::Drop(pattern) => regex(pattern).map(|_| ()),
+            ValueTransform::When { pattern, then } => {
+                regex(pattern)?;
+                then.validate()
             }
+            ValueTransform::JsonParse { path: Some(path) } => ClaimPath::parse(path)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            ValueTransform::Chain(transforms) => transforms.iter().try_for_each(Self::validate),
+            _ => Ok(()),
         }
     }
 
@@ -120,6 +265,21 @@ impl ValueTransform {
     }
 }
 
+fn regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("Invalid regex pattern: {}", e))
+}
+
+/// String values of a parsed JSON document
+fn json_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Null => Vec::new(),
+        Value::String(s) => vec![s.clone()],
+        Value::Bool(_) | Value::Number(_) => vec![value.to_string()],
+        Value::Array(items) => items.iter().flat_map(json_strings).collect(),
+        Value::Object(members) => members.keys().cloned().collect(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,5 +472,105 @@ mod tests {
             group: 1,
         };
         assert!(transform.apply("test").is_err());
+        assert!(transform.validate().is_err());
+    }
+
+    #[test]
+    fn test_transform_lookup() {
+        let table = HashMap::from([
+            ("idp-admins".to_string(), "admin".to_string()),
+            ("idp-devs".to_string(), "developer".to_string()),
+        ]);
+        let transform = ValueTransform::Lookup {
+            table: table.clone(),
+            default: Some("viewer".to_string()),
+        };
+        assert_eq!(transform.apply("idp-devs").unwrap(), "developer");
+        assert_eq!(transform.apply("other").unwrap(), "viewer");
+
+        let transform = ValueTransform::Lookup {
+            table,
+            default: None,
+        };
+        assert!(transform.apply_all("other").unwrap().is_empty());
+        assert!(transform.apply("other").is_err());
+    }
+
+    #[test]
+    fn test_transform_when() {
+        let transform = ValueTransform::When {
+            pattern: "^/".to_string(),
+            then: Box::new(ValueTransform::SplitLast("/".to_string())),
+        };
+        assert_eq!(transform.apply("/acme/engineering").unwrap(), "engineering");
+        assert_eq!(transform.apply("sales").unwrap(), "sales");
+    }
+
+    #[test]
+    fn test_transform_json_parse() {
+        let transform = ValueTransform::JsonParse {
+            path: Some("roles".to_string()),
+        };
+        assert_eq!(
+            transform
+                .apply_all(r#"{"roles": ["admin", "viewer"]}"#)
+                .unwrap(),
+            vec!["admin", "viewer"]
+        );
+        assert!(transform.apply_all(r#"{"groups": []}"#).unwrap().is_empty());
+        assert!(transform.apply_all("not json").is_err());
+
+        let transform = ValueTransform::JsonParse { path: None };
+        assert_eq!(transform.apply("42").unwrap(), "42");
+    }
+
+    #[test]
+    fn test_transform_base64_decode() {
+        let transform = ValueTransform::Base64Decode;
+        assert_eq!(transform.apply("YWRtaW4=").unwrap(), "admin");
+        // URL-safe alphabet without padding
+        assert_eq!(transform.apply("Pz8_").unwrap(), "???");
+        assert!(transform.apply("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_transform_split_filter_and_drop() {
+        let transform = ValueTransform::Chain(vec![
+            ValueTransform::Split(",".to_string()),
+            ValueTransform::Filter("^app-".to_string()),
+            ValueTransform::Drop("-test$".to_string()),
+            ValueTransform::RegexReplace {
+                pattern: "^app-".to_string(),
+                replacement: "".to_string(),
+            },
+        ]);
+        assert_eq!(
+            transform
+                .apply_all("app-admin, other, app-sandbox-test,app-billing")
+                .unwrap(),
+            vec!["admin", "billing"]
+        );
+        assert_eq!(transform.apply("other,app-billing").unwrap(), "billing");
+    }
+
+    #[test]
+    fn test_transform_embedded_claim_chain_from_json() {
+        let transform: ValueTransform = serde_json::from_str(
+            r#"{"type": "Chain", "value": [
+                {"type": "Base64Decode"},
+                {"type": "JsonParse", "value": {"path": "groups"}},
+                {"type": "Lookup", "value": {"table": {"g-1": "admin"}}}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(transform.validate().is_ok());
+
+        // {"groups":["g-1","g-2"]}
+        let encoded = "eyJncm91cHMiOlsiZy0xIiwiZy0yIl19";
+        assert_eq!(transform.apply_all(encoded).unwrap(), vec!["admin"]);
+
+        let round_trip: ValueTransform =
+            serde_json::from_str(&serde_json::to_string(&transform).unwrap()).unwrap();
+        assert_eq!(round_trip.apply_all(encoded).unwrap(), vec!["admin"]);
     }
 }
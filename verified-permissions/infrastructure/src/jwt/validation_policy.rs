@@ -0,0 +1,269 @@
+//! Per-identity-source token validation policy
+//!
+//! The signature, issuer and expiration are always checked. The policy
+//! tunes the time checks and adds checks on the audience and on arbitrary
+//! claims, and says whether tokens must be sender-constrained. It is stored
+//! with the identity source's configuration under `token_validation`.
+
+use crate::error::{AuthorizationError, Result};
+use crate::jwt::{ClaimPath, SenderConstraint, ValidatedClaims};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default leeway for `exp`, `nbf` and `iat`, in seconds
+pub const DEFAULT_CLOCK_SKEW_SECONDS: u64 = 60;
+
+/// Claim that must name one of the identity source's client IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudienceCheck {
+    /// `aud` must contain a client ID
+    Aud,
+    /// `client_id` must be a client ID (OAuth access tokens, e.g. Cognito)
+    ClientId,
+    /// Either of the above
+    AudOrClientId,
+}
+
+/// Claim a token must carry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequiredClaim {
+    /// Claim name or path (e.g. "token_use", "azp", "realm_access.roles")
+    pub claim: String,
+
+    /// Accepted values; empty accepts any value. An array claim matches when
+    /// one of its elements is accepted.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// How the tokens of an identity source are validated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenValidationPolicy {
+    /// Leeway for `exp`, `nbf` and `iat`, in seconds (default: 60)
+    pub clock_skew_seconds: u64,
+
+    /// Reject tokens used before their `nbf` (default: true)
+    pub validate_nbf: bool,
+
+    /// Reject tokens issued longer ago than this, from `iat`
+    pub max_token_age_seconds: Option<u64>,
+
+    /// Claims that must be present, optionally with given values
+    pub required_claims: Vec<RequiredClaim>,
+
+    /// Where the client ID is checked; `None` uses the identity source
+    /// default (`aud`, or `aud` or `client_id` for Cognito)
+    pub audience_check: Option<AudienceCheck>,
+
+    /// Whether tokens bound with `cnf` need proof of possession (DPoP or a
+    /// client certificate) (default: disabled)
+    pub sender_constraint: SenderConstraint,
+}
+
+impl Default for TokenValidationPolicy {
+    fn default() -> Self {
+        Self {
+            clock_skew_seconds: DEFAULT_CLOCK_SKEW_SECONDS,
+            validate_nbf: true,
+            max_token_age_seconds: None,
+            required_claims: Vec::new(),
+            audience_check: None,
+            sender_constraint: SenderConstraint::Disabled,
+        }
+    }
+}
+
+impl TokenValidationPolicy {
+    /// Checks the claims that signature validation does not cover: token
+    /// age, audience or client ID, and required claims
+    ///
+    /// No client ID is checked when `client_ids` is empty.
+    pub fn check_claims(&self, claims: &ValidatedClaims, client_ids: &[String]) -> Result<()> {
+        self.check_claims_at(claims, client_ids, jsonwebtoken::get_current_timestamp())
+    }
+
+    fn check_claims_at(
+        &self,
+        claims: &ValidatedClaims,
+        client_ids: &[String],
+        now: u64,
+    ) -> Result<()> {
+        if let Some(max_age) = self.max_token_age_seconds {
+            let now = now as i64;
+            let skew = self.clock_skew_seconds as i64;
+            if claims.iat > now + skew {
+                return Err(rejected("token issued in the future (iat)".to_string()));
+            }
+            if now - claims.iat > max_age as i64 + skew {
+                return Err(rejected(format!(
+                    "token is older than {} seconds (iat)",
+                    max_age
+                )));
+            }
+        }
+
+        if !client_ids.is_empty() {
+            let accepted = |id: &str| client_ids.iter().any(|client_id| client_id == id);
+            let aud = || claims.aud.iter().any(|aud| accepted(aud));
+            let client_id = || {
+                claims
+                    .additional_claims
+                    .get("client_id")
+                    .and_then(Value::as_str)
+                    .is_some_and(accepted)
+            };
+            let (matches, expected) = match self.audience_check.unwrap_or(AudienceCheck::Aud) {
+                AudienceCheck::Aud => (aud(), "aud"),
+                AudienceCheck::ClientId => (client_id(), "client_id"),
+                AudienceCheck::AudOrClientId => (aud() || client_id(), "aud or client_id"),
+            };
+            if !matches {
+                return Err(rejected(format!(
+                    "{} does not name an allowed client ID",
+                    expected
+                )));
+            }
+        }
+
+        for required in &self.required_claims {
+            let value = claim_value(claims, &required.claim)
+                .ok_or_else(|| rejected(format!("missing required claim '{}'", required.claim)))?;
+            if !required.values.is_empty() && !value_matches(value, &required.values) {
+                return Err(rejected(format!(
+                    "claim '{}' must be one of: {}",
+                    required.claim,
+                    required.values.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn rejected(reason: String) -> AuthorizationError {
+    AuthorizationError::Unauthenticated(format!("Token rejected: {}", reason))
+}
+
+/// Value of a claim, including the registered claims kept outside
+/// `additional_claims`
+fn claim_value(claims: &ValidatedClaims, name: &str) -> Option<Value> {
+    match name {
+        "sub" => Some(Value::from(claims.sub.as_str())),
+        "iss" => Some(Value::from(claims.iss.as_str())),
+        "aud" => Some(Value::from(claims.aud.clone())),
+        "exp" => Some(Value::from(claims.exp)),
+        "iat" => Some(Value::from(claims.iat)),
+        _ => ClaimPath::lookup(|name| claims.additional_claims.get(name), name),
+    }
+}
+
+fn value_matches(value: Value, accepted: &[String]) -> bool {
+    match value {
+        Value::Array(items) => items.into_iter().any(|item| value_matches(item, accepted)),
+        Value::String(s) => accepted.contains(&s),
+        Value::Null => false,
+        other => accepted.contains(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn claims(extra: Value) -> ValidatedClaims {
+        let additional_claims: HashMap<String, Value> = serde_json::from_value(extra).unwrap();
+        ValidatedClaims {
+            sub: "user123".to_string(),
+            iss: "https://issuer.example.com".to_string(),
+            aud: vec!["web".to_string()],
+            exp: NOW as i64 + 3600,
+            iat: NOW as i64 - 600,
+            additional_claims,
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let policy: TokenValidationPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(policy, TokenValidationPolicy::default());
+        assert_eq!(policy.clock_skew_seconds, DEFAULT_CLOCK_SKEW_SECONDS);
+        assert!(policy.validate_nbf);
+    }
+
+    #[test]
+    fn test_max_token_age() {
+        let policy = TokenValidationPolicy {
+            max_token_age_seconds: Some(300),
+            clock_skew_seconds: 0,
+            ..Default::default()
+        };
+        let token = claims(serde_json::json!({}));
+        assert!(policy.check_claims_at(&token, &[], NOW).is_err());
+        assert!(policy.check_claims_at(&token, &[], NOW - 400).is_ok());
+        // Issued after "now"
+        assert!(policy.check_claims_at(&token, &[], NOW - 700).is_err());
+    }
+
+    #[test]
+    fn test_audience_check() {
+        let client_ids = vec!["web".to_string()];
+        let access = ValidatedClaims {
+            aud: Vec::new(),
+            ..claims(serde_json::json!({ "client_id": "web" }))
+        };
+        let id = claims(serde_json::json!({}));
+
+        let aud = TokenValidationPolicy::default();
+        assert!(aud.check_claims_at(&id, &client_ids, NOW).is_ok());
+        assert!(aud.check_claims_at(&access, &client_ids, NOW).is_err());
+
+        let client_id = TokenValidationPolicy {
+            audience_check: Some(AudienceCheck::ClientId),
+            ..Default::default()
+        };
+        assert!(client_id.check_claims_at(&access, &client_ids, NOW).is_ok());
+        assert!(client_id.check_claims_at(&id, &client_ids, NOW).is_err());
+
+        let either = TokenValidationPolicy {
+            audience_check: Some(AudienceCheck::AudOrClientId),
+            ..Default::default()
+        };
+        assert!(either.check_claims_at(&id, &client_ids, NOW).is_ok());
+        assert!(either.check_claims_at(&access, &client_ids, NOW).is_ok());
+        let mobile = vec!["mobile".to_string()];
+        assert!(either.check_claims_at(&id, &mobile, NOW).is_err());
+    }
+
+    #[test]
+    fn test_required_claims() {
+        let policy = TokenValidationPolicy {
+            required_claims: vec![
+                RequiredClaim {
+                    claim: "token_use".to_string(),
+                    values: vec!["access".to_string()],
+                },
+                RequiredClaim {
+                    claim: "azp".to_string(),
+                    values: Vec::new(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let ok = claims(serde_json::json!({ "token_use": "access", "azp": "web" }));
+        assert!(policy.check_claims_at(&ok, &[], NOW).is_ok());
+
+        let id_token = claims(serde_json::json!({ "token_use": "id", "azp": "web" }));
+        assert!(policy.check_claims_at(&id_token, &[], NOW).is_err());
+
+        let no_azp = claims(serde_json::json!({ "token_use": "access" }));
+        let error = policy.check_claims_at(&no_azp, &[], NOW).unwrap_err();
+        assert!(error.to_string().contains("azp"));
+    }
+}
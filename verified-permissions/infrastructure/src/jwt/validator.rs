@@ -1,133 +1,126 @@
 //! JWT token validation
 
 use crate::error::{AuthorizationError, Result};
+use crate::jwt::algorithms::AlgorithmAllowlist;
+use crate::jwt::jwks_cache::JwksCache;
+use crate::jwt::validation_policy::TokenValidationPolicy;
 use crate::jwt::ValidatedClaims;
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
-use serde_json::Value;
-use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
-/// JWT Validator with JWKS caching
+/// JWT Validator backed by a shared JWKS cache
 pub struct JwtValidator {
-    /// HTTP client for fetching JWKS
-    client: reqwest::Client,
-    
-    /// Cache of public keys by key ID
-    key_cache: Arc<RwLock<HashMap<String, DecodingKey>>>,
+    /// Signing keys by issuer, with rotation handling and metrics
+    jwks: Arc<JwksCache>,
 }
 
 impl JwtValidator {
-    /// Create a new JWT validator
+    /// Create a new JWT validator with its own JWKS cache
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            key_cache: Arc::new(RwLock::new(HashMap::new())),
-        }
+        Self::with_jwks_cache(Arc::new(JwksCache::new()))
+    }
+
+    /// Create a JWT validator that looks keys up in the given cache
+    pub fn with_jwks_cache(jwks: Arc<JwksCache>) -> Self {
+        Self { jwks }
+    }
+
+    /// The JWKS cache used by this validator
+    pub fn jwks_cache(&self) -> &Arc<JwksCache> {
+        &self.jwks
     }
 
     /// Validate a JWT token
+    ///
+    /// Signing keys come from `jwks_uri`, or from the JWKS URI found through
+    /// OIDC discovery of `expected_issuer` when it is `None`. Tokens whose
+    /// `alg` is not in `algorithms` are rejected before any key is looked up.
+    /// `policy` sets the time checks and which claims must name one of
+    /// `client_ids`, and lists other required claims.
     pub async fn validate_token(
         &self,
         token: &str,
         expected_issuer: &str,
-        expected_audiences: &[String],
-        jwks_uri: &str,
+        client_ids: &[String],
+        jwks_uri: Option<&str>,
+        algorithms: &AlgorithmAllowlist,
+        policy: &TokenValidationPolicy,
     ) -> Result<ValidatedClaims> {
-        // Decode header to get key ID
+        // Decode header to get algorithm and key ID
         let header = decode_header(token).map_err(|e| {
             AuthorizationError::Internal(format!("Failed to decode JWT header: {}", e))
         })?;
+        algorithms.check(header.alg)?;
 
         let kid = header.kid.ok_or_else(|| {
             AuthorizationError::Internal("JWT header missing 'kid' field".to_string())
         })?;
 
         // Get decoding key (from cache or fetch)
-        let decoding_key = self.get_decoding_key(&kid, jwks_uri).await?;
+        let decoding_key = self
+            .jwks
+            .get_key_from(&kid, expected_issuer, jwks_uri)
+            .await?;
 
-        // Setup validation
-        let mut validation = Validation::new(header.alg);
-        validation.set_issuer(&[expected_issuer]);
-        validation.set_audience(expected_audiences);
-
-        // Decode and validate token
-        let token_data = decode::<ValidatedClaims>(token, &decoding_key, &validation)
-            .map_err(|e| {
-                AuthorizationError::Internal(format!("JWT validation failed: {}", e))
-            })?;
-
-        Ok(token_data.claims)
+        Self::decode_claims(
+            token,
+            &decoding_key,
+            header.alg,
+            expected_issuer,
+            client_ids,
+            policy,
+        )
     }
 
-    /// Get decoding key from cache or fetch from JWKS endpoint
-    async fn get_decoding_key(&self, kid: &str, jwks_uri: &str) -> Result<DecodingKey> {
-        // Check cache first
-        {
-            let cache = self.key_cache.read().await;
-            if let Some(key) = cache.get(kid) {
-                return Ok(key.clone());
-            }
-        }
-
-        // Fetch JWKS
-        let jwks = self.fetch_jwks(jwks_uri).await?;
-
-        // Find key with matching kid
-        let jwk = jwks
-            .get("keys")
-            .and_then(|keys| keys.as_array())
-            .and_then(|keys| {
-                keys.iter().find(|key| {
-                    key.get("kid")
-                        .and_then(|k| k.as_str())
-                        .map(|k| k == kid)
-                        .unwrap_or(false)
-                })
-            })
-            .ok_or_else(|| {
-                AuthorizationError::Internal(format!("Key with kid '{}' not found in JWKS", kid))
-            })?;
-
-        // Extract public key components
-        let n = jwk
-            .get("n")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AuthorizationError::Internal("Missing 'n' in JWK".to_string()))?;
-
-        let e = jwk
-            .get("e")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AuthorizationError::Internal("Missing 'e' in JWK".to_string()))?;
-
-        // Create decoding key from RSA components
-        let decoding_key = DecodingKey::from_rsa_components(n, e)
-            .map_err(|e| AuthorizationError::Internal(format!("Failed to create decoding key: {}", e)))?;
-
-        // Cache the key
-        {
-            let mut cache = self.key_cache.write().await;
-            cache.insert(kid.to_string(), decoding_key.clone());
-        }
+    /// Validate a JWT token signed with a shared secret (HS256/HS384/HS512)
+    pub fn validate_token_with_secret(
+        &self,
+        token: &str,
+        expected_issuer: &str,
+        client_ids: &[String],
+        secret: &[u8],
+        algorithms: &AlgorithmAllowlist,
+        policy: &TokenValidationPolicy,
+    ) -> Result<ValidatedClaims> {
+        let header = decode_header(token).map_err(|e| {
+            AuthorizationError::Internal(format!("Failed to decode JWT header: {}", e))
+        })?;
+        algorithms.check(header.alg)?;
 
-        Ok(decoding_key)
+        Self::decode_claims(
+            token,
+            &DecodingKey::from_secret(secret),
+            header.alg,
+            expected_issuer,
+            client_ids,
+            policy,
+        )
     }
 
-    /// Fetch JWKS from endpoint
-    async fn fetch_jwks(&self, jwks_uri: &str) -> Result<Value> {
-        let response = self
-            .client
-            .get(jwks_uri)
-            .send()
-            .await
-            .map_err(|e| AuthorizationError::Internal(format!("Failed to fetch JWKS: {}", e)))?;
+    /// Verifies the signature with `algorithm` alone, then issuer,
+    /// expiration and the checks of `policy`
+    fn decode_claims(
+        token: &str,
+        decoding_key: &DecodingKey,
+        algorithm: Algorithm,
+        expected_issuer: &str,
+        client_ids: &[String],
+        policy: &TokenValidationPolicy,
+    ) -> Result<ValidatedClaims> {
+        // Setup validation; the key must belong to the algorithm's family
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[expected_issuer]);
+        validation.leeway = policy.clock_skew_seconds;
+        validation.validate_nbf = policy.validate_nbf;
+        // Audience and client ID are checked by the policy
+        validation.validate_aud = false;
 
-        let jwks = response
-            .json::<Value>()
-            .await
-            .map_err(|e| AuthorizationError::Internal(format!("Failed to parse JWKS: {}", e)))?;
+        // Decode and validate token
+        let token_data = decode::<ValidatedClaims>(token, decoding_key, &validation)
+            .map_err(|e| AuthorizationError::Internal(format!("JWT validation failed: {}", e)))?;
 
-        Ok(jwks)
+        policy.check_claims(&token_data.claims, client_ids)?;
+        Ok(token_data.claims)
     }
 }
 
@@ -140,7 +133,8 @@ impl Default for JwtValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use jsonwebtoken::{encode, EncodingKey, Header};
+    use crate::jwt::test_support::{TestServer, sign_token, sign_token_with, test_jwks};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -153,10 +147,10 @@ mod tests {
         email: String,
     }
 
-    #[test]
-    fn test_jwt_validator_creation() {
+    #[tokio::test]
+    async fn test_jwt_validator_creation() {
         let validator = JwtValidator::new();
-        assert!(validator.key_cache.try_read().is_ok());
+        assert_eq!(validator.jwks_cache().cached_issuers_count().await, 0);
     }
 
     #[test]
@@ -192,8 +186,221 @@ mod tests {
         let validator = JwtValidator::new();
         
         // Verify cache starts empty
-        let cache = validator.key_cache.read().await;
-        assert_eq!(cache.len(), 0);
+        let metrics = validator.jwks_cache().metrics().await;
+        assert_eq!(metrics.hits + metrics.misses, 0);
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_shares_jwks_cache() {
+        let server = TestServer::start().await;
+        server.route("/jwks", test_jwks());
+        let jwks_uri = format!("{}/jwks", server.url);
+
+        let token = sign_token(&TestClaims {
+            sub: "user123".to_string(),
+            iss: server.url.clone(),
+            aud: vec!["test-client".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            email: "test@example.com".to_string(),
+        });
+
+        let jwks = Arc::new(JwksCache::new());
+        let audiences = vec!["test-client".to_string()];
+        let algorithms = AlgorithmAllowlist::asymmetric(&[]).unwrap();
+        let policy = TokenValidationPolicy::default();
+        for _ in 0..2 {
+            let claims = JwtValidator::with_jwks_cache(jwks.clone())
+                .validate_token(
+                    &token,
+                    &server.url,
+                    &audiences,
+                    Some(&jwks_uri),
+                    &algorithms,
+                    &policy,
+                )
+                .await
+                .unwrap();
+            assert_eq!(claims.sub, "user123");
+        }
+
+        let metrics = jwks.metrics().await;
+        assert_eq!((metrics.misses, metrics.hits), (1, 1));
+        assert_eq!(server.requests(), 1);
+    }
+
+    fn test_claims(issuer: &str) -> TestClaims {
+        TestClaims {
+            sub: "user123".to_string(),
+            iss: issuer.to_string(),
+            aud: vec!["test-client".to_string()],
+            exp: 9999999999,
+            iat: 1234567890,
+            email: "test@example.com".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_ec_okp_and_pss_tokens() {
+        let server = TestServer::start().await;
+        server.route("/jwks", test_jwks());
+        let jwks_uri = format!("{}/jwks", server.url);
+
+        let validator = JwtValidator::new();
+        let audiences = vec!["test-client".to_string()];
+        let algorithms = AlgorithmAllowlist::asymmetric(&[]).unwrap();
+        let policy = TokenValidationPolicy::default();
+        for algorithm in [Algorithm::ES256, Algorithm::EdDSA, Algorithm::PS256] {
+            let token = sign_token_with(algorithm, &test_claims(&server.url));
+            let claims = validator
+                .validate_token(
+                    &token,
+                    &server.url,
+                    &audiences,
+                    Some(&jwks_uri),
+                    &algorithms,
+                    &policy,
+                )
+                .await
+                .unwrap_or_else(|e| panic!("{:?} token rejected: {}", algorithm, e));
+            assert_eq!(claims.sub, "user123");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_algorithm_not_in_allowlist_is_rejected() {
+        let server = TestServer::start().await;
+        server.route("/jwks", test_jwks());
+        let jwks_uri = format!("{}/jwks", server.url);
+        let validator = JwtValidator::new();
+        let audiences = vec!["test-client".to_string()];
+        let policy = TokenValidationPolicy::default();
+
+        // Same RSA key, but the source only accepts PS256
+        let pss_only = AlgorithmAllowlist::asymmetric(&["PS256".to_string()]).unwrap();
+        let token = sign_token_with(Algorithm::RS256, &test_claims(&server.url));
+        let error = validator
+            .validate_token(
+                &token,
+                &server.url,
+                &audiences,
+                Some(&jwks_uri),
+                &pss_only,
+                &policy,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AuthorizationError::Unauthenticated(_)));
+        assert_eq!(server.requests(), 0);
+
+        // An HMAC token cannot reach a JWKS-backed source
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(crate::jwt::test_support::TEST_KID.to_string());
+        let forged = encode(
+            &header,
+            &test_claims(&server.url),
+            &EncodingKey::from_secret(b"public-key-bytes"),
+        )
+        .unwrap();
+        let algorithms = AlgorithmAllowlist::asymmetric(&[]).unwrap();
+        let result = validator
+            .validate_token(
+                &forged,
+                &server.url,
+                &audiences,
+                Some(&jwks_uri),
+                &algorithms,
+                &policy,
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_token_with_secret() {
+        let secret = b"0123456789abcdef0123456789abcdef";
+        let issuer = "https://dev.example.com";
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &test_claims(issuer),
+            &EncodingKey::from_secret(secret),
+        )
+        .unwrap();
+
+        let validator = JwtValidator::new();
+        let audiences = vec!["test-client".to_string()];
+        let hs256 = AlgorithmAllowlist::symmetric(&[]).unwrap();
+        let policy = TokenValidationPolicy::default();
+        let claims = validator
+            .validate_token_with_secret(&token, issuer, &audiences, secret, &hs256, &policy)
+            .unwrap();
+        assert_eq!(claims.sub, "user123");
+
+        let wrong_secret = b"another-secret-another-secret-00";
+        let result = validator.validate_token_with_secret(
+            &token,
+            issuer,
+            &audiences,
+            wrong_secret,
+            &hs256,
+            &policy,
+        );
+        assert!(result.is_err());
+
+        let hs512 = AlgorithmAllowlist::symmetric(&["HS512".to_string()]).unwrap();
+        let result = validator
+            .validate_token_with_secret(&token, issuer, &audiences, secret, &hs512, &policy);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_nbf_and_clock_skew() {
+        let secret = b"0123456789abcdef0123456789abcdef";
+        let issuer = "https://dev.example.com";
+        let now = jsonwebtoken::get_current_timestamp() as i64;
+        let token = |nbf: i64, exp: i64| {
+            encode(
+                &Header::new(Algorithm::HS256),
+                &serde_json::json!({
+                    "sub": "user123",
+                    "iss": issuer,
+                    "aud": "test-client",
+                    "iat": now,
+                    "nbf": nbf,
+                    "exp": exp
+                }),
+                &EncodingKey::from_secret(secret),
+            )
+            .unwrap()
+        };
+
+        let validator = JwtValidator::new();
+        let audiences = vec!["test-client".to_string()];
+        let hs256 = AlgorithmAllowlist::symmetric(&[]).unwrap();
+        let validate = |token: &str, policy: &TokenValidationPolicy| {
+            validator.validate_token_with_secret(token, issuer, &audiences, secret, &hs256, policy)
+        };
+
+        // Expired 30s ago and not yet valid for 30s: within the default skew
+        let skewed = token(now + 30, now - 30);
+        assert!(validate(&skewed, &TokenValidationPolicy::default()).is_ok());
+
+        let strict = TokenValidationPolicy {
+            clock_skew_seconds: 0,
+            ..Default::default()
+        };
+        assert!(validate(&skewed, &strict).is_err());
+
+        // Not yet valid but unexpired: only the nbf check can reject it
+        let not_yet_valid = token(now + 30, now + 300);
+        assert!(validate(&not_yet_valid, &strict).is_err());
+
+        let ignore_nbf = TokenValidationPolicy {
+            clock_skew_seconds: 0,
+            validate_nbf: false,
+            ..Default::default()
+        };
+        assert!(validate(&not_yet_valid, &ignore_nbf).is_ok());
     }
 
     #[test]
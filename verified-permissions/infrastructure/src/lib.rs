@@ -3,6 +3,8 @@
 //! This layer contains implementations of domain interfaces (repositories)
 //! and external service integrations (database, cache, JWT, etc.).
 
+pub mod cache;
+pub mod decision_log;
 pub mod error;
 pub mod events;
 pub mod factory;
@@ -11,9 +13,10 @@ pub mod repository;
 
 // TODO: These modules need updating to use new crate structure
 // Temporarily commented out to allow compilation
-// pub mod cache;
 // pub mod config;
 
+pub use cache::{CacheManager, CompiledPolicyStore, PolicyStoreCache};
+pub use decision_log::{DecisionLogConfig, DecisionLogWriter};
 #[cfg(feature = "postgres")]
 pub use events::PostgresEventStore;
 pub use events::{EventStoreBox, EventStoreType, InMemoryEventBus, SqliteEventStore};
@@ -2,9 +2,10 @@
 
 use async_trait::async_trait;
 use hodei_domain::{
-    CedarPolicy, DomainError, DomainResult, IdentitySource, IdentitySourceType,
-    Policy, PolicyId, PolicyRepository, PolicyStore, PolicyStoreId, PolicyTemplate,
-    RollbackResult, Schema, Snapshot, SnapshotPolicy,
+    CedarPolicy, DecisionLog, DecisionLogFilter, DomainError, DomainResult, EntityKey,
+    IdentitySource, IdentitySourceType, Policy, PolicyId, PolicyRepository, PolicyStore,
+    PolicyStoreId, PolicyTemplate, Principal, Resource, RevokedClaim, RollbackResult, Schema,
+    Snapshot, SnapshotPolicy, StoredEntity, TemplateLink, TokenRevocation,
 };
 use serde_json;
 
@@ -37,8 +38,9 @@ impl RepositoryAdapter {
             tags,
             created_at: model.created_at,
             updated_at: model.updated_at,
-            default_identity_source_id: None,
+            default_identity_source_id: model.default_identity_source_id,
             identity_source_ids: Vec::new(),
+            strict_validation: model.strict_validation,
         })
     }
 
@@ -56,11 +58,20 @@ impl RepositoryAdapter {
         let policy_store_id = PolicyStoreId::new(model.policy_store_id)?;
         let policy_id = PolicyId::new(model.policy_id)?;
         let statement = CedarPolicy::new(model.statement)?;
+        let template_link = match model.policy_template_id {
+            Some(policy_template_id) => Some(TemplateLink {
+                policy_template_id,
+                principal: model.template_principal.map(Principal::new).transpose()?,
+                resource: model.template_resource.map(Resource::new).transpose()?,
+            }),
+            None => None,
+        };
         Ok(Policy {
             policy_store_id,
             policy_id,
             statement,
             description: model.description,
+            template_link,
             created_at: model.created_at,
             updated_at: model.updated_at,
         })
@@ -93,6 +104,107 @@ impl RepositoryAdapter {
         })
     }
 
+    fn map_entity(model: models::Entity) -> DomainResult<StoredEntity> {
+        let policy_store_id = PolicyStoreId::new(model.policy_store_id)?;
+        let attributes = serde_json::from_str(&model.attributes_json)
+            .map_err(|e| DomainError::Internal(format!("Invalid stored attributes: {}", e)))?;
+        let parents = serde_json::from_str(&model.parents_json)
+            .map_err(|e| DomainError::Internal(format!("Invalid stored parents: {}", e)))?;
+        Ok(StoredEntity {
+            policy_store_id,
+            key: EntityKey::new(model.entity_type, model.entity_id),
+            attributes,
+            parents,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        })
+    }
+
+    fn entity_model(entity: &StoredEntity) -> DomainResult<models::Entity> {
+        let attributes_json = serde_json::to_string(&entity.attributes)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        let parents_json = serde_json::to_string(&entity.parents)
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        Ok(models::Entity {
+            policy_store_id: entity.policy_store_id.to_string(),
+            entity_type: entity.key.entity_type.clone(),
+            entity_id: entity.key.entity_id.clone(),
+            attributes_json,
+            parents_json,
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+        })
+    }
+
+    fn map_token_revocation(model: models::TokenRevocation) -> DomainResult<TokenRevocation> {
+        Ok(TokenRevocation {
+            policy_store_id: PolicyStoreId::new(model.policy_store_id)?,
+            claim: RevokedClaim::try_from(model.claim)?,
+            value: model.value,
+            expires_at: model.expires_at,
+            reason: model.reason,
+            created_at: model.created_at,
+        })
+    }
+
+    fn token_revocation_model(revocation: &TokenRevocation) -> models::TokenRevocation {
+        models::TokenRevocation {
+            policy_store_id: revocation.policy_store_id.to_string(),
+            claim: revocation.claim.to_string(),
+            value: revocation.value.clone(),
+            expires_at: revocation.expires_at,
+            reason: revocation.reason.clone(),
+            created_at: revocation.created_at,
+        }
+    }
+
+    fn map_decision_log(model: models::AuthorizationLog) -> DomainResult<DecisionLog> {
+        let policy_store_id = PolicyStoreId::new(model.policy_store_id)?;
+        let parse_error = |e: serde_json::Error| {
+            DomainError::Internal(format!("Invalid stored decision log: {}", e))
+        };
+        Ok(DecisionLog {
+            id: Some(model.id),
+            policy_store_id,
+            principal: model.principal,
+            action: model.action,
+            resource: model.resource,
+            decision: model.decision,
+            determining_policies: serde_json::from_str(&model.determining_policies_json)
+                .map_err(parse_error)?,
+            errors: serde_json::from_str(&model.errors_json).map_err(parse_error)?,
+            latency_micros: model.latency_us.max(0) as u64,
+            caller: serde_json::from_str(&model.caller_json).map_err(parse_error)?,
+            correlation_id: model.correlation_id,
+            timestamp: model.timestamp,
+        })
+    }
+
+    fn decision_log_model(log: &DecisionLog) -> DomainResult<models::AuthorizationLog> {
+        let to_json = |e: serde_json::Error| DomainError::Internal(e.to_string());
+        Ok(models::AuthorizationLog {
+            id: log.id.unwrap_or_default(),
+            policy_store_id: log.policy_store_id.to_string(),
+            principal: log.principal.clone(),
+            action: log.action.clone(),
+            resource: log.resource.clone(),
+            decision: log.decision.clone(),
+            determining_policies_json: serde_json::to_string(&log.determining_policies)
+                .map_err(to_json)?,
+            errors_json: serde_json::to_string(&log.errors).map_err(to_json)?,
+            latency_us: i64::try_from(log.latency_micros).unwrap_or(i64::MAX),
+            caller_json: serde_json::to_string(&log.caller).map_err(to_json)?,
+            correlation_id: log.correlation_id.clone(),
+            timestamp: log.timestamp,
+        })
+    }
+
+    fn entity_key_pairs(keys: &[EntityKey]) -> Vec<(String, String)> {
+        keys.iter()
+            .map(|key| (key.entity_type.clone(), key.entity_id.clone()))
+            .collect()
+    }
+
     fn cedar_statement(statement: &CedarPolicy) -> String {
         statement.as_str().to_string()
     }
@@ -109,6 +221,8 @@ impl RepositoryAdapter {
         match ty {
             IdentitySourceType::Cognito => "cognito",
             IdentitySourceType::Oidc => "oidc",
+            IdentitySourceType::SharedSecret => "shared_secret",
+            IdentitySourceType::Introspection => "introspection",
         }
     }
 }
@@ -133,9 +247,10 @@ impl PolicyRepository for RepositoryAdapter {
     async fn get_policy_store(&self, id: &PolicyStoreId) -> DomainResult<PolicyStore> {
         let model = self
             .sqlite_repo
-            .get_policy_store(Self::policy_store_id_str(id))
+            .find_policy_store(Self::policy_store_id_str(id))
             .await
-            .map_err(|e| DomainError::Internal(e.to_string()))?;
+            .map_err(|e| DomainError::Internal(e.to_string()))?
+            .ok_or_else(|| DomainError::PolicyStoreNotFound(id.to_string()))?;
         Self::map_policy_store(model)
     }
 
@@ -154,10 +269,19 @@ impl PolicyRepository for RepositoryAdapter {
         name: Option<String>,
         description: Option<String>,
         status: Option<String>,
+        strict_validation: Option<bool>,
+        default_identity_source_id: Option<Option<String>>,
     ) -> DomainResult<PolicyStore> {
         let model = self
             .sqlite_repo
-            .update_policy_store(Self::policy_store_id_str(id), name, description, status)
+            .update_policy_store(
+                Self::policy_store_id_str(id),
+                name,
+                description,
+                status,
+                strict_validation,
+                default_identity_source_id.as_ref().map(Option::as_deref),
+            )
             .await
             .map_err(|e| DomainError::Internal(e.to_string()))?;
         Self::map_policy_store(model)
@@ -273,6 +397,58 @@ impl PolicyRepository for RepositoryAdapter {
         Self::map_policy(model)
     }
 
+    async fn create_template_linked_policy(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_id: &PolicyId,
+        link: &TemplateLink,
+        statement: &CedarPolicy,
+        description: Option<String>,
+    ) -> DomainResult<Policy> {
+        let model = self
+            .sqlite_repo
+            .create_template_linked_policy(
+                Self::policy_store_id_str(policy_store_id),
+                Self::policy_id_str(policy_id),
+                Self::cedar_statement(statement),
+                models::TemplateLink {
+                    policy_template_id: link.policy_template_id.clone(),
+                    principal: link.principal.as_ref().map(|p| p.as_str().to_string()),
+                    resource: link.resource.as_ref().map(|r| r.as_str().to_string()),
+                },
+                description,
+            )
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        Self::map_policy(model)
+    }
+
+    async fn update_template_linked_policy(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        policy_id: &PolicyId,
+        link: &TemplateLink,
+        statement: &CedarPolicy,
+        description: Option<String>,
+    ) -> DomainResult<Policy> {
+        let model = self
+            .sqlite_repo
+            .update_template_linked_policy(
+                Self::policy_store_id_str(policy_store_id),
+                Self::policy_id_str(policy_id),
+                Self::cedar_statement(statement),
+                models::TemplateLink {
+                    policy_template_id: link.policy_template_id.clone(),
+                    principal: link.principal.as_ref().map(|p| p.as_str().to_string()),
+                    resource: link.resource.as_ref().map(|r| r.as_str().to_string()),
+                },
+                description,
+            )
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        Self::map_policy(model)
+    }
+
     async fn delete_policy(
         &self,
         policy_store_id: &PolicyStoreId,
@@ -410,6 +586,143 @@ impl PolicyRepository for RepositoryAdapter {
         Ok(())
     }
 
+    // Entity Store Operations
+    async fn put_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        entities: &[StoredEntity],
+    ) -> DomainResult<()> {
+        let models = entities
+            .iter()
+            .map(Self::entity_model)
+            .collect::<DomainResult<Vec<_>>>()?;
+        self.sqlite_repo
+            .put_entities(Self::policy_store_id_str(policy_store_id), &models)
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))
+    }
+
+    async fn get_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[EntityKey],
+    ) -> DomainResult<Vec<StoredEntity>> {
+        let models = self
+            .sqlite_repo
+            .get_entities(
+                Self::policy_store_id_str(policy_store_id),
+                &Self::entity_key_pairs(keys),
+            )
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        models.into_iter().map(Self::map_entity).collect()
+    }
+
+    async fn list_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+    ) -> DomainResult<Vec<StoredEntity>> {
+        let models = self
+            .sqlite_repo
+            .list_entities(Self::policy_store_id_str(policy_store_id))
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        models.into_iter().map(Self::map_entity).collect()
+    }
+
+    async fn delete_entities(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[EntityKey],
+    ) -> DomainResult<u64> {
+        self.sqlite_repo
+            .delete_entities(
+                Self::policy_store_id_str(policy_store_id),
+                &Self::entity_key_pairs(keys),
+            )
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))
+    }
+
+    // Token Revocation Operations
+    async fn put_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        revocations: &[TokenRevocation],
+    ) -> DomainResult<()> {
+        let models: Vec<_> = revocations
+            .iter()
+            .map(Self::token_revocation_model)
+            .collect();
+        self.sqlite_repo
+            .put_token_revocations(Self::policy_store_id_str(policy_store_id), &models)
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))
+    }
+
+    async fn list_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+    ) -> DomainResult<Vec<TokenRevocation>> {
+        let models = self
+            .sqlite_repo
+            .list_token_revocations(Self::policy_store_id_str(policy_store_id))
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?;
+        models.into_iter().map(Self::map_token_revocation).collect()
+    }
+
+    async fn delete_token_revocations(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        keys: &[(RevokedClaim, String)],
+    ) -> DomainResult<u64> {
+        let keys: Vec<(String, String)> = keys
+            .iter()
+            .map(|(claim, value)| (claim.to_string(), value.clone()))
+            .collect();
+        self.sqlite_repo
+            .delete_token_revocations(Self::policy_store_id_str(policy_store_id), &keys)
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))
+    }
+
+    // Decision Log Operations
+    async fn record_decisions(&self, logs: &[DecisionLog]) -> DomainResult<()> {
+        let models = logs
+            .iter()
+            .map(Self::decision_log_model)
+            .collect::<DomainResult<Vec<_>>>()?;
+        self.sqlite_repo
+            .insert_authorization_logs(&models)
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))
+    }
+
+    async fn query_decision_logs(
+        &self,
+        policy_store_id: &PolicyStoreId,
+        filter: &DecisionLogFilter,
+    ) -> DomainResult<Vec<DecisionLog>> {
+        let model_filter = models::AuthorizationLogFilter {
+            start_time: filter.start_time,
+            end_time: filter.end_time,
+            principal: filter.principal.clone(),
+            action: filter.action.clone(),
+            resource: filter.resource.clone(),
+            decision: filter.decision.clone(),
+            before_id: filter.before_id,
+            limit: i64::from(filter.limit),
+        };
+        self.sqlite_repo
+            .query_authorization_logs(Self::policy_store_id_str(policy_store_id), &model_filter)
+            .await
+            .map_err(|e| DomainError::Internal(e.to_string()))?
+            .into_iter()
+            .map(Self::map_decision_log)
+            .collect()
+    }
+
     // Snapshot / Version Control Operations
     async fn create_policy_store_snapshot(
         &self,
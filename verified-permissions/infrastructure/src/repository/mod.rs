@@ -4,7 +4,9 @@ pub mod models;
 mod sqlite_repository;
 pub mod adapter;
 
-// Optional DB implementations
+// Optional DB implementations. These predate the current repository layer
+// (they still target `crate::storage`) and do not build; the entity store,
+// decision logs and token revocations are only implemented for SQLite.
 #[cfg(feature = "postgres")]
 pub mod postgres_repository;
 #[cfg(feature = "surreal")]
@@ -13,6 +13,7 @@ pub struct PolicyStore {
     pub tags: String,                // JSON serialized vector of strings
     pub identity_source_ids: String, // JSON serialized vector of strings
     pub default_identity_source_id: Option<String>,
+    pub strict_validation: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -31,15 +32,26 @@ pub struct Policy {
     pub policy_id: String,
     pub statement: String,
     pub description: Option<String>,
+    pub policy_template_id: Option<String>, // Set for template-linked policies
+    pub template_principal: Option<String>, // Cedar entity UID bound to ?principal
+    pub template_resource: Option<String>,  // Cedar entity UID bound to ?resource
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Template and slot values of a template-linked policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateLink {
+    pub policy_template_id: String,
+    pub principal: Option<String>, // Cedar entity UID bound to ?principal
+    pub resource: Option<String>,  // Cedar entity UID bound to ?resource
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdentitySource {
     pub id: String,
     pub policy_store_id: String,
-    pub configuration_type: String,          // "cognito" or "oidc"
+    pub configuration_type: String,          // "cognito", "oidc", "shared_secret" or "introspection"
     pub configuration_json: String,          // JSON serialized configuration
     pub claims_mapping_json: Option<String>, // JSON serialized claims mapping
     pub description: Option<String>,
@@ -57,6 +69,59 @@ pub struct PolicyTemplate {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Stored entity database model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entity {
+    pub policy_store_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub attributes_json: String, // JSON object of Cedar attribute values
+    pub parents_json: String,    // JSON array of {"entity_type", "entity_id"} objects
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Token revocation database model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRevocation {
+    pub policy_store_id: String,
+    pub claim: String, // "jti", "sub" or "sid"
+    pub value: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Authorization decision log database model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationLog {
+    pub id: i64,
+    pub policy_store_id: String,
+    pub principal: String,
+    pub action: String,
+    pub resource: String,
+    pub decision: String,
+    pub determining_policies_json: String, // JSON array of policy ids
+    pub errors_json: String,               // JSON array of error messages
+    pub latency_us: i64,
+    pub caller_json: String, // JSON object of caller metadata
+    pub correlation_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Filter for authorization log queries
+#[derive(Debug, Clone, Default)]
+pub struct AuthorizationLogFilter {
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub principal: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub decision: Option<String>,
+    pub before_id: Option<i64>,
+    pub limit: i64,
+}
+
 /// Snapshot database model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
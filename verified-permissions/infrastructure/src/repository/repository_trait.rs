@@ -1,9 +1,45 @@
 //! Policy Repository Trait - Abstracción para múltiples bases de datos
 
 use async_trait::async_trait;
-use crate::storage::models::{PolicyStore, Schema, Policy, IdentitySource};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::storage::models::{PolicyStore, Schema, Policy, IdentitySource, TokenRevocation};
 use crate::error::Result;
 
+/// Decisión de autorización registrada por el data plane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationLog {
+    /// Asignado por la base de datos (0 al insertar)
+    pub id: i64,
+    pub policy_store_id: String,
+    pub principal: String,
+    pub action: String,
+    pub resource: String,
+    pub decision: String,
+    /// Array JSON con los IDs de las políticas determinantes
+    pub determining_policies_json: String,
+    /// Array JSON con los errores de evaluación
+    pub errors_json: String,
+    pub latency_us: i64,
+    /// Objeto JSON con los metadatos del llamante
+    pub caller_json: String,
+    pub correlation_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Filtros para consultar decisiones; los campos vacíos no filtran
+#[derive(Debug, Clone, Default)]
+pub struct AuthorizationLogFilter {
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub principal: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub decision: Option<String>,
+    pub before_id: Option<i64>,
+    pub limit: i64,
+}
+
 /// Trait que define las operaciones de persistencia para el sistema de autorización.
 ///
 /// Este trait abstrae la capa de persistencia permitiendo múltiples implementaciones:
@@ -225,7 +261,60 @@ pub trait PolicyRepository: Send + Sync {
         identity_source_id: &str,
     ) -> Result<()>;
 
+    // ============================================================================
+    // Token Revocation Operations
+    // ============================================================================
+
+    /// Crea o reemplaza revocaciones de tokens y purga las ya expiradas del
+    /// Policy Store
+    ///
+    /// # Arguments
+    /// * `policy_store_id` - ID del Policy Store
+    /// * `revocations` - Revocaciones a guardar (upsert por claim y valor)
+    async fn put_token_revocations(
+        &self,
+        policy_store_id: &str,
+        revocations: &[TokenRevocation],
+    ) -> Result<()>;
+
+    /// Lista las revocaciones de tokens no expiradas de un Policy Store
+    ///
+    /// # Arguments
+    /// * `policy_store_id` - ID del Policy Store
+    async fn list_token_revocations(&self, policy_store_id: &str) -> Result<Vec<TokenRevocation>>;
+
+    /// Elimina revocaciones de tokens
+    ///
+    /// # Arguments
+    /// * `policy_store_id` - ID del Policy Store
+    /// * `keys` - Pares (claim, valor) a eliminar
+    ///
+    /// # Returns
+    /// Número de revocaciones eliminadas
+    async fn delete_token_revocations(
+        &self,
+        policy_store_id: &str,
+        keys: &[(String, String)],
+    ) -> Result<u64>;
+
     // ============================================================================
     // Audit Operations
     // ============================================================================
+
+    /// Registra un lote de decisiones de autorización
+    ///
+    /// # Arguments
+    /// * `logs` - Decisiones a guardar en `authorization_logs`
+    async fn log_authorizations(&self, logs: &[AuthorizationLog]) -> Result<()>;
+
+    /// Consulta las decisiones de un Policy Store, de la más reciente a la más antigua
+    ///
+    /// # Arguments
+    /// * `policy_store_id` - ID del Policy Store
+    /// * `filter` - Rango de tiempo, campos a filtrar y límite
+    async fn query_authorization_logs(
+        &self,
+        policy_store_id: &str,
+        filter: &AuthorizationLogFilter,
+    ) -> Result<Vec<AuthorizationLog>>;
 }
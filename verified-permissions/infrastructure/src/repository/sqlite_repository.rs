@@ -2,7 +2,7 @@
 
 use super::models;
 use anyhow::anyhow;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
@@ -27,6 +27,7 @@ impl SqliteRepository {
                 tags TEXT DEFAULT '[]',
                 identity_source_ids TEXT DEFAULT '[]',
                 default_identity_source_id TEXT,
+                strict_validation INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )
@@ -63,6 +64,12 @@ impl SqliteRepository {
             .execute(&pool)
             .await;
 
+        let _ = sqlx::query(
+            "ALTER TABLE policy_stores ADD COLUMN strict_validation INTEGER NOT NULL DEFAULT 0",
+        )
+        .execute(&pool)
+        .await;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS schemas (
@@ -94,6 +101,19 @@ impl SqliteRepository {
         .execute(&pool)
         .await?;
 
+        // Template-linked policies (migration for existing databases)
+        let _ = sqlx::query("ALTER TABLE policies ADD COLUMN policy_template_id TEXT")
+            .execute(&pool)
+            .await;
+
+        let _ = sqlx::query("ALTER TABLE policies ADD COLUMN template_principal TEXT")
+            .execute(&pool)
+            .await;
+
+        let _ = sqlx::query("ALTER TABLE policies ADD COLUMN template_resource TEXT")
+            .execute(&pool)
+            .await;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS identity_sources (
@@ -129,6 +149,41 @@ impl SqliteRepository {
         .execute(&pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS entities (
+                policy_store_id TEXT NOT NULL,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                attributes_json TEXT NOT NULL DEFAULT '{}',
+                parents_json TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (policy_store_id, entity_type, entity_id),
+                FOREIGN KEY (policy_store_id) REFERENCES policy_stores(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_revocations (
+                policy_store_id TEXT NOT NULL,
+                claim TEXT NOT NULL,
+                value TEXT NOT NULL,
+                expires_at TEXT,
+                reason TEXT,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (policy_store_id, claim, value),
+                FOREIGN KEY (policy_store_id) REFERENCES policy_stores(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS authorization_logs (
@@ -138,6 +193,11 @@ impl SqliteRepository {
                 action TEXT NOT NULL,
                 resource TEXT NOT NULL,
                 decision TEXT NOT NULL,
+                determining_policies TEXT NOT NULL DEFAULT '[]',
+                errors TEXT NOT NULL DEFAULT '[]',
+                latency_us INTEGER NOT NULL DEFAULT 0,
+                caller TEXT NOT NULL DEFAULT '{}',
+                correlation_id TEXT,
                 timestamp TEXT NOT NULL,
                 FOREIGN KEY (policy_store_id) REFERENCES policy_stores(id) ON DELETE CASCADE
             )
@@ -146,6 +206,37 @@ impl SqliteRepository {
         .execute(&pool)
         .await?;
 
+        // Decision log columns (migration for existing databases)
+        let _ = sqlx::query(
+            "ALTER TABLE authorization_logs ADD COLUMN determining_policies TEXT NOT NULL DEFAULT '[]'",
+        )
+        .execute(&pool)
+        .await;
+        let _ = sqlx::query(
+            "ALTER TABLE authorization_logs ADD COLUMN errors TEXT NOT NULL DEFAULT '[]'",
+        )
+        .execute(&pool)
+        .await;
+        let _ = sqlx::query(
+            "ALTER TABLE authorization_logs ADD COLUMN latency_us INTEGER NOT NULL DEFAULT 0",
+        )
+        .execute(&pool)
+        .await;
+        let _ = sqlx::query(
+            "ALTER TABLE authorization_logs ADD COLUMN caller TEXT NOT NULL DEFAULT '{}'",
+        )
+        .execute(&pool)
+        .await;
+        let _ = sqlx::query("ALTER TABLE authorization_logs ADD COLUMN correlation_id TEXT")
+            .execute(&pool)
+            .await;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_authorization_logs_store_time ON authorization_logs (policy_store_id, timestamp)",
+        )
+        .execute(&pool)
+        .await?;
+
         // Create audit log table
         sqlx::query(
             r#"
@@ -242,21 +333,31 @@ impl SqliteRepository {
             tags: tags_json,
             identity_source_ids: "[]".to_string(),
             default_identity_source_id: None,
+            strict_validation: false,
             created_at: now,
             updated_at: now,
         })
     }
 
     pub async fn get_policy_store(&self, id: &str) -> anyhow::Result<models::PolicyStore> {
-        let row = sqlx::query(
-            "SELECT id, name, description, status, author, tags, identity_source_ids, default_identity_source_id, created_at, updated_at FROM policy_stores WHERE id = ?",
+        self.find_policy_store(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Policy store not found: {}", id))
+    }
+
+    /// Gets a policy store, or `None` if it does not exist
+    pub async fn find_policy_store(&self, id: &str) -> anyhow::Result<Option<models::PolicyStore>> {
+        let Some(row) = sqlx::query(
+            "SELECT id, name, description, status, author, tags, identity_source_ids, default_identity_source_id, strict_validation, created_at, updated_at FROM policy_stores WHERE id = ?",
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Policy store not found: {}", id))?;
+        else {
+            return Ok(None);
+        };
 
-        Ok(models::PolicyStore {
+        Ok(Some(models::PolicyStore {
             id: row.get("id"),
             name: row.get("name"),
             description: row.get("description"),
@@ -265,14 +366,15 @@ impl SqliteRepository {
             tags: row.get("tags"),
             identity_source_ids: row.get("identity_source_ids"),
             default_identity_source_id: row.get("default_identity_source_id"),
+            strict_validation: row.get("strict_validation"),
             created_at: row.get::<String, _>("created_at").parse().unwrap(),
             updated_at: row.get::<String, _>("updated_at").parse().unwrap(),
-        })
+        }))
     }
 
     pub async fn list_policy_stores(&self) -> anyhow::Result<Vec<models::PolicyStore>> {
         let rows = sqlx::query(
-            "SELECT id, name, description, status, author, tags, identity_source_ids, default_identity_source_id, created_at, updated_at FROM policy_stores ORDER BY created_at DESC",
+            "SELECT id, name, description, status, author, tags, identity_source_ids, default_identity_source_id, strict_validation, created_at, updated_at FROM policy_stores ORDER BY created_at DESC",
         )
         .fetch_all(&self.pool)
         .await?;
@@ -288,6 +390,7 @@ impl SqliteRepository {
                 tags: row.get("tags"),
                 identity_source_ids: row.get("identity_source_ids"),
                 default_identity_source_id: row.get("default_identity_source_id"),
+                strict_validation: row.get("strict_validation"),
                 created_at: row.get::<String, _>("created_at").parse().unwrap(),
                 updated_at: row.get::<String, _>("updated_at").parse().unwrap(),
             })
@@ -300,6 +403,8 @@ impl SqliteRepository {
         name: Option<String>,
         description: Option<String>,
         status: Option<String>,
+        strict_validation: Option<bool>,
+        default_identity_source_id: Option<Option<&str>>,
     ) -> anyhow::Result<models::PolicyStore> {
         let now = Utc::now();
 
@@ -309,13 +414,19 @@ impl SqliteRepository {
         sqlx::query(
             r#"
             UPDATE policy_stores
-            SET name = COALESCE(?, name), description = COALESCE(?, description), status = COALESCE(?, status), updated_at = ?
+            SET name = COALESCE(?, name), description = COALESCE(?, description), status = COALESCE(?, status),
+                strict_validation = COALESCE(?, strict_validation),
+                default_identity_source_id = CASE WHEN ? THEN ? ELSE default_identity_source_id END,
+                updated_at = ?
             WHERE id = ?
             "#,
         )
         .bind(name.as_ref())
         .bind(description.as_ref())
         .bind(status.as_ref())
+        .bind(strict_validation)
+        .bind(default_identity_source_id.is_some())
+        .bind(default_identity_source_id.flatten())
         .bind(now.to_rfc3339())
         .bind(id)
         .execute(&self.pool)
@@ -439,6 +550,57 @@ impl SqliteRepository {
             policy_id: policy_id.to_string(),
             statement,
             description,
+            policy_template_id: None,
+            template_principal: None,
+            template_resource: None,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub async fn create_template_linked_policy(
+        &self,
+        policy_store_id: &str,
+        policy_id: &str,
+        statement: String,
+        link: models::TemplateLink,
+        description: Option<String>,
+    ) -> anyhow::Result<models::Policy> {
+        let models::TemplateLink {
+            policy_template_id,
+            principal: template_principal,
+            resource: template_resource,
+        } = link;
+        // Verify policy store and template exist
+        self.get_policy_store(policy_store_id).await?;
+        self.get_policy_template(policy_store_id, &policy_template_id)
+            .await?;
+
+        let now = Utc::now();
+
+        sqlx::query(
+            "INSERT INTO policies (policy_store_id, policy_id, statement, description, policy_template_id, template_principal, template_resource, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(policy_store_id)
+        .bind(policy_id)
+        .bind(&statement)
+        .bind(&description)
+        .bind(&policy_template_id)
+        .bind(&template_principal)
+        .bind(&template_resource)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(models::Policy {
+            policy_store_id: policy_store_id.to_string(),
+            policy_id: policy_id.to_string(),
+            statement,
+            description,
+            policy_template_id: Some(policy_template_id),
+            template_principal,
+            template_resource,
             created_at: now,
             updated_at: now,
         })
@@ -450,7 +612,7 @@ impl SqliteRepository {
         policy_id: &str,
     ) -> anyhow::Result<models::Policy> {
         let row = sqlx::query(
-            "SELECT policy_store_id, policy_id, statement, description, created_at, updated_at FROM policies WHERE policy_store_id = ? AND policy_id = ?",
+            "SELECT policy_store_id, policy_id, statement, description, policy_template_id, template_principal, template_resource, created_at, updated_at FROM policies WHERE policy_store_id = ? AND policy_id = ?",
         )
         .bind(policy_store_id)
         .bind(policy_id)
@@ -463,6 +625,9 @@ impl SqliteRepository {
             policy_id: row.get("policy_id"),
             statement: row.get("statement"),
             description: row.get("description"),
+            policy_template_id: row.get("policy_template_id"),
+            template_principal: row.get("template_principal"),
+            template_resource: row.get("template_resource"),
             created_at: row.get::<String, _>("created_at").parse().unwrap(),
             updated_at: row.get::<String, _>("updated_at").parse().unwrap(),
         })
@@ -478,10 +643,59 @@ impl SqliteRepository {
         let now = Utc::now();
 
         let result = sqlx::query(
-            "UPDATE policies SET statement = ?, description = ?, updated_at = ? WHERE policy_store_id = ? AND policy_id = ?",
+            "UPDATE policies SET statement = ?, description = ?, policy_template_id = NULL, template_principal = NULL, template_resource = NULL, updated_at = ? WHERE policy_store_id = ? AND policy_id = ?",
+        )
+        .bind(&statement)
+        .bind(&description)
+        .bind(now.to_rfc3339())
+        .bind(policy_store_id)
+        .bind(policy_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Policy not found: {}", policy_id));
+        }
+
+        Ok(models::Policy {
+            policy_store_id: policy_store_id.to_string(),
+            policy_id: policy_id.to_string(),
+            statement,
+            description,
+            policy_template_id: None,
+            template_principal: None,
+            template_resource: None,
+            created_at: now, // We don't have the original created_at, but it's not critical
+            updated_at: now,
+        })
+    }
+
+    pub async fn update_template_linked_policy(
+        &self,
+        policy_store_id: &str,
+        policy_id: &str,
+        statement: String,
+        link: models::TemplateLink,
+        description: Option<String>,
+    ) -> anyhow::Result<models::Policy> {
+        let models::TemplateLink {
+            policy_template_id,
+            principal: template_principal,
+            resource: template_resource,
+        } = link;
+        self.get_policy_template(policy_store_id, &policy_template_id)
+            .await?;
+
+        let now = Utc::now();
+
+        let result = sqlx::query(
+            "UPDATE policies SET statement = ?, description = ?, policy_template_id = ?, template_principal = ?, template_resource = ?, updated_at = ? WHERE policy_store_id = ? AND policy_id = ?",
         )
         .bind(&statement)
         .bind(&description)
+        .bind(&policy_template_id)
+        .bind(&template_principal)
+        .bind(&template_resource)
         .bind(now.to_rfc3339())
         .bind(policy_store_id)
         .bind(policy_id)
@@ -497,6 +711,9 @@ impl SqliteRepository {
             policy_id: policy_id.to_string(),
             statement,
             description,
+            policy_template_id: Some(policy_template_id),
+            template_principal,
+            template_resource,
             created_at: now, // We don't have the original created_at, but it's not critical
             updated_at: now,
         })
@@ -526,7 +743,7 @@ impl SqliteRepository {
         policy_store_id: &str,
     ) -> anyhow::Result<Vec<models::Policy>> {
         let rows = sqlx::query(
-            "SELECT policy_store_id, policy_id, statement, description, created_at, updated_at FROM policies WHERE policy_store_id = ? ORDER BY created_at DESC",
+            "SELECT policy_store_id, policy_id, statement, description, policy_template_id, template_principal, template_resource, created_at, updated_at FROM policies WHERE policy_store_id = ? ORDER BY created_at DESC",
         )
         .bind(policy_store_id)
         .fetch_all(&self.pool)
@@ -539,6 +756,9 @@ impl SqliteRepository {
                 policy_id: row.get("policy_id"),
                 statement: row.get("statement"),
                 description: row.get("description"),
+                policy_template_id: row.get("policy_template_id"),
+                template_principal: row.get("template_principal"),
+                template_resource: row.get("template_resource"),
                 created_at: row.get::<String, _>("created_at").parse().unwrap(),
                 updated_at: row.get::<String, _>("updated_at").parse().unwrap(),
             })
@@ -662,6 +882,15 @@ impl SqliteRepository {
             ));
         }
 
+        // A deleted identity source can no longer be the store default
+        sqlx::query(
+            "UPDATE policy_stores SET default_identity_source_id = NULL WHERE id = ? AND default_identity_source_id = ?",
+        )
+        .bind(policy_store_id)
+        .bind(identity_source_id)
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
@@ -763,6 +992,24 @@ impl SqliteRepository {
         policy_store_id: &str,
         template_id: &str,
     ) -> anyhow::Result<()> {
+        // Refuse to orphan template-linked policies
+        let linked: i64 = sqlx::query(
+            "SELECT COUNT(*) AS count FROM policies WHERE policy_store_id = ? AND policy_template_id = ?",
+        )
+        .bind(policy_store_id)
+        .bind(template_id)
+        .fetch_one(&self.pool)
+        .await?
+        .get("count");
+
+        if linked > 0 {
+            return Err(anyhow::anyhow!(
+                "Policy template {} is still linked by {} policies",
+                template_id,
+                linked
+            ));
+        }
+
         let result = sqlx::query(
             "DELETE FROM policy_templates WHERE policy_store_id = ? AND template_id = ?",
         )
@@ -778,6 +1025,333 @@ impl SqliteRepository {
         Ok(())
     }
 
+    // ========================================================================
+    // Entity Store Operations
+    // ========================================================================
+
+    pub async fn put_entities(
+        &self,
+        policy_store_id: &str,
+        entities: &[models::Entity],
+    ) -> anyhow::Result<()> {
+        // Verify policy store exists
+        self.get_policy_store(policy_store_id).await?;
+
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        for entity in entities {
+            sqlx::query(
+                r#"
+                INSERT INTO entities (policy_store_id, entity_type, entity_id, attributes_json, parents_json, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(policy_store_id, entity_type, entity_id) DO UPDATE SET
+                    attributes_json = excluded.attributes_json,
+                    parents_json = excluded.parents_json,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(policy_store_id)
+            .bind(&entity.entity_type)
+            .bind(&entity.entity_id)
+            .bind(&entity.attributes_json)
+            .bind(&entity.parents_json)
+            .bind(now.to_rfc3339())
+            .bind(now.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_entities(
+        &self,
+        policy_store_id: &str,
+        keys: &[(String, String)],
+    ) -> anyhow::Result<Vec<models::Entity>> {
+        let mut entities = Vec::with_capacity(keys.len());
+
+        for (entity_type, entity_id) in keys {
+            let row = sqlx::query(
+                "SELECT policy_store_id, entity_type, entity_id, attributes_json, parents_json, created_at, updated_at FROM entities WHERE policy_store_id = ? AND entity_type = ? AND entity_id = ?",
+            )
+            .bind(policy_store_id)
+            .bind(entity_type)
+            .bind(entity_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some(row) = row {
+                entities.push(Self::map_entity_row(&row));
+            }
+        }
+
+        Ok(entities)
+    }
+
+    pub async fn list_entities(&self, policy_store_id: &str) -> anyhow::Result<Vec<models::Entity>> {
+        let rows = sqlx::query(
+            "SELECT policy_store_id, entity_type, entity_id, attributes_json, parents_json, created_at, updated_at FROM entities WHERE policy_store_id = ? ORDER BY entity_type, entity_id",
+        )
+        .bind(policy_store_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(Self::map_entity_row).collect())
+    }
+
+    pub async fn delete_entities(
+        &self,
+        policy_store_id: &str,
+        keys: &[(String, String)],
+    ) -> anyhow::Result<u64> {
+        let mut deleted = 0;
+        let mut tx = self.pool.begin().await?;
+
+        for (entity_type, entity_id) in keys {
+            let result = sqlx::query(
+                "DELETE FROM entities WHERE policy_store_id = ? AND entity_type = ? AND entity_id = ?",
+            )
+            .bind(policy_store_id)
+            .bind(entity_type)
+            .bind(entity_id)
+            .execute(&mut *tx)
+            .await?;
+
+            deleted += result.rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    fn map_entity_row(row: &sqlx::sqlite::SqliteRow) -> models::Entity {
+        models::Entity {
+            policy_store_id: row.get("policy_store_id"),
+            entity_type: row.get("entity_type"),
+            entity_id: row.get("entity_id"),
+            attributes_json: row.get("attributes_json"),
+            parents_json: row.get("parents_json"),
+            created_at: row.get::<String, _>("created_at").parse().unwrap(),
+            updated_at: row.get::<String, _>("updated_at").parse().unwrap(),
+        }
+    }
+
+    // ========================================================================
+    // Token Revocation Operations
+    // ========================================================================
+
+    /// Upserts revocations by (claim, value) and purges the store's expired
+    /// revocations in the same transaction
+    ///
+    /// Expiry times use the fixed-width log timestamp format, so they compare
+    /// as text.
+    pub async fn put_token_revocations(
+        &self,
+        policy_store_id: &str,
+        revocations: &[models::TokenRevocation],
+    ) -> anyhow::Result<()> {
+        // Verify policy store exists
+        self.get_policy_store(policy_store_id).await?;
+
+        let now = Self::log_timestamp(&Utc::now());
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "DELETE FROM token_revocations WHERE policy_store_id = ? AND expires_at IS NOT NULL AND expires_at <= ?",
+        )
+        .bind(policy_store_id)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+
+        for revocation in revocations {
+            sqlx::query(
+                r#"
+                INSERT INTO token_revocations (policy_store_id, claim, value, expires_at, reason, created_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT(policy_store_id, claim, value) DO UPDATE SET
+                    expires_at = excluded.expires_at,
+                    reason = excluded.reason,
+                    created_at = excluded.created_at
+                "#,
+            )
+            .bind(policy_store_id)
+            .bind(&revocation.claim)
+            .bind(&revocation.value)
+            .bind(revocation.expires_at.as_ref().map(Self::log_timestamp))
+            .bind(&revocation.reason)
+            .bind(Self::log_timestamp(&revocation.created_at))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn list_token_revocations(
+        &self,
+        policy_store_id: &str,
+    ) -> anyhow::Result<Vec<models::TokenRevocation>> {
+        let rows = sqlx::query(
+            "SELECT policy_store_id, claim, value, expires_at, reason, created_at FROM token_revocations WHERE policy_store_id = ? AND (expires_at IS NULL OR expires_at > ?) ORDER BY claim, value",
+        )
+        .bind(policy_store_id)
+        .bind(Self::log_timestamp(&Utc::now()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| models::TokenRevocation {
+                policy_store_id: row.get("policy_store_id"),
+                claim: row.get("claim"),
+                value: row.get("value"),
+                expires_at: row
+                    .get::<Option<String>, _>("expires_at")
+                    .map(|expires_at| expires_at.parse().unwrap()),
+                reason: row.get("reason"),
+                created_at: row.get::<String, _>("created_at").parse().unwrap(),
+            })
+            .collect())
+    }
+
+    pub async fn delete_token_revocations(
+        &self,
+        policy_store_id: &str,
+        keys: &[(String, String)],
+    ) -> anyhow::Result<u64> {
+        let mut deleted = 0;
+        let mut tx = self.pool.begin().await?;
+
+        for (claim, value) in keys {
+            let result = sqlx::query(
+                "DELETE FROM token_revocations WHERE policy_store_id = ? AND claim = ? AND value = ?",
+            )
+            .bind(policy_store_id)
+            .bind(claim)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+
+            deleted += result.rows_affected();
+        }
+
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    // ========================================================================
+    // Authorization Log Operations
+    // ========================================================================
+
+    /// Log timestamps have a fixed width so that they sort and compare as text
+    fn log_timestamp(timestamp: &DateTime<Utc>) -> String {
+        timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+
+    pub async fn insert_authorization_logs(
+        &self,
+        logs: &[models::AuthorizationLog],
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for log in logs {
+            sqlx::query(
+                r#"
+                INSERT INTO authorization_logs (policy_store_id, principal, action, resource, decision, determining_policies, errors, latency_us, caller, correlation_id, timestamp)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&log.policy_store_id)
+            .bind(&log.principal)
+            .bind(&log.action)
+            .bind(&log.resource)
+            .bind(&log.decision)
+            .bind(&log.determining_policies_json)
+            .bind(&log.errors_json)
+            .bind(log.latency_us)
+            .bind(&log.caller_json)
+            .bind(&log.correlation_id)
+            .bind(Self::log_timestamp(&log.timestamp))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn query_authorization_logs(
+        &self,
+        policy_store_id: &str,
+        filter: &models::AuthorizationLogFilter,
+    ) -> anyhow::Result<Vec<models::AuthorizationLog>> {
+        let start_time = filter.start_time.as_ref().map(Self::log_timestamp);
+        let end_time = filter.end_time.as_ref().map(Self::log_timestamp);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, policy_store_id, principal, action, resource, decision, determining_policies, errors, latency_us, caller, correlation_id, timestamp
+            FROM authorization_logs
+            WHERE policy_store_id = ?
+              AND (? IS NULL OR timestamp >= ?)
+              AND (? IS NULL OR timestamp < ?)
+              AND (? IS NULL OR principal = ?)
+              AND (? IS NULL OR action = ?)
+              AND (? IS NULL OR resource = ?)
+              AND (? IS NULL OR decision = ?)
+              AND (? IS NULL OR id < ?)
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(policy_store_id)
+        .bind(&start_time)
+        .bind(&start_time)
+        .bind(&end_time)
+        .bind(&end_time)
+        .bind(&filter.principal)
+        .bind(&filter.principal)
+        .bind(&filter.action)
+        .bind(&filter.action)
+        .bind(&filter.resource)
+        .bind(&filter.resource)
+        .bind(&filter.decision)
+        .bind(&filter.decision)
+        .bind(filter.before_id)
+        .bind(filter.before_id)
+        .bind(filter.limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| models::AuthorizationLog {
+                id: row.get("id"),
+                policy_store_id: row.get("policy_store_id"),
+                principal: row.get("principal"),
+                action: row.get("action"),
+                resource: row.get("resource"),
+                decision: row.get("decision"),
+                determining_policies_json: row.get("determining_policies"),
+                errors_json: row.get("errors"),
+                latency_us: row.get("latency_us"),
+                caller_json: row.get("caller"),
+                correlation_id: row.get("correlation_id"),
+                timestamp: row.get::<String, _>("timestamp").parse().unwrap(),
+            })
+            .collect())
+    }
+
     // Snapshot / Version Control Operations
     pub async fn create_policy_store_snapshot(
         &self,
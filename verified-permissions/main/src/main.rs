@@ -7,7 +7,10 @@ use hodei_api::grpc::{AuthorizationControlService, AuthorizationDataService};
 use hodei_api::proto::authorization_control_server::AuthorizationControlServer;
 use hodei_api::proto::authorization_data_server::AuthorizationDataServer;
 use hodei_domain::events::EventDispatcher;
+use hodei_infrastructure::cache::CacheManager;
+use hodei_infrastructure::decision_log::{DecisionLogConfig, DecisionLogWriter};
 use hodei_infrastructure::factory::{create_event_bus, create_event_store};
+use hodei_infrastructure::jwt::JwksCache;
 use hodei_infrastructure::repository::RepositoryAdapter;
 use hodei_shared::config::{Configuration, Settings};
 use std::sync::Arc;
@@ -53,6 +56,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     info!("✅ Repository initialized successfully");
 
+    // Compile every policy store into memory for the data plane
+    info!("🧠 Loading policy stores into cache...");
+    let cache = Arc::new(CacheManager::new(repository.clone()));
+    if let Err(e) = cache.initialize().await {
+        eprintln!("❌ Failed to initialize policy cache: {}", e);
+        return Err(e.into());
+    }
+
+    info!("✅ Policy cache initialized successfully");
+
     // Initialize Event Store infrastructure (Hexagonal Architecture - Infrastructure Layer)
     info!("🔔 Initializing event store and audit system...");
     let event_store = create_event_store(settings.database_url())
@@ -71,8 +84,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("✅ Event store and audit system initialized successfully");
 
     // Create gRPC services with repository and event dispatcher (Dependency Injection)
-    let control_service = AuthorizationControlService::new(repository.clone(), dispatcher.clone());
-    let data_service = AuthorizationDataService::new(repository.clone());
+    let control_service =
+        AuthorizationControlService::new(repository.clone(), dispatcher.clone(), cache.clone())
+            .with_shared_secret_identity_sources(settings.shared_secret_identity_sources_enabled());
+    // Token signing keys are shared by all identity sources and refreshed in the background
+    let jwks_cache = Arc::new(JwksCache::new());
+    jwks_cache.clone().start_background_refresh();
+
+    let mut data_service = AuthorizationDataService::new(repository.clone(), cache.clone())
+        .with_batch_max_concurrency(settings.batch_max_concurrency())
+        .with_jwks_cache(jwks_cache)
+        .with_shared_secret_identity_sources(settings.shared_secret_identity_sources_enabled());
+
+    // Decision log writer: batches decisions into authorization_logs off the request path
+    let mut decision_log_handle = None;
+    if settings.decision_log_enabled() {
+        let (writer, handle) = DecisionLogWriter::spawn(
+            repository.clone(),
+            DecisionLogConfig {
+                capacity: settings.decision_log_capacity(),
+                batch_size: settings.decision_log_batch_size(),
+                flush_interval: std::time::Duration::from_millis(
+                    settings.decision_log_flush_interval_ms(),
+                ),
+            },
+        );
+        data_service = data_service.with_decision_log(writer);
+        decision_log_handle = Some(handle);
+    }
 
     // Configure gRPC server
     let mut server_builder = Server::builder();
@@ -130,5 +169,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Wait for server to stop
     server_handle.await?;
 
+    // The server dropped the last writer handle: wait for queued decisions
+    if let Some(handle) = decision_log_handle {
+        let _ = handle.await;
+    }
+
     Ok(())
 }
@@ -32,6 +32,12 @@ pub trait Configuration: Send + Sync {
     fn tls_cert_path(&self) -> Option<&str>;
     fn tls_key_path(&self) -> Option<&str>;
     fn tls_client_ca_path(&self) -> Option<&str>;
+    fn batch_max_concurrency(&self) -> usize;
+    fn decision_log_enabled(&self) -> bool;
+    fn decision_log_capacity(&self) -> usize;
+    fn decision_log_batch_size(&self) -> usize;
+    fn decision_log_flush_interval_ms(&self) -> u64;
+    fn shared_secret_identity_sources_enabled(&self) -> bool;
 }
 
 /// Configuration source trait (Hexagonal Architecture - Port)
@@ -255,6 +261,34 @@ impl ConfigLoader {
             if let Some(val) = config.get("TLS_CLIENT_CA_PATH") {
                 settings.server.tls.client_ca_path = Some(val.clone());
             }
+
+            // Data plane configuration
+            if let Some(val) = config.get("BATCH_MAX_CONCURRENCY") {
+                settings.data_plane.batch_max_concurrency = val
+                    .parse()
+                    .unwrap_or(settings.data_plane.batch_max_concurrency)
+                    .max(1);
+            }
+
+            // Decision log configuration
+            if let Some(val) = config.get("DECISION_LOG_ENABLED") {
+                settings.data_plane.decision_log_enabled = val.parse().unwrap_or(true);
+            }
+            if let Some(val) = config.get("DECISION_LOG_CAPACITY") {
+                settings.data_plane.decision_log_capacity = val.parse().unwrap_or(10_000);
+            }
+            if let Some(val) = config.get("DECISION_LOG_BATCH_SIZE") {
+                settings.data_plane.decision_log_batch_size = val.parse().unwrap_or(256);
+            }
+            if let Some(val) = config.get("DECISION_LOG_FLUSH_INTERVAL_MS") {
+                settings.data_plane.decision_log_flush_interval_ms = val.parse().unwrap_or(1000);
+            }
+
+            // Identity source configuration
+            if let Some(val) = config.get("SHARED_SECRET_IDENTITY_SOURCES_ENABLED") {
+                settings.data_plane.shared_secret_identity_sources_enabled =
+                    val.parse().unwrap_or(false);
+            }
         }
 
         Ok(settings)
@@ -328,12 +362,44 @@ impl Default for GrpcConfig {
     }
 }
 
+/// Data plane configuration
+#[derive(Debug, Clone)]
+pub struct DataPlaneConfig {
+    /// Maximum number of batch items evaluated at once
+    pub batch_max_concurrency: usize,
+    /// Record every decision in the authorization_logs table
+    pub decision_log_enabled: bool,
+    /// Decisions queued for writing before new ones are dropped
+    pub decision_log_capacity: usize,
+    /// Decisions written per database batch
+    pub decision_log_batch_size: usize,
+    /// Maximum time a partial batch waits before it is written
+    pub decision_log_flush_interval_ms: u64,
+    /// Accept HMAC-signed tokens from shared-secret identity sources
+    /// (development and testing only)
+    pub shared_secret_identity_sources_enabled: bool,
+}
+
+impl Default for DataPlaneConfig {
+    fn default() -> Self {
+        Self {
+            batch_max_concurrency: 16,
+            decision_log_enabled: true,
+            decision_log_capacity: 10_000,
+            decision_log_batch_size: 256,
+            decision_log_flush_interval_ms: 1000,
+            shared_secret_identity_sources_enabled: false,
+        }
+    }
+}
+
 /// Complete application settings
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub grpc: GrpcConfig,
+    pub data_plane: DataPlaneConfig,
     pub log_level: String,
     pub shutdown_timeout: u64,
 }
@@ -365,6 +431,7 @@ impl Default for Settings {
             server: ServerConfig::default(),
             database: DatabaseConfig::default(),
             grpc: GrpcConfig::default(),
+            data_plane: DataPlaneConfig::default(),
             log_level: "info".to_string(),
             shutdown_timeout: 30,
         }
@@ -416,6 +483,30 @@ impl Configuration for Settings {
     fn tls_client_ca_path(&self) -> Option<&str> {
         self.server.tls.client_ca_path.as_deref()
     }
+
+    fn batch_max_concurrency(&self) -> usize {
+        self.data_plane.batch_max_concurrency
+    }
+
+    fn decision_log_enabled(&self) -> bool {
+        self.data_plane.decision_log_enabled
+    }
+
+    fn decision_log_capacity(&self) -> usize {
+        self.data_plane.decision_log_capacity
+    }
+
+    fn decision_log_batch_size(&self) -> usize {
+        self.data_plane.decision_log_batch_size
+    }
+
+    fn decision_log_flush_interval_ms(&self) -> u64 {
+        self.data_plane.decision_log_flush_interval_ms
+    }
+
+    fn shared_secret_identity_sources_enabled(&self) -> bool {
+        self.data_plane.shared_secret_identity_sources_enabled
+    }
 }
 
 impl fmt::Display for Settings {